@@ -2,13 +2,21 @@
 extern crate rocket;
 
 use api::{
-    booking_handlers, club_handlers, game_handlers, person_handlers, recorded_data_handlers,
-    recording_session_handlers, team_handlers, training_handlers, user_handlers,
+    booking_handlers, camera_handlers, club_handlers, comment_handlers, game_handlers,
+    highlight_handlers, media_handlers, person_handlers, recorded_data_handlers,
+    recording_session_handlers, storage_handlers, team_handlers, training_handlers, user_handlers,
 };
+use application::{
+    db_entities::{storage::purge, video::highlight},
+    player::{blocking::run_blocking, player::StreamMap, scheduler},
+    secrets,
+};
+use infrastructure::storage::init_storage;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::fs::FileServer;
 use rocket::http::Header;
 use rocket::tokio::sync::Mutex;
+use rocket::tokio::task;
 use rocket::{
     config::LogLevel,
     fairing::AdHoc,
@@ -20,7 +28,6 @@ use rocket::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr};
-use std::process::Child;
 use std::sync::Arc;
 use utoipa::{
     openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
@@ -28,9 +35,6 @@ use utoipa::{
 };
 use utoipa_swagger_ui::SwaggerUi;
 
-type StreamMap = Arc<Mutex<HashMap<String, Child>>>;
-type Cams = Arc<Mutex<Vec<(String, String)>>>;
-
 #[derive(OpenApi)]
 #[openapi(
     info(
@@ -49,7 +53,9 @@ type Cams = Arc<Mutex<Vec<(String, String)>>>;
         (name = "Partite", description = "Operazioni relative alle partite e alle formazioni"),
         (name = "Allenamenti", description = "Operazioni relative agli allenamenti"),
         (name = "Sessioni di registrazione", description = "Operazioni relative alle sessioni di registrazione"),
-        (name = "Dati registrati", description = "Operazioni relative ai dati registrati dal sistema")
+        (name = "Telecamere", description = "Operazioni relative alle telecamere"),
+        (name = "Dati registrati", description = "Operazioni relative ai dati registrati dal sistema"),
+        (name = "Archivio", description = "Operazioni relative alla conservazione dei video e allo spazio occupato")
     ),
     paths(
         person_handlers::find_person_handler,
@@ -106,19 +112,92 @@ type Cams = Arc<Mutex<Vec<(String, String)>>>;
         recording_session_handlers::list_recording_sessions_by_booking_handler,
         recording_session_handlers::update_recording_session_handler,
         recording_session_handlers::delete_recording_session_handler,
+        recording_session_handlers::list_session_angles_handler,
+
+        camera_handlers::create_camera_handler,
+        camera_handlers::find_camera_handler,
+        camera_handlers::list_cameras_handler,
+        camera_handlers::update_camera_handler,
+        camera_handlers::delete_camera_handler,
+        camera_handlers::probe_camera_handler,
 
         recorded_data_handlers::list_videos_by_booking_handler,
         recorded_data_handlers::find_video_handler,
         recorded_data_handlers::delete_video_handler,
         recorded_data_handlers::create_screenshot_handler,
         recorded_data_handlers::delete_screenshot_handler,
+        recorded_data_handlers::video_file_handler,
+        recorded_data_handlers::sign_video_handler,
+        recorded_data_handlers::screenshot_file_handler,
+        recorded_data_handlers::create_thumbnails_handler,
+        recorded_data_handlers::update_video_metadata_handler,
+        recorded_data_handlers::poster_handler,
+        recorded_data_handlers::sprite_handler,
+        recorded_data_handlers::thumbnails_track_handler,
+        recorded_data_handlers::chapters_track_handler,
+        recorded_data_handlers::chapters_metadata_handler,
+        recorded_data_handlers::chapters_video_handler,
+        recorded_data_handlers::create_vod_handler,
+        recorded_data_handlers::sign_vod_handler,
         recorded_data_handlers::create_timestamp_handler,
+        recorded_data_handlers::list_timestamps_handler,
+        recorded_data_handlers::find_timestamp_handler,
+        recorded_data_handlers::update_timestamp_handler,
         recorded_data_handlers::delete_timestamp_handler,
+        recorded_data_handlers::list_player_moments_handler,
+        recorded_data_handlers::create_annotation_handler,
+        recorded_data_handlers::list_annotations_handler,
+        recorded_data_handlers::find_annotation_handler,
+        recorded_data_handlers::update_annotation_handler,
+        recorded_data_handlers::delete_annotation_handler,
+        recorded_data_handlers::annotated_screenshot_handler,
+        recorded_data_handlers::annotated_timestamp_handler,
         recorded_data_handlers::create_clip_handler,
         recorded_data_handlers::share_video_handler,
+        recorded_data_handlers::list_video_shares_handler,
+        recorded_data_handlers::update_video_share_handler,
+        recorded_data_handlers::revoke_video_share_handler,
+        recorded_data_handlers::update_video_team_share_handler,
+        recorded_data_handlers::revoke_video_team_share_handler,
+        recorded_data_handlers::update_video_club_share_handler,
+        recorded_data_handlers::revoke_video_club_share_handler,
+        recorded_data_handlers::pin_video_handler,
+        recorded_data_handlers::unpin_video_handler,
+
+        recorded_data_handlers::start_camera_stream_handler,
+        recorded_data_handlers::stop_camera_stream_handler,
+        recorded_data_handlers::start_session_streams_handler,
+        recorded_data_handlers::stop_session_streams_handler,
+        recorded_data_handlers::list_active_streams_handler,
+        recorded_data_handlers::sign_camera_stream_handler,
 
-        recorded_data_handlers::end_streams_capture,
-        recorded_data_handlers::init_streams_capture,
+        media_handlers::dash_file_handler,
+        media_handlers::signed_dash_file_handler,
+
+        storage_handlers::storage_usage_handler,
+        storage_handlers::list_expiring_videos_handler,
+        storage_handlers::list_expiration_notices_handler,
+        storage_handlers::create_retention_rule_handler,
+        storage_handlers::list_retention_rules_handler,
+        storage_handlers::update_retention_rule_handler,
+        storage_handlers::delete_retention_rule_handler,
+        storage_handlers::create_storage_quota_handler,
+        storage_handlers::list_storage_quotas_handler,
+        storage_handlers::update_storage_quota_handler,
+        storage_handlers::delete_storage_quota_handler,
+        highlight_handlers::create_highlight_job_handler,
+        highlight_handlers::list_highlight_jobs_handler,
+        highlight_handlers::find_highlight_job_handler,
+        comment_handlers::create_video_comment_handler,
+        comment_handlers::reply_video_comment_handler,
+        comment_handlers::list_video_comments_handler,
+        comment_handlers::find_video_comment_handler,
+        comment_handlers::update_video_comment_handler,
+        comment_handlers::video_comment_history_handler,
+        comment_handlers::delete_video_comment_handler,
+        comment_handlers::resolve_video_comment_handler,
+        comment_handlers::unresolve_video_comment_handler,
+        comment_handlers::list_video_comment_mentions_handler,
     ),
     modifiers(&SecurityAddon)
 )]
@@ -187,6 +266,7 @@ impl Fairing for Cors {
 #[launch]
 fn rocket() -> _ {
     let streams: StreamMap = Arc::new(Mutex::new(HashMap::new()));
+    let scheduler_streams = streams.clone();
 
     match dotenvy::dotenv() {
         Ok(_) => {}
@@ -212,9 +292,59 @@ fn rocket() -> _ {
     rocket::custom(figment)
         .attach(AdHoc::config::<RocketConfig>())
         .attach(Cors)
+        .attach(AdHoc::try_on_ignite("Media storage", |rocket| {
+            Box::pin(async move {
+                // Verifica dell'archivio dei file multimediali, senza il quale il server non viene avviato
+                match run_blocking(|| init_storage().map_err(|e| e.to_string())).await {
+                    Ok(()) => Ok(rocket),
+                    Err(err) => {
+                        eprintln!("Error initializing the media storage: {}", err);
+                        Err(rocket)
+                    }
+                }
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Secrets rotation", |_| {
+            Box::pin(async move {
+                // Cifratura delle credenziali con la chiave attiva
+                task::spawn_blocking(|| {
+                    if let Err(err) = secrets::rotate_secrets() {
+                        eprintln!("Error rotating secrets: {}", err.message);
+                    }
+                });
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Recording scheduler", |_| {
+            Box::pin(async move {
+                // Avvio e arresto automatico delle registrazioni programmate
+                task::spawn(scheduler::run(scheduler_streams));
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Retention purge", |_| {
+            Box::pin(async move {
+                // Eliminazione periodica dei video scaduti secondo le regole di conservazione
+                task::spawn(purge::run());
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Highlight jobs", |_| {
+            Box::pin(async move {
+                // Ripresa delle compilazioni dei video di highlight interrotte dall'arresto del server
+                task::spawn_blocking(|| {
+                    if let Err(err) = highlight::resume_highlight_jobs() {
+                        eprintln!("Error resuming highlight jobs: {}", err.message);
+                    }
+                });
+            })
+        }))
         .manage(streams)
         .mount("/static", FileServer::from("./static"))
-        .mount("/dash", FileServer::from("./infrastructure/tmp/dash")) // Serve DASH files
+        .mount(
+            "/media",
+            routes![
+                media_handlers::dash_file_handler,
+                media_handlers::signed_dash_file_handler,
+            ],
+        )
         .mount(
             "/person",
             routes![
@@ -299,7 +429,19 @@ fn rocket() -> _ {
                 recording_session_handlers::find_recording_session_handler,
                 recording_session_handlers::list_recording_sessions_by_booking_handler,
                 recording_session_handlers::update_recording_session_handler,
-                recording_session_handlers::delete_recording_session_handler
+                recording_session_handlers::delete_recording_session_handler,
+                recording_session_handlers::list_session_angles_handler,
+            ],
+        )
+        .mount(
+            "/camera",
+            routes![
+                camera_handlers::create_camera_handler,
+                camera_handlers::find_camera_handler,
+                camera_handlers::list_cameras_handler,
+                camera_handlers::update_camera_handler,
+                camera_handlers::delete_camera_handler,
+                camera_handlers::probe_camera_handler,
             ],
         )
         .mount(
@@ -310,17 +452,93 @@ fn rocket() -> _ {
                 recorded_data_handlers::delete_video_handler,
                 recorded_data_handlers::create_screenshot_handler,
                 recorded_data_handlers::delete_screenshot_handler,
+                recorded_data_handlers::video_file_handler,
+                recorded_data_handlers::sign_video_handler,
+                recorded_data_handlers::screenshot_file_handler,
+                recorded_data_handlers::create_thumbnails_handler,
+                recorded_data_handlers::update_video_metadata_handler,
+                recorded_data_handlers::poster_handler,
+                recorded_data_handlers::sprite_handler,
+                recorded_data_handlers::thumbnails_track_handler,
+                recorded_data_handlers::chapters_track_handler,
+                recorded_data_handlers::chapters_metadata_handler,
+                recorded_data_handlers::chapters_video_handler,
+                recorded_data_handlers::create_vod_handler,
+                recorded_data_handlers::sign_vod_handler,
                 recorded_data_handlers::create_timestamp_handler,
+                recorded_data_handlers::list_timestamps_handler,
+                recorded_data_handlers::find_timestamp_handler,
+                recorded_data_handlers::update_timestamp_handler,
                 recorded_data_handlers::delete_timestamp_handler,
+                recorded_data_handlers::list_player_moments_handler,
+                recorded_data_handlers::create_annotation_handler,
+                recorded_data_handlers::list_annotations_handler,
+                recorded_data_handlers::find_annotation_handler,
+                recorded_data_handlers::update_annotation_handler,
+                recorded_data_handlers::delete_annotation_handler,
+                recorded_data_handlers::annotated_screenshot_handler,
+                recorded_data_handlers::annotated_timestamp_handler,
                 recorded_data_handlers::create_clip_handler,
                 recorded_data_handlers::share_video_handler,
+                recorded_data_handlers::list_video_shares_handler,
+                recorded_data_handlers::update_video_share_handler,
+                recorded_data_handlers::revoke_video_share_handler,
+                recorded_data_handlers::update_video_team_share_handler,
+                recorded_data_handlers::revoke_video_team_share_handler,
+                recorded_data_handlers::update_video_club_share_handler,
+                recorded_data_handlers::revoke_video_club_share_handler,
+                recorded_data_handlers::pin_video_handler,
+                recorded_data_handlers::unpin_video_handler,
             ],
         )
         .mount(
             "/player",
             routes![
-                recorded_data_handlers::end_streams_capture,
-                recorded_data_handlers::init_streams_capture,
+                recorded_data_handlers::start_camera_stream_handler,
+                recorded_data_handlers::stop_camera_stream_handler,
+                recorded_data_handlers::start_session_streams_handler,
+                recorded_data_handlers::stop_session_streams_handler,
+                recorded_data_handlers::list_active_streams_handler,
+                recorded_data_handlers::sign_camera_stream_handler,
+            ],
+        )
+        .mount(
+            "/storage",
+            routes![
+                storage_handlers::storage_usage_handler,
+                storage_handlers::list_expiring_videos_handler,
+                storage_handlers::list_expiration_notices_handler,
+                storage_handlers::create_retention_rule_handler,
+                storage_handlers::list_retention_rules_handler,
+                storage_handlers::update_retention_rule_handler,
+                storage_handlers::delete_retention_rule_handler,
+                storage_handlers::create_storage_quota_handler,
+                storage_handlers::list_storage_quotas_handler,
+                storage_handlers::update_storage_quota_handler,
+                storage_handlers::delete_storage_quota_handler,
+            ],
+        )
+        .mount(
+            "/video",
+            routes![
+                comment_handlers::create_video_comment_handler,
+                comment_handlers::reply_video_comment_handler,
+                comment_handlers::list_video_comments_handler,
+                comment_handlers::find_video_comment_handler,
+                comment_handlers::update_video_comment_handler,
+                comment_handlers::video_comment_history_handler,
+                comment_handlers::delete_video_comment_handler,
+                comment_handlers::resolve_video_comment_handler,
+                comment_handlers::unresolve_video_comment_handler,
+                comment_handlers::list_video_comment_mentions_handler,
+            ],
+        )
+        .mount(
+            "/highlight",
+            routes![
+                highlight_handlers::create_highlight_job_handler,
+                highlight_handlers::list_highlight_jobs_handler,
+                highlight_handlers::find_highlight_job_handler,
             ],
         )
         .mount(
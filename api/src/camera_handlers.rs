@@ -0,0 +1,214 @@
+use application::{
+    authentication::JWT,
+    db_entities::camera::{
+        create::authorize_create_camera,
+        delete::authorize_delete_camera,
+        read::{authorize_find_camera, authorize_list_cameras},
+        update::authorize_update_camera,
+    },
+    player::{player::StreamMap, probe::authorize_probe_camera},
+};
+use domain::models::{full_tables::Camera, insertions::NewCamera, others::CameraProbe};
+use rocket::{delete, get, post, put, serde::json::Json};
+use shared::response_models::ApiError;
+
+/// Inserisce una nuova telecamera
+///
+/// Dopo i dovuti controlli sui dati, inserisce la nuova telecamera nel database.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/camera",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Telecamere"],
+    responses(
+        (status = CREATED, description = "Telecamera inserita con successo", body = Camera, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/", data = "<camera>")]
+pub fn create_camera_handler(
+    key: Result<JWT, ApiError>,
+    camera: Json<NewCamera>,
+) -> Result<Json<Camera>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_camera(key.claims, camera.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Restituisce una telecamera
+///
+/// Restituisce una telecamera dato il suo ID.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/camera",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Telecamere"],
+    responses(
+        (status = OK, description = "Telecamera trovata con successo", body = Camera, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Telecamera non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<camera_id>")]
+pub fn find_camera_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+) -> Result<Json<Camera>, ApiError> {
+    let key = key?;
+
+    let res = authorize_find_camera(key.claims, camera_id)?;
+    Ok(Json(res))
+}
+
+/// Restituisce la lista delle telecamere
+///
+/// Restituisce tutte le telecamere presenti nel sistema, comprese quelle disabilitate.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/camera",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Telecamere"],
+    responses(
+        (status = OK, description = "Telecamere trovate con successo", body = [Camera], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/")]
+pub fn list_cameras_handler(key: Result<JWT, ApiError>) -> Result<Json<Vec<Camera>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_cameras(key.claims)?;
+    Ok(Json(res))
+}
+
+/// Aggiorna i dati di una telecamera
+///
+/// Dopo i dovuti controlli sui dati, vengono aggiornati i dati della telecamera.
+/// Se la telecamera viene disabilitata, la sua stream viene terminata.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/camera",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Telecamere"],
+    responses(
+        (status = OK, description = "Dati aggiornati con successo", body = Camera, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Telecamera non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera da modificare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<camera_id>", data = "<camera>")]
+pub async fn update_camera_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+    camera: Json<NewCamera>,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<Camera>, ApiError> {
+    let key = key?;
+
+    let res = authorize_update_camera(key.claims, camera_id, camera.into_inner(), state).await?;
+    Ok(Json(res))
+}
+
+/// Elimina una telecamera
+///
+/// Elimina una telecamera dato il suo ID e termina la sua stream, se attiva.
+/// Una telecamera che ha registrato dei video non può essere eliminata, ma solo disabilitata.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/camera",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Telecamere"],
+    responses(
+        (status = OK, description = "Telecamera eliminata con successo", body = Camera, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Telecamera non trovata", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "La telecamera ha registrato dei video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera da eliminare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<camera_id>")]
+pub async fn delete_camera_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<Camera>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_camera(key.claims, camera_id, state).await?;
+    Ok(Json(res))
+}
+
+/// Verifica la raggiungibilità di una telecamera
+///
+/// Esegue le richieste RTSP OPTIONS e DESCRIBE verso la telecamera e restituisce l'esito della verifica:
+/// se la telecamera è raggiungibile, il tempo di risposta, i metodi supportati e le tracce della stream.
+/// Una telecamera non raggiungibile non è considerata un errore, l'esito è riportato nella risposta.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/camera",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Telecamere"],
+    responses(
+        (status = OK, description = "Verifica eseguita", body = CameraProbe, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Telecamera non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera da verificare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<camera_id>/probe")]
+pub async fn probe_camera_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+) -> Result<Json<CameraProbe>, ApiError> {
+    let key = key?;
+
+    let res = authorize_probe_camera(key.claims, camera_id).await?;
+    Ok(Json(res))
+}
@@ -0,0 +1,405 @@
+use application::{
+    authentication::JWT,
+    db_entities::video::comment::{
+        authorize_create_video_comment, authorize_delete_video_comment,
+        authorize_find_video_comment, authorize_list_video_comment_mentions,
+        authorize_list_video_comments, authorize_reply_video_comment,
+        authorize_resolve_video_comment, authorize_update_video_comment,
+        authorize_video_comment_history,
+    },
+};
+use domain::models::{
+    full_tables::{VideoComment, VideoCommentRevision},
+    others::{VideoCommentData, VideoCommentDetails, VideoCommentRequest, VideoCommentThread},
+};
+use rocket::{delete, get, post, put, response::status::Created, serde::json::Json};
+use shared::response_models::ApiError;
+
+/// Inserisce una nuova discussione in un video
+///
+/// Viene inserito il primo commento di una discussione sul video specificato, ancorata a un istante (`start_instant`),
+/// a un intervallo (`start_instant` e `end_instant`) o, senza istanti, al video intero.
+/// Gli utenti menzionati devono avere l'accesso al video in lettura, e trovano il commento tra le proprie menzioni.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = CREATED, description = "Discussione inserita con successo", body = VideoCommentThread, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti o utente menzionato senza accesso al video", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da commentare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/comment", data = "<comment>")]
+pub fn create_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment: Json<VideoCommentRequest>,
+) -> Result<Created<Json<VideoCommentThread>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_video_comment(key.claims, video_id, comment.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Risponde a una discussione di un video
+///
+/// Viene inserita una risposta nella discussione del commento specificato e viene restituita la discussione.
+/// Le risposte a una risposta vengono aggiunte alla stessa discussione.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = CREATED, description = "Risposta inserita con successo", body = VideoCommentThread, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti o utente menzionato senza accesso al video", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del commento"),
+        ("comment_id" = i64, Path, description = "ID del commento al quale rispondere"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/comment/<comment_id>/reply", data = "<comment>")]
+pub fn reply_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+    comment: Json<VideoCommentData>,
+) -> Result<Created<Json<VideoCommentThread>>, ApiError> {
+    let key = key?;
+
+    let res =
+        authorize_reply_video_comment(key.claims, video_id, comment_id, comment.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Restituisce le discussioni di un video
+///
+/// Restituisce le discussioni del video specificato, ciascuna con le sue risposte, ordinate per istante
+/// a partire da quelle sul video intero. Con il parametro `resolved` vengono restituite solo le discussioni
+/// risolte o solo quelle ancora aperte.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Discussioni trovate con successo", body = [VideoCommentThread], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("resolved" = Option<bool>, Query, description = "Se vero solo le discussioni risolte, se falso solo quelle aperte"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/comment?<resolved>")]
+pub fn list_video_comments_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    resolved: Option<bool>,
+) -> Result<Json<Vec<VideoCommentThread>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_video_comments(key.claims, video_id, resolved)?;
+    Ok(Json(res))
+}
+
+/// Restituisce una discussione di un video
+///
+/// Restituisce la discussione alla quale appartiene il commento specificato, che può esserne il primo commento
+/// o una risposta, con tutte le sue risposte.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Discussione trovata con successo", body = VideoCommentThread, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("comment_id" = i64, Path, description = "ID di un commento della discussione"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/comment/<comment_id>")]
+pub fn find_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Json<VideoCommentThread>, ApiError> {
+    let key = key?;
+
+    let res = authorize_find_video_comment(key.claims, video_id, comment_id)?;
+    Ok(Json(res))
+}
+
+/// Modifica un commento di un video
+///
+/// Vengono sostituiti il testo e le menzioni del commento specificato. Se il testo cambia, quello precedente
+/// viene salvato nella cronologia delle modifiche del commento.
+///
+/// ### Chi ha accesso:
+/// - L'autore del commento, se ha ancora l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Commento modificato con successo", body = VideoCommentDetails, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti o utente menzionato senza accesso al video", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del commento"),
+        ("comment_id" = i64, Path, description = "ID del commento da modificare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/comment/<comment_id>", data = "<comment>")]
+pub fn update_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+    comment: Json<VideoCommentData>,
+) -> Result<Json<VideoCommentDetails>, ApiError> {
+    let key = key?;
+
+    let res =
+        authorize_update_video_comment(key.claims, video_id, comment_id, comment.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Restituisce la cronologia delle modifiche di un commento
+///
+/// Restituisce i testi precedenti del commento specificato, dal più vecchio al più recente, ciascuno con
+/// il momento nel quale è stato sostituito.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Cronologia trovata con successo", body = [VideoCommentRevision], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del commento"),
+        ("comment_id" = i64, Path, description = "ID del commento"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/comment/<comment_id>/history")]
+pub fn video_comment_history_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Json<Vec<VideoCommentRevision>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_video_comment_history(key.claims, video_id, comment_id)?;
+    Ok(Json(res))
+}
+
+/// Elimina un commento di un video
+///
+/// Viene eliminato il commento specificato. Eliminando il primo commento di una discussione viene eliminata
+/// l'intera discussione.
+///
+/// ### Chi ha accesso:
+/// - L'autore del commento, se ha ancora l'accesso al video in lettura
+/// - Chiunque abbia il permesso di eliminare il video
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Commento eliminato con successo", body = VideoComment, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del commento"),
+        ("comment_id" = i64, Path, description = "ID del commento da eliminare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/comment/<comment_id>")]
+pub fn delete_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Json<VideoComment>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_video_comment(key.claims, video_id, comment_id)?;
+    Ok(Json(res))
+}
+
+/// Risolve una discussione di un video
+///
+/// La discussione alla quale appartiene il commento specificato viene segnata come risolta dall'utente.
+///
+/// ### Chi ha accesso:
+/// - Chi ha iniziato la discussione, se ha ancora l'accesso al video in lettura
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Discussione risolta con successo", body = VideoCommentThread, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del commento"),
+        ("comment_id" = i64, Path, description = "ID di un commento della discussione"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/comment/<comment_id>/resolved")]
+pub fn resolve_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Json<VideoCommentThread>, ApiError> {
+    let key = key?;
+
+    let res = authorize_resolve_video_comment(key.claims, video_id, comment_id, true)?;
+    Ok(Json(res))
+}
+
+/// Riapre una discussione di un video
+///
+/// La discussione alla quale appartiene il commento specificato torna a essere aperta.
+///
+/// ### Chi ha accesso:
+/// - Chi ha iniziato la discussione, se ha ancora l'accesso al video in lettura
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Discussione riaperta con successo", body = VideoCommentThread, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o commento non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del commento"),
+        ("comment_id" = i64, Path, description = "ID di un commento della discussione"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/comment/<comment_id>/resolved")]
+pub fn unresolve_video_comment_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Json<VideoCommentThread>, ApiError> {
+    let key = key?;
+
+    let res = authorize_resolve_video_comment(key.claims, video_id, comment_id, false)?;
+    Ok(Json(res))
+}
+
+/// Restituisce i commenti nei quali l'utente è stato menzionato
+///
+/// Restituisce i commenti, dal più recente, nei quali l'utente autenticato è stato menzionato, dei soli video
+/// che l'utente può ancora leggere.
+///
+/// ### Chi ha accesso:
+/// - Qualsiasi utente autenticato
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Menzioni trovate con successo", body = [VideoCommentDetails], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/comment/mentions")]
+pub fn list_video_comment_mentions_handler(
+    key: Result<JWT, ApiError>,
+) -> Result<Json<Vec<VideoCommentDetails>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_video_comment_mentions(key.claims)?;
+    Ok(Json(res))
+}
@@ -0,0 +1,115 @@
+use application::{
+    authentication::JWT,
+    db_entities::video::highlight::{
+        authorize_create_highlight_job, authorize_find_highlight_job, authorize_list_highlight_jobs,
+    },
+};
+use domain::models::{
+    full_tables::HighlightJob,
+    others::{HighlightJobDetails, HighlightRequest},
+};
+use rocket::{get, post, response::status::Created, serde::json::Json};
+use shared::response_models::ApiError;
+
+/// Richiede la compilazione di un video di highlight
+///
+/// Compila in background un unico video con le parti dei video indicate da segnaposti e clip, anche di video
+/// e telecamere diversi, una dopo l'altra nell'ordine indicato. Ogni parte può avere un pre-roll e un post-roll,
+/// in millisecondi, e può essere preceduta da un titolo. La parte di un segnaposto va dal pre-roll prima
+/// al post-roll dopo il suo istante, quindi richiede almeno uno dei due.
+///
+/// Lo stato della compilazione può essere seguito con l'ID restituito: al termine il nuovo video è collegato
+/// a ciascuno dei video dai quali è stato ottenuto, come una clip, e l'utente ne è il proprietario.
+///
+/// ### Chi ha accesso:
+/// - Chiunque abbia l'accesso in scrittura a tutti i video dai quali vengono estratte le parti
+#[utoipa::path(
+    context_path = "/highlight",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = CREATED, description = "Compilazione richiesta con successo", body = HighlightJobDetails, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Segnaposto, clip o video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INSUFFICIENT_STORAGE, description = "Le squadre di uno dei video o le loro società sportive hanno esaurito la quota di spazio", body = ApiError, content_type = "application/json")
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/", data = "<request>")]
+pub fn create_highlight_job_handler(
+    key: Result<JWT, ApiError>,
+    request: Json<HighlightRequest>,
+) -> Result<Created<Json<HighlightJobDetails>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_highlight_job(key.claims, request.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Restituisce le richieste di compilazione di video di highlight
+///
+/// Restituisce le richieste dell'utente, dalla più recente.
+///
+/// ### Chi ha accesso:
+/// - Chiunque, limitatamente alle sue richieste
+#[utoipa::path(
+    context_path = "/highlight",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Richieste trovate con successo", body = [HighlightJob], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/")]
+pub fn list_highlight_jobs_handler(
+    key: Result<JWT, ApiError>,
+) -> Result<Json<Vec<HighlightJob>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_highlight_jobs(key.claims)?;
+    Ok(Json(res))
+}
+
+/// Restituisce una richiesta di compilazione di un video di highlight
+///
+/// Restituisce lo stato della compilazione (`pending`, `running`, `completed` o `failed`) e le parti che
+/// compongono il video. Quando la compilazione è completata contiene l'ID del video prodotto, quando
+/// non è riuscita il motivo.
+///
+/// ### Chi ha accesso:
+/// - L'utente che ha richiesto il video di highlight
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/highlight",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Richiesta trovata con successo", body = HighlightJobDetails, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Richiesta non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("job_id" = i64, Path, description = "ID della richiesta di compilazione"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<job_id>")]
+pub fn find_highlight_job_handler(
+    key: Result<JWT, ApiError>,
+    job_id: i64,
+) -> Result<Json<HighlightJobDetails>, ApiError> {
+    let key = key?;
+
+    let res = authorize_find_highlight_job(key.claims, job_id)?;
+    Ok(Json(res))
+}
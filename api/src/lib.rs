@@ -1,9 +1,14 @@
 pub mod booking_handlers;
+pub mod camera_handlers;
 pub mod club_handlers;
+pub mod comment_handlers;
 pub mod game_handlers;
+pub mod highlight_handlers;
+pub mod media_handlers;
 pub mod person_handlers;
 pub mod recorded_data_handlers;
 pub mod recording_session_handlers;
+pub mod storage_handlers;
 pub mod team_handlers;
 pub mod training_handlers;
 pub mod user_handlers;
@@ -0,0 +1,378 @@
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+
+use application::{
+    authentication::JWT,
+    db_entities::video::file::MediaFile,
+    player::media::{authorize_dash_file, dash_file_not_found, signed_dash_file, DashFile},
+};
+use infrastructure::storage::{presign_duration, read, storage, StorageError};
+use rocket::{
+    fs::NamedFile,
+    get,
+    http::{ContentType, Status},
+    request::{FromRequest, Outcome, Request},
+    response::{self, Redirect, Responder, Response},
+    tokio::{
+        fs::File,
+        io::{AsyncReadExt, AsyncSeekExt, Take},
+        task,
+    },
+};
+use shared::response_models::{ApiError, ApiErrorType};
+
+/// Restituisce un file di una diretta o di un video on demand
+///
+/// Restituisce il manifest o un segmento DASH della diretta di una telecamera o di un video pubblicato on demand.
+/// Il percorso inizia con la cartella della telecamera, ad esempio `CAM1/manifest.mpd`,
+/// o con quella del video, ad esempio `video_1/manifest.mpd`.
+///
+/// ### Chi ha accesso:
+/// - Per le dirette, gli amministratori, chi può controllare la telecamera e chi ha accesso
+///   a una sessione di registrazione in corso che utilizza la telecamera
+/// - Per i video on demand, chiunque abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/media",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "File DASH trovato con successo"),
+        (status = TEMPORARY_REDIRECT, description = "Il segmento è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "File DASH non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("path" = String, Path, description = "Percorso del file DASH, a partire dalla cartella della telecamera o del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/dash/<path..>")]
+pub async fn dash_file_handler(
+    key: Result<JWT, ApiError>,
+    path: PathBuf,
+) -> Result<DashResponse, ApiError> {
+    let key = key?;
+
+    let dash_file = authorize_dash_file(key.claims, path.clone())?;
+    open_dash_file(&path, dash_file).await
+}
+
+/// Restituisce un file di una diretta o di un video on demand tramite un URL firmato
+///
+/// Restituisce il manifest o un segmento DASH della diretta di una telecamera o di un video on demand
+/// senza l'header di autenticazione,
+/// per i client che non possono impostarlo, come gli elementi `<video>` e dash.js.
+/// Il token è ottenuto insieme all'URL firmato del manifest e fa parte del percorso,
+/// così che gli URL relativi dei segmenti lo contengano.
+///
+/// ### Chi ha accesso:
+/// - Chi possiede un token valido e non scaduto per la diretta della telecamera o per il video
+#[utoipa::path(
+    context_path = "/media",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "File DASH trovato con successo"),
+        (status = TEMPORARY_REDIRECT, description = "Il segmento è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = UNAUTHORIZED, description = "Token non valido o scaduto", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "Il token non dà accesso al file richiesto", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "File DASH non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("token" = String, Path, description = "Token dell'URL firmato"),
+        ("path" = String, Path, description = "Percorso del file DASH, a partire dalla cartella della telecamera o del video"),
+    )
+)]
+#[get("/signed/<token>/<path..>")]
+pub async fn signed_dash_file_handler(
+    token: &str,
+    path: PathBuf,
+) -> Result<DashResponse, ApiError> {
+    let dash_file = signed_dash_file(token, path.clone())?;
+    open_dash_file(&path, dash_file).await
+}
+
+/// File DASH inviato al client: letto dal disco, scaricato dall'archivio dei file multimediali
+/// o sostituito da un reindirizzamento al suo URL firmato nell'archivio.
+pub enum DashResponse {
+    File(ContentType, NamedFile),
+    Content(ContentType, Vec<u8>),
+    Redirect(Redirect),
+}
+
+impl<'r> Responder<'r, 'static> for DashResponse {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        match self {
+            DashResponse::File(content_type, file) => (content_type, file).respond_to(request),
+            DashResponse::Content(content_type, content) => {
+                (content_type, content).respond_to(request)
+            }
+            DashResponse::Redirect(redirect) => redirect.respond_to(request),
+        }
+    }
+}
+
+// Invia il file con il tipo di contenuto previsto da DASH, che Rocket non riconosce dall'estensione
+async fn open_dash_file(path: &Path, dash_file: DashFile) -> Result<DashResponse, ApiError> {
+    let content_type = match path.extension().and_then(|e| e.to_str()) {
+        Some("mpd") => ContentType::new("application", "dash+xml"),
+        Some("m4s") => ContentType::new("video", "iso.segment"),
+        Some(extension) => ContentType::from_extension(extension).unwrap_or(ContentType::Binary),
+        None => ContentType::Binary,
+    };
+
+    let file_location = match dash_file {
+        DashFile::Live(file_location) => file_location,
+        DashFile::OnDemand(key) => match storage().local_path(&key) {
+            Some(file_location) => file_location,
+            None if content_type == ContentType::new("application", "dash+xml") => {
+                // Il manifest viene inviato dal server, così che gli URL relativi dei segmenti
+                // puntino al server, che li reindirizza ai loro URL firmati nell'archivio
+                let content = task::spawn_blocking(move || read(&key))
+                    .await
+                    .map_err(|_| dash_file_not_found(path))?
+                    .map_err(|e| storage_error(path, e))?;
+
+                return Ok(DashResponse::Content(content_type, content));
+            }
+            None => {
+                let url = storage()
+                    .presign(&key, presign_duration(), None)
+                    .map_err(|e| storage_error(path, e))?
+                    .ok_or_else(|| dash_file_not_found(path))?;
+
+                return Ok(DashResponse::Redirect(Redirect::temporary(url)));
+            }
+        },
+    };
+
+    match NamedFile::open(file_location).await {
+        Ok(file) => Ok(DashResponse::File(content_type, file)),
+        Err(_) => Err(dash_file_not_found(path)),
+    }
+}
+
+// Errore dell'archivio dei file multimediali durante la lettura di un file
+fn storage_error(path: &Path, error: StorageError) -> ApiError {
+    match error {
+        StorageError::NotFound(_) => return dash_file_not_found(path),
+        error => {
+            return ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while reading {} - {}", path.display(), error),
+            };
+        }
+    }
+}
+
+/// Header `Range` della richiesta, se presente.
+pub struct ByteRange(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ByteRange {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, ()> {
+        Outcome::Success(ByteRange(
+            req.headers()
+                .get_one("Range")
+                .map(|range| range.to_string()),
+        ))
+    }
+}
+
+/// File inviato al client: letto dal disco, o sostituito da un reindirizzamento al suo URL firmato
+/// se l'archivio dei file multimediali è remoto.
+pub enum FileResponse {
+    Local(FilePart),
+    Remote(Redirect),
+    /// Il file viene ancora preparato in background: `202 Accepted`, con l'header `Retry-After`
+    /// che indica dopo quanti secondi ripetere la richiesta
+    Pending,
+}
+
+// Secondi dopo i quali ripetere la richiesta di un file ancora in preparazione
+const PENDING_RETRY_SECONDS: u32 = 5;
+
+impl<'r> Responder<'r, 'static> for FileResponse {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        match self {
+            FileResponse::Local(file) => file.respond_to(request),
+            FileResponse::Remote(redirect) => redirect.respond_to(request),
+            FileResponse::Pending => Response::build()
+                .status(Status::Accepted)
+                .raw_header("Retry-After", PENDING_RETRY_SECONDS.to_string())
+                .ok(),
+        }
+    }
+}
+
+/// File sul disco inviato per intero o in parte, secondo l'header `Range` della richiesta.
+///
+/// Consente ai browser di spostarsi all'interno di un video senza scaricarlo tutto.
+pub struct FilePart {
+    status: Status,
+    content_type: ContentType,
+    content_disposition: String,
+    content_range: Option<String>,
+    length: u64,
+    body: Option<Take<File>>,
+}
+
+impl<'r> Responder<'r, 'static> for FilePart {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .status(self.status)
+            .header(self.content_type)
+            .raw_header("Accept-Ranges", "bytes")
+            .raw_header("Content-Disposition", self.content_disposition);
+
+        if let Some(content_range) = self.content_range {
+            response.raw_header("Content-Range", content_range);
+        }
+
+        if let Some(body) = self.body {
+            // La lunghezza è nota, ma la parte del file da inviare non può essere letta con seek
+            response
+                .raw_header("Content-Length", self.length.to_string())
+                .streamed_body(body);
+        }
+
+        response.ok()
+    }
+}
+
+/// Prepara l'invio del file, per intero o nella parte richiesta dall'header `Range`.
+///
+/// Con `download` il file viene proposto come download invece che mostrato dal browser.
+/// Sono supportati solo gli intervalli singoli: con più intervalli il file viene inviato per intero.
+///
+/// Se l'archivio dei file multimediali è remoto il client viene reindirizzato all'URL firmato del file,
+/// e gli intervalli vengono gestiti dall'archivio.
+pub async fn serve_file(
+    file: MediaFile,
+    range: ByteRange,
+    download: bool,
+) -> Result<FileResponse, ApiError> {
+    let file_not_found = |e: std::io::Error| ApiError {
+        http_status: Status::NotFound,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!("Error - File {} not available - {}", file.file_name, e),
+    };
+
+    let content_disposition = format!(
+        "{}; filename=\"{}\"",
+        if download { "attachment" } else { "inline" },
+        file.file_name
+    );
+
+    let location = match storage().local_path(&file.key) {
+        Some(location) => location,
+        None => {
+            let url = storage()
+                .presign(&file.key, presign_duration(), Some(&content_disposition))
+                .map_err(|e| ApiError {
+                    http_status: Status::InternalServerError,
+                    error_code: 123, // TODO organizzare i codici di errore
+                    error_type: ApiErrorType::ApplicationError,
+                    message: format!("Error while signing file {} - {}", file.file_name, e),
+                })?
+                .ok_or_else(|| {
+                    file_not_found(std::io::Error::from(std::io::ErrorKind::NotFound))
+                })?;
+
+            return Ok(FileResponse::Remote(Redirect::temporary(url)));
+        }
+    };
+
+    let mut body = File::open(&location).await.map_err(file_not_found)?;
+    let file_length = body.metadata().await.map_err(file_not_found)?.len();
+
+    let content_type = location
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(ContentType::from_extension)
+        .unwrap_or(ContentType::Binary);
+
+    let (status, start, end) = match range.0.as_deref().map(|r| parse_range(r, file_length)) {
+        Some(Some(Ok((start, end)))) => (Status::PartialContent, start, end),
+        Some(Some(Err(()))) => {
+            return Ok(FileResponse::Local(FilePart {
+                status: Status::RangeNotSatisfiable,
+                content_type,
+                content_disposition,
+                content_range: Some(format!("bytes */{}", file_length)),
+                length: 0,
+                body: None,
+            }));
+        }
+        // Senza un intervallo valido il file viene inviato per intero
+        _ => (Status::Ok, 0, file_length.saturating_sub(1)),
+    };
+
+    let length = if file_length == 0 { 0 } else { end - start + 1 };
+
+    body.seek(SeekFrom::Start(start))
+        .await
+        .map_err(|e| ApiError {
+            http_status: Status::InternalServerError,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: format!("Error while reading file {} - {}", file.file_name, e),
+        })?;
+
+    return Ok(FileResponse::Local(FilePart {
+        status,
+        content_type,
+        content_disposition,
+        content_range: (status == Status::PartialContent)
+            .then(|| format!("bytes {}-{}/{}", start, end, file_length)),
+        length,
+        body: Some(body.take(length)),
+    }));
+}
+
+// Interpreta un header `Range` con un solo intervallo di byte (`bytes=0-99`, `bytes=100-` o `bytes=-100`).
+// Restituisce None se l'header va ignorato e un errore se l'intervallo è fuori dal file.
+fn parse_range(range: &str, file_length: u64) -> Option<Result<(u64, u64), ()>> {
+    let range = range.trim().strip_prefix("bytes=")?;
+
+    if range.contains(',') {
+        return None;
+    }
+
+    let (start, end) = range.trim().split_once('-')?;
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", "") => return None,
+        // Ultimi byte del file
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            if suffix == 0 || file_length == 0 {
+                return Some(Err(()));
+            }
+            (file_length.saturating_sub(suffix), file_length - 1)
+        }
+        (start, "") => (start.parse::<u64>().ok()?, file_length.saturating_sub(1)),
+        (start, end) => {
+            let start = start.parse::<u64>().ok()?;
+            let end = end.parse::<u64>().ok()?;
+            if end < start {
+                return None;
+            }
+            (start, end.min(file_length.saturating_sub(1)))
+        }
+    };
+
+    if start >= file_length {
+        return Some(Err(()));
+    }
+
+    return Some(Ok((start, end)));
+}
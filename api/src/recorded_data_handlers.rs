@@ -1,47 +1,102 @@
+use crate::media_handlers::{serve_file, ByteRange, FileResponse};
 use application::authentication::JWT;
-use application::player::player;
-use domain::models::full_tables::{Screenshot, Video};
-use domain::models::others::{NewClip, NewScreenshot, NewTimestamp, UserList};
-use rocket::tokio::sync::Mutex;
-use rocket::{delete, get, post, serde::json::Json};
-use shared::response_models::ApiError;
-use std::collections::HashMap;
-use std::process::Child;
-use std::sync::Arc;
-
-type StreamMap = Arc<Mutex<HashMap<String, Child>>>;
-type Cams = Arc<Mutex<Vec<(String, String)>>>;
+use application::db_entities::storage::retention::authorize_pin_video;
+use application::db_entities::video::annotation::{
+    authorize_annotated_screenshot, authorize_annotated_time_marker, authorize_create_annotation,
+    authorize_delete_annotation, authorize_find_annotation, authorize_list_annotations,
+    authorize_update_annotation, signed_annotated_screenshot, signed_annotated_time_marker,
+};
+use application::db_entities::video::chapters::{
+    authorize_chapters, authorize_chapters_video, signed_chapters, signed_chapters_video,
+    ChaptersFormat,
+};
+use application::db_entities::video::clip::authorize_create_clip;
+use application::db_entities::video::delete::authorize_delete_video;
+use application::db_entities::video::file::{
+    authorize_screenshot_file, authorize_sign_video, authorize_video_file, signed_screenshot_file,
+    signed_video_file,
+};
+use application::db_entities::video::metadata::authorize_update_video_metadata;
+use application::db_entities::video::read::{
+    authorize_find_video, authorize_list_videos_by_booking,
+};
+use application::db_entities::video::screenshot::{
+    authorize_create_screenshot, authorize_delete_screenshot,
+};
+use application::db_entities::video::share::{
+    authorize_list_video_shares, authorize_revoke_video_club, authorize_revoke_video_team,
+    authorize_revoke_video_user, authorize_share_video, authorize_update_video_club,
+    authorize_update_video_team, authorize_update_video_user,
+};
+use application::db_entities::video::thumbnails::{
+    authorize_create_thumbnails, authorize_thumbnail_image, authorize_thumbnails_track,
+    signed_thumbnail_image, signed_thumbnails_track, ThumbnailImage,
+};
+use application::db_entities::video::time_marker::{
+    authorize_create_time_marker, authorize_delete_time_marker, authorize_find_time_marker,
+    authorize_list_player_moments, authorize_list_time_markers, authorize_update_time_marker,
+};
+use application::db_entities::video::vod::{authorize_create_vod, authorize_sign_vod};
+use application::player::{
+    live::{
+        authorize_list_active_streams, authorize_start_camera_stream,
+        authorize_start_session_streams, authorize_stop_camera_stream,
+        authorize_stop_session_streams,
+    },
+    media::authorize_sign_camera_stream,
+    player::StreamMap,
+};
+use domain::models::full_tables::{Annotation, Screenshot, Video, VideoClub, VideoTeam, VideoUser};
+use domain::models::others::{
+    ActiveStream, AnnotationData, AnnotationRequest, ClipRequest, PlayerMoment,
+    PlayerMomentsFilter, ScreenshotRequest, SignedUrl, TimeMarkerData, TimeMarkerDetails,
+    TimeMarkerRequest, VideoPermissions, VideoShareRequest, VideoShares, VideoSummary,
+};
+use rocket::{
+    delete, get, http::ContentType, post, put, response::status::Created, serde::json::Json,
+};
+use shared::{response_models::ApiError, NaiveDateTimeForm};
 
 /// Restituisce la lista dei video per una prenotazione
 ///
-/// Restituisce la lista con tutti i video registrati durante la partita o allenamento associati alla prenotazione.
+/// Restituisce la lista con tutti i video registrati durante la partita o allenamento associati alla prenotazione,
+/// comprese le clip, con il numero di clip e di segnaposti di ciascun video.
+/// I video possono essere filtrati per telecamera e per sessione di registrazione.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
 /// - Un allenatore della squadra
 /// - Un giocatore della squadra
+/// - Chiunque altro abbia l'accesso in lettura a qualche video, limitatamente a quei video
 #[utoipa::path(
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = OK, description = "Video della prenotazione trovati con successo", body = [Video], content_type = "application/json"),
+        (status = OK, description = "Video della prenotazione trovati con successo", body = [VideoSummary], content_type = "application/json"),
         (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
     ),
     params(
         ("booking_id" = i64, Path, description = "ID della prenotazione da cercare"),
+        ("camera_id" = Option<i64>, Query, description = "ID della telecamera che ha registrato i video"),
+        ("session_id" = Option<i64>, Query, description = "ID della sessione di registrazione dei video"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[get("/list-by-booking/<booking_id>")]
+#[get("/list-by-booking/<booking_id>?<camera_id>&<session_id>")]
 pub fn list_videos_by_booking_handler(
     key: Result<JWT, ApiError>,
     booking_id: i64,
-) -> Result<String, ApiError> {
-    todo!()
+    camera_id: Option<i64>,
+    session_id: Option<i64>,
+) -> Result<Json<Vec<VideoSummary>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_videos_by_booking(key.claims, booking_id, camera_id, session_id)?;
+    Ok(Json(res))
 }
 
 /// Restituisce un video
@@ -71,19 +126,26 @@ pub fn list_videos_by_booking_handler(
     )
 )]
 #[get("/<video_id>")]
-pub fn find_video_handler(key: Result<JWT, ApiError>, video_id: i64) -> Result<String, ApiError> {
-    todo!()
+pub fn find_video_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_find_video(key.claims, video_id)?;
+    Ok(Json(res))
 }
 
 /// Elimina un video
 ///
-/// Elimina un video dato il suo ID.
+/// Elimina un video dato il suo ID, insieme al suo file, ai suoi screenshot e ai suoi segnaposti.
+/// Le clip estratte dal video non vengono eliminate.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
 /// - Un allenatore della squadra
-/// - Un giocatore
-/// - Chiunque altro abbia l'accesso al video in scrittura
+/// - Il proprietario del video
+/// - Chiunque altro abbia il permesso di eliminare il video
 #[utoipa::path(
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
@@ -102,13 +164,19 @@ pub fn find_video_handler(key: Result<JWT, ApiError>, video_id: i64) -> Result<S
     )
 )]
 #[delete("/<video_id>")]
-pub fn delete_video_handler(key: Result<JWT, ApiError>, video_id: i64) -> Result<String, ApiError> {
-    todo!()
+pub fn delete_video_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_video(key.claims, video_id)?;
+    Ok(Json(res))
 }
 
 /// Inserisce un nuovo screenshot associato al video
 ///
-/// Viene inserito un nuovo screenshot associato al video specificato.
+/// Viene estratto il fotogramma del video all'istante indicato e viene inserito un nuovo screenshot associato al video specificato.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
@@ -119,8 +187,8 @@ pub fn delete_video_handler(key: Result<JWT, ApiError>, video_id: i64) -> Result
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = CREATED, description = "Screenshot inserito con successo", body = String),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = CREATED, description = "Screenshot inserito con successo", body = Screenshot, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti o istante oltre la durata del video", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
         (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
@@ -133,17 +201,20 @@ pub fn delete_video_handler(key: Result<JWT, ApiError>, video_id: i64) -> Result
     )
 )]
 #[post("/<video_id>/screenshot", data = "<screenshot>")]
-pub fn create_screenshot_handler(
+pub async fn create_screenshot_handler(
     key: Result<JWT, ApiError>,
     video_id: i64,
-    screenshot: Json<NewScreenshot>,
-) -> Result<String, ApiError> {
-    todo!()
+    screenshot: Json<ScreenshotRequest>,
+) -> Result<Created<Json<Screenshot>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_screenshot(key.claims, video_id, screenshot.into_inner()).await?;
+    Ok(Created::new("").body(Json(res)))
 }
 
 /// Elimina uno screenshot associato a un video
 ///
-/// Viene eliminato lo screenshot associato al video specificato.
+/// Viene eliminato lo screenshot associato al video specificato, insieme alla sua immagine.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
@@ -158,10 +229,11 @@ pub fn create_screenshot_handler(
         (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
-        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+        (status = NOT_FOUND, description = "Video o screenshot non trovato", body = ApiError, content_type = "application/json")
     ),
     params(
         ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("screenshot_id" = i64, Path, description = "ID dello screenshot da eliminare"),
     ),
     security(
         ("jwt_token" = [])
@@ -172,48 +244,161 @@ pub fn delete_screenshot_handler(
     key: Result<JWT, ApiError>,
     video_id: i64,
     screenshot_id: i64,
-) -> Result<String, ApiError> {
-    todo!()
+) -> Result<Json<Screenshot>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_screenshot(key.claims, video_id, screenshot_id)?;
+    Ok(Json(res))
 }
 
-/// Inserisce un nuovo timestamp associato al video
+/// Restituisce il file di un video
 ///
-/// Viene inserito un nuovo timestamp associato al video specificato.
+/// Restituisce il file del video o della clip, per intero o nella parte richiesta con l'header `Range`
+/// (risposta `206 Partial Content`), così che il browser possa spostarsi nel video senza scaricarlo tutto.
+/// Con `download=true` il file viene proposto come download.
+///
+/// Oltre che con l'header di autenticazione, il file può essere richiesto con il parametro `token`
+/// di un URL firmato, per gli elementi `<video>` che non possono inviare header.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
 /// - Un allenatore della squadra
 /// - Un giocatore
-/// - Chiunque altro abbia l'accesso al video in scrittura
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
 #[utoipa::path(
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = CREATED, description = "Timestamp inserito con successo", body = String),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = OK, description = "File del video restituito per intero"),
+        (status = PARTIAL_CONTENT, description = "Parte del file del video richiesta con l'header Range"),
+        (status = TEMPORARY_REDIRECT, description = "Il file è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o file non trovato", body = ApiError, content_type = "application/json"),
+        (status = RANGE_NOT_SATISFIABLE, description = "L'intervallo richiesto è fuori dal file")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da scaricare"),
+        ("download" = Option<bool>, Query, description = "Propone il file come download invece di mostrarlo"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/file?<download>&<token>")]
+pub async fn video_file_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    download: Option<bool>,
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_video_file(token, video_id)?,
+        None => authorize_video_file(key?.claims, video_id)?,
+    };
+
+    serve_file(file, range, download.unwrap_or(false)).await
+}
+
+/// Restituisce un URL firmato di un video
+///
+/// Restituisce un URL del file del video che può essere usato senza l'header di autenticazione
+/// fino alla sua scadenza, ad esempio da un elemento `<video>`.
+/// Lo stesso token, passato come parametro `token`, dà accesso anche alle immagini degli screenshot del video.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "URL firmato generato con successo", body = SignedUrl, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
         (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
     ),
     params(
-        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("video_id" = i64, Path, description = "ID del video"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[post("/<video_id>/timestamp", data = "<timestamp>")]
-pub fn create_timestamp_handler(
+#[get("/<video_id>/signed-url")]
+pub fn sign_video_handler(
     key: Result<JWT, ApiError>,
     video_id: i64,
-    timestamp: Json<NewTimestamp>,
-) -> Result<String, ApiError> {
-    todo!()
+) -> Result<Json<SignedUrl>, ApiError> {
+    let key = key?;
+
+    let res = authorize_sign_video(key.claims, video_id)?;
+    Ok(Json(res))
 }
 
-/// Elimina un timestamp associato a un video
+/// Restituisce l'immagine di uno screenshot
+///
+/// Restituisce l'immagine JPEG dello screenshot, per intero o nella parte richiesta con l'header `Range`.
+/// Con `download=true` l'immagine viene proposta come download.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Immagine dello screenshot restituita per intero"),
+        (status = PARTIAL_CONTENT, description = "Parte dell'immagine richiesta con l'header Range"),
+        (status = TEMPORARY_REDIRECT, description = "Il file è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Screenshot o immagine non trovati", body = ApiError, content_type = "application/json"),
+        (status = RANGE_NOT_SATISFIABLE, description = "L'intervallo richiesto è fuori dal file")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video dello screenshot"),
+        ("screenshot_id" = i64, Path, description = "ID dello screenshot da scaricare"),
+        ("download" = Option<bool>, Query, description = "Propone l'immagine come download invece di mostrarla"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/screenshot/<screenshot_id>/file?<download>&<token>")]
+pub async fn screenshot_file_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    screenshot_id: i64,
+    download: Option<bool>,
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_screenshot_file(token, video_id, screenshot_id)?,
+        None => authorize_screenshot_file(key?.claims, video_id, screenshot_id)?,
+    };
+
+    serve_file(file, range, download.unwrap_or(false)).await
+}
+
+/// Genera le anteprime di un video
 ///
-/// Viene eliminato lo timestamp associato al video specificato.
+/// Genera l'immagine di copertina, le miniature a intervalli regolari e la traccia WebVTT delle miniature del video,
+/// sostituendo quelle già presenti. Le anteprime vengono generate automaticamente al termine di ogni registrazione
+/// e per ogni clip: questo endpoint serve per i video che ne sono privi.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
@@ -224,31 +409,36 @@ pub fn create_timestamp_handler(
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = OK, description = "Timestamp eliminato con successo", body = Screenshot, content_type = "application/json"),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = OK, description = "Anteprime generate con successo", body = Video, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
-        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante la generazione delle anteprime", body = ApiError, content_type = "application/json")
     ),
     params(
-        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("video_id" = i64, Path, description = "ID del video del quale generare le anteprime"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[delete("/<video_id>/timestamp/<timestamp_id>")]
-pub fn delete_timestamp_handler(
+#[post("/<video_id>/thumbnails")]
+pub async fn create_thumbnails_handler(
     key: Result<JWT, ApiError>,
     video_id: i64,
-    timestamp_id: i64,
-) -> Result<String, ApiError> {
-    todo!()
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_thumbnails(key.claims, video_id).await?;
+    Ok(Json(res))
 }
 
-/// Inserisce una nuova clip associata a un video
+/// Aggiorna le caratteristiche tecniche di un video
 ///
-/// Viene salvata una clip relativa al video specificato.
+/// Legge di nuovo con FFprobe durata, risoluzione, frame rate, codec, bitrate e dimensione del file del video.
+/// Le caratteristiche vengono lette automaticamente al termine di ogni registrazione e per ogni clip:
+/// questo endpoint serve per i video registrati in precedenza o per i quali la lettura non è riuscita.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
@@ -259,143 +449,1604 @@ pub fn delete_timestamp_handler(
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = CREATED, description = "Clip inserita con successo", body = String),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = OK, description = "Caratteristiche del video aggiornate con successo", body = Video, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
-        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante la lettura delle caratteristiche del video", body = ApiError, content_type = "application/json")
     ),
     params(
-        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("video_id" = i64, Path, description = "ID del video del quale aggiornare le caratteristiche"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[post("/<video_id>/clip", data = "<clip>")]
-pub fn create_clip_handler(
+#[post("/<video_id>/metadata")]
+pub async fn update_video_metadata_handler(
     key: Result<JWT, ApiError>,
     video_id: i64,
-    clip: Json<NewClip>,
-) -> Result<String, ApiError> {
-    todo!()
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_update_video_metadata(key.claims, video_id).await?;
+    Ok(Json(res))
 }
 
-/// Permette di condividere un video con altri utenti
+/// Restituisce l'immagine di copertina di un video
 ///
-/// Gli utenti specificati vengono aggiunti alla lista di persone che hanno l'accesso al video.
+/// Restituisce l'immagine JPEG da mostrare come copertina del video prima della riproduzione.
+/// Può essere richiesta con il parametro `token` di un URL firmato del video.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
 /// - Un allenatore della squadra
 /// - Un giocatore
-/// - Chiunque altro abbia l'accesso al video per condividere
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
 #[utoipa::path(
     context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = OK, description = "Condivisione avvenuta con successo", body = String),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
-        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = OK, description = "Immagine di copertina trovata con successo"),
+        (status = TEMPORARY_REDIRECT, description = "Il file è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
-        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+        (status = NOT_FOUND, description = "Video non trovato o anteprime non generate", body = ApiError, content_type = "application/json")
     ),
     params(
-        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("video_id" = i64, Path, description = "ID del video"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[post("/<video_id>/share", data = "<users>")]
-pub fn share_video_handler(
+#[get("/<video_id>/poster?<token>")]
+pub async fn poster_handler(
     key: Result<JWT, ApiError>,
     video_id: i64,
-    users: Json<UserList>,
-) -> Result<String, ApiError> {
-    todo!()
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_thumbnail_image(token, video_id, ThumbnailImage::Poster)?,
+        None => authorize_thumbnail_image(key?.claims, video_id, ThumbnailImage::Poster)?,
+    };
+
+    serve_file(file, range, false).await
 }
 
-/// Inizializza la ricezione delle streams.
+/// Restituisce le miniature di un video
 ///
-/// Per ogni entry nell'array cams chiama la funzione stream di player.rs.
+/// Restituisce l'immagine JPEG con le miniature del video a intervalli regolari, disposte in una griglia.
+/// La posizione di ciascuna miniatura è indicata dalla traccia WebVTT delle miniature.
+/// Può essere richiesta con il parametro `token` di un URL firmato del video.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
 /// - Un allenatore della squadra
-/// - Chiunque altro abbia l'accesso alle telecamere
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
 #[utoipa::path(
-    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = OK, description = "Stream avviata con successo", body = String),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
-        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = OK, description = "Miniature trovate con successo"),
+        (status = TEMPORARY_REDIRECT, description = "Il file è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
-        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+        (status = NOT_FOUND, description = "Video non trovato o anteprime non generate", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/sprite?<token>")]
+pub async fn sprite_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_thumbnail_image(token, video_id, ThumbnailImage::Sprite)?,
+        None => authorize_thumbnail_image(key?.claims, video_id, ThumbnailImage::Sprite)?,
+    };
+
+    serve_file(file, range, false).await
+}
+
+/// Restituisce la traccia WebVTT delle miniature di un video
+///
+/// Restituisce la traccia WebVTT che associa a ogni intervallo del video la sua miniatura, usata dai player
+/// per mostrare le anteprime durante lo scorrimento. Le miniature sono indicate con URL relativi a quello della traccia;
+/// se la traccia è richiesta con il parametro `token` di un URL firmato, il token viene aggiunto anche agli URL delle miniature.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Traccia delle miniature trovata con successo", body = String, content_type = "text/vtt"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o anteprime non generate", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/thumbnails.vtt?<token>")]
+pub fn thumbnails_track_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    token: Option<&str>,
+) -> Result<(ContentType, String), ApiError> {
+    let res = match token {
+        Some(token) => signed_thumbnails_track(token, video_id)?,
+        None => authorize_thumbnails_track(key?.claims, video_id)?,
+    };
+
+    Ok((ContentType::new("text", "vtt"), res))
+}
+
+/// Restituisce i capitoli di un video come traccia WebVTT
+///
+/// Restituisce una traccia WebVTT di tipo `chapters`, con un capitolo per ogni segnaposto del video:
+/// ogni capitolo ha il nome del segnaposto, inizia al suo istante e termina al segnaposto successivo
+/// o alla fine del video.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Capitoli esportati con successo", body = String, content_type = "text/vtt"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante la lettura della durata del video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/chapters.vtt?<token>")]
+pub async fn chapters_track_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    token: Option<&str>,
+) -> Result<(ContentType, String), ApiError> {
+    let res = match token {
+        Some(token) => signed_chapters(token, video_id, ChaptersFormat::WebVtt).await?,
+        None => authorize_chapters(key?.claims, video_id, ChaptersFormat::WebVtt).await?,
+    };
+
+    Ok((ContentType::new("text", "vtt"), res))
+}
+
+/// Restituisce i capitoli di un video come file di metadati di FFmpeg
+///
+/// Restituisce un file di metadati di FFmpeg (`;FFMETADATA1`) con il nome del video e un capitolo
+/// per ogni segnaposto, in millisecondi, da usare con l'opzione `-map_chapters` di FFmpeg.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Capitoli esportati con successo", body = String, content_type = "text/plain"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante la lettura della durata del video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/chapters.ffmetadata?<token>")]
+pub async fn chapters_metadata_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    token: Option<&str>,
+) -> Result<(ContentType, String), ApiError> {
+    let res = match token {
+        Some(token) => signed_chapters(token, video_id, ChaptersFormat::FfMetadata).await?,
+        None => authorize_chapters(key?.claims, video_id, ChaptersFormat::FfMetadata).await?,
+    };
+
+    Ok((ContentType::Plain, res))
+}
+
+/// Scarica un video con i capitoli incorporati
+///
+/// Restituisce una copia del file MP4 del video con un capitolo per ogni segnaposto, che i player desktop
+/// (come VLC) mostrano anche senza connessione. La copia viene preparata in background alla prima richiesta,
+/// che riceve `202 Accepted` con l'header `Retry-After` finché la copia non è pronta, e viene riutilizzata
+/// finché i segnaposti non cambiano; un video senza segnaposti viene restituito così com'è.
+/// Come per il file del video sono supportati l'header `Range` e i reindirizzamenti all'archivio remoto.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "File del video con i capitoli restituito per intero"),
+        (status = PARTIAL_CONTENT, description = "Parte del file richiesta con l'header Range"),
+        (status = ACCEPTED, description = "La copia del video con i capitoli viene preparata: ripetere la richiesta dopo i secondi dell'header Retry-After"),
+        (status = TEMPORARY_REDIRECT, description = "Il file è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o file non trovato", body = ApiError, content_type = "application/json"),
+        (status = RANGE_NOT_SATISFIABLE, description = "L'intervallo richiesto è fuori dal file"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante l'inserimento dei capitoli nel video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da scaricare"),
+        ("download" = Option<bool>, Query, description = "Propone il file come download invece di mostrarlo"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[get("/start")]
-pub async fn init_streams_capture(state: &rocket::State<StreamMap>) {
-    match player::list_cameras().await {
-        Ok(cams) => {
-            println!("Camera list saved");
-            let cams_lock = cams.lock().await;
-            for (name, url) in cams_lock.iter() {
-                match player::stream(url.clone(), name.clone(), state).await {
-                    Ok(_) => println!("Started stream for: {}", url),
-                    Err(e) => eprintln!("Error starting stream {}: {}", name, e),
-                }
-            }
-        }
-        Err(e) => eprintln!("Error getting camera list: {}", e),
+#[get("/<video_id>/chapters.mp4?<download>&<token>")]
+pub async fn chapters_video_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    download: Option<bool>,
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_chapters_video(token, video_id).await?,
+        None => authorize_chapters_video(key?.claims, video_id).await?,
+    };
+
+    match file {
+        Some(file) => serve_file(file, range, download.unwrap_or(false)).await,
+        None => Ok(FileResponse::Pending),
     }
 }
 
-/// Termina la ricezione delle streams.
+/// Pubblica un video on demand
 ///
-/// Per ogni entry nell'array state che contiene i processi di ricezione attivi
-/// termina quel processo.
+/// Pubblica il video in formato DASH on demand, sostituendo la versione già presente.
+/// I video registrati vengono pubblicati automaticamente al termine della sessione di registrazione,
+/// prendendo il posto della diretta: questo endpoint serve per i video che ne sono privi.
 ///
 /// ### Chi ha accesso:
 /// - Il responsabile della società sportiva
 /// - Un allenatore della squadra
-/// - Chiunque altro abbia l'accesso alle telecamere
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
 #[utoipa::path(
-    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
     tags = ["Dati registrati"],
     responses(
-        (status = OK, description = "Stream terminata con successo", body = String),
-        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = OK, description = "Video pubblicato con successo", body = Video, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
         (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
         (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
-        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante la pubblicazione del video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da pubblicare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/vod")]
+pub async fn create_vod_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_vod(key.claims, video_id).await?;
+    Ok(Json(res))
+}
+
+/// Restituisce un URL firmato del video on demand
+///
+/// Restituisce un URL del manifest DASH on demand del video che può essere usato senza l'header di autenticazione
+/// fino alla sua scadenza, ad esempio da dash.js, come quello della diretta della telecamera.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "URL firmato generato con successo", body = SignedUrl, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non pubblicato on demand", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video"),
     ),
     security(
         ("jwt_token" = [])
     )
 )]
-#[get("/stop")]
-pub async fn end_streams_capture(state: &rocket::State<StreamMap>) {
-    let mut streams = state.lock().await;
+#[get("/<video_id>/vod/signed-url")]
+pub fn sign_vod_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<SignedUrl>, ApiError> {
+    let key = key?;
 
-    match player::list_cameras().await {
-        Ok(cams) => {
-            let cams_lock = cams.lock().await;
-            for (_name, url) in cams_lock.iter() {
-                if let Some(mut child) = streams.remove(url) {
-                    match child.kill() {
-                        Ok(_) => println!("Stream stopped"),
-                        Err(e) => eprintln!("Failed to stop FFmpeg process: {}", e),
-                    }
-                } else {
-                    println!("Stream not found");
-                }
-            }
-        }
-        Err(e) => eprintln!("Error getting camera list: {}", e),
-    }
+    let res = authorize_sign_vod(key.claims, video_id)?;
+    Ok(Json(res))
+}
+
+/// Inserisce un nuovo timestamp associato al video
+///
+/// Viene inserito un nuovo timestamp (segnaposto) associato al video specificato.
+/// Se `all_angles` è vero, il timestamp viene inserito anche nei video delle altre telecamere della stessa sessione
+/// di registrazione che l'utente può modificare, nello stesso momento della registrazione.
+/// Il timestamp può riferirsi ad alcuni giocatori, a una squadra e a una delle formazioni della partita del video;
+/// se è indicata una formazione, i giocatori devono farne parte e la squadra è quella della formazione.
+/// Vengono restituiti tutti i timestamp inseriti, a partire da quello del video specificato.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = CREATED, description = "Timestamp inserito con successo", body = [TimeMarkerDetails], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/timestamp", data = "<timestamp>")]
+pub fn create_timestamp_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    timestamp: Json<TimeMarkerRequest>,
+) -> Result<Created<Json<Vec<TimeMarkerDetails>>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_time_marker(key.claims, video_id, timestamp.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Restituisce i timestamp di un video
+///
+/// Restituisce la lista dei timestamp associati al video specificato, ordinati per istante.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Timestamp trovati con successo", body = [TimeMarkerDetails], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/timestamp")]
+pub fn list_timestamps_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<Vec<TimeMarkerDetails>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_time_markers(key.claims, video_id)?;
+    Ok(Json(res))
+}
+
+/// Restituisce un timestamp di un video
+///
+/// Restituisce un timestamp associato al video specificato dato il suo ID.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Timestamp trovato con successo", body = TimeMarkerDetails, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o timestamp non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("timestamp_id" = i64, Path, description = "ID del timestamp da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/timestamp/<timestamp_id>")]
+pub fn find_timestamp_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    timestamp_id: i64,
+) -> Result<Json<TimeMarkerDetails>, ApiError> {
+    let key = key?;
+
+    let res = authorize_find_time_marker(key.claims, video_id, timestamp_id)?;
+    Ok(Json(res))
+}
+
+/// Modifica un timestamp di un video
+///
+/// Vengono modificati istante, nome, note, giocatori, squadra e formazione del timestamp associato al video specificato.
+/// I timestamp inseriti negli altri video della sessione di registrazione non vengono modificati.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Timestamp modificato con successo", body = TimeMarkerDetails, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o timestamp non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("timestamp_id" = i64, Path, description = "ID del timestamp da modificare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/timestamp/<timestamp_id>", data = "<timestamp>")]
+pub fn update_timestamp_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    timestamp_id: i64,
+    timestamp: Json<TimeMarkerData>,
+) -> Result<Json<TimeMarkerDetails>, ApiError> {
+    let key = key?;
+
+    let res =
+        authorize_update_time_marker(key.claims, video_id, timestamp_id, timestamp.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Elimina un timestamp associato a un video
+///
+/// Viene eliminato il timestamp associato al video specificato.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Timestamp eliminato con successo", body = TimeMarkerDetails, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o timestamp non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("timestamp_id" = i64, Path, description = "ID del timestamp da eliminare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/timestamp/<timestamp_id>")]
+pub fn delete_timestamp_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    timestamp_id: i64,
+) -> Result<Json<TimeMarkerDetails>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_time_marker(key.claims, video_id, timestamp_id)?;
+    Ok(Json(res))
+}
+
+/// Restituisce i momenti di un giocatore
+///
+/// Restituisce i timestamp di tutti i video nei quali è stato indicato il giocatore, ordinati per data di registrazione,
+/// con la posizione da cui far partire la riproduzione del video (`offset_ms`) e la data e ora della registrazione.
+/// I momenti possono essere filtrati per nome del timestamp (ad esempio il tipo di azione), per squadra e per data dei video.
+/// Vengono restituiti solo i momenti dei video che l'utente può vedere.
+///
+/// ### Chi ha accesso:
+/// - Chiunque è loggato, limitatamente ai video ai quali ha accesso in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Momenti trovati con successo", body = [PlayerMoment], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Giocatore non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("player_id" = i64, Path, description = "ID del giocatore"),
+        ("name" = Option<String>, Query, description = "Nome dei timestamp da cercare"),
+        ("team_id" = Option<i64>, Query, description = "ID della squadra dei timestamp"),
+        ("from_date" = Option<String>, Query, description = "Data minima dei video"),
+        ("to_date" = Option<String>, Query, description = "Data massima dei video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/moments/player/<player_id>?<name>&<team_id>&<from_date>&<to_date>")]
+pub fn list_player_moments_handler(
+    key: Result<JWT, ApiError>,
+    player_id: i64,
+    name: Option<String>,
+    team_id: Option<i64>,
+    from_date: Option<NaiveDateTimeForm>,
+    to_date: Option<NaiveDateTimeForm>,
+) -> Result<Json<Vec<PlayerMoment>>, ApiError> {
+    let key = key?;
+
+    let filter = PlayerMomentsFilter {
+        name,
+        team_id,
+        from_date: from_date.map(|x| x.0),
+        to_date: to_date.map(|x| x.0),
+    };
+
+    let res = authorize_list_player_moments(key.claims, player_id, filter)?;
+    Ok(Json(res))
+}
+
+/// Inserisce una nuova clip associata a un video
+///
+/// Viene salvata una clip relativa al video specificato, tra gli istanti di inizio e di fine indicati.
+/// La clip è un nuovo video, condiviso con gli stessi utenti del video originale.
+///
+/// La modalità `copy` (predefinita) è veloce ma la clip inizia dal fotogramma chiave precedente all'istante di inizio,
+/// la modalità `reencode` ricodifica il video e la clip inizia esattamente all'istante di inizio.
+///
+/// La clip viene rifiutata se le squadre del video o le loro società sportive hanno esaurito la loro quota di spazio.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = CREATED, description = "Clip inserita con successo", body = Video, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti o inizio della clip oltre la durata del video", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json"),
+        (status = INSUFFICIENT_STORAGE, description = "Le squadre del video o le loro società sportive hanno esaurito la quota di spazio", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/clip", data = "<clip>")]
+pub async fn create_clip_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    clip: Json<ClipRequest>,
+) -> Result<Created<Json<Video>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_clip(key.claims, video_id, clip.into_inner()).await?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Permette di condividere un video con altri utenti
+///
+/// I permessi specificati (lettura, modifica, eliminazione e condivisione) vengono concessi agli utenti indicati,
+/// alle squadre indicate (i giocatori e gli allenatori, anche quelli che entrano nella squadra in seguito) e alle società
+/// sportive indicate (i responsabili e i membri delle sue squadre). Le condivisioni già presenti vengono unite a quelle
+/// nuove: una nuova condivisione non toglie mai dei permessi già concessi.
+/// Non è possibile concedere dei permessi che non si hanno, né modificare i permessi del proprietario del video
+/// o di chi ha più permessi di chi condivide.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Condivisione avvenuta con successo", body = VideoShares, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video, utente, squadra o società sportiva non trovati", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da condividere"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/share", data = "<share>")]
+pub fn share_video_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    share: Json<VideoShareRequest>,
+) -> Result<Json<VideoShares>, ApiError> {
+    let key = key?;
+
+    let res = authorize_share_video(key.claims, video_id, share.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Restituisce le condivisioni di un video
+///
+/// Restituisce gli utenti, le squadre e le società sportive con cui è stato condiviso il video, con i relativi permessi.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Condivisioni trovate con successo", body = VideoShares, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/share")]
+pub fn list_video_shares_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<VideoShares>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_video_shares(key.claims, video_id)?;
+    Ok(Json(res))
+}
+
+/// Modifica i permessi di un utente su un video
+///
+/// I permessi concessi all'utente vengono sostituiti con quelli specificati.
+/// Non è possibile concedere dei permessi che non si hanno, né modificare i permessi del proprietario del video
+/// o di chi ha più permessi di chi li modifica.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Permessi modificati con successo", body = VideoUser, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non condiviso con l'utente", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "L'utente è il proprietario del video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video condiviso"),
+        ("user_id" = i64, Path, description = "ID dell'utente di cui modificare i permessi"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/share/<user_id>", data = "<permissions>")]
+pub fn update_video_share_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    user_id: i64,
+    permissions: Json<VideoPermissions>,
+) -> Result<Json<VideoUser>, ApiError> {
+    let key = key?;
+
+    let res = authorize_update_video_user(key.claims, video_id, user_id, permissions.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Revoca la condivisione di un video con un utente
+///
+/// Vengono tolti all'utente tutti i permessi concessi con una condivisione.
+/// Non è possibile revocare i permessi del proprietario del video o di chi ha più permessi di chi li revoca.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Condivisione revocata con successo", body = VideoUser, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non condiviso con l'utente", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "L'utente è il proprietario del video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video condiviso"),
+        ("user_id" = i64, Path, description = "ID dell'utente di cui revocare i permessi"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/share/<user_id>")]
+pub fn revoke_video_share_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    user_id: i64,
+) -> Result<Json<VideoUser>, ApiError> {
+    let key = key?;
+
+    let res = authorize_revoke_video_user(key.claims, video_id, user_id)?;
+    Ok(Json(res))
+}
+
+/// Modifica i permessi di una squadra su un video
+///
+/// I permessi concessi alla squadra vengono sostituiti con quelli specificati.
+/// Non è possibile concedere dei permessi che non si hanno.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Permessi modificati con successo", body = VideoTeam, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non condiviso con la squadra", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video condiviso"),
+        ("team_id" = i64, Path, description = "ID della squadra di cui modificare i permessi"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/share/team/<team_id>", data = "<permissions>")]
+pub fn update_video_team_share_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    team_id: i64,
+    permissions: Json<VideoPermissions>,
+) -> Result<Json<VideoTeam>, ApiError> {
+    let key = key?;
+
+    let res = authorize_update_video_team(key.claims, video_id, team_id, permissions.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Revoca la condivisione di un video con una squadra
+///
+/// Vengono tolti alla squadra tutti i permessi concessi con una condivisione.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Condivisione revocata con successo", body = VideoTeam, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non condiviso con la squadra", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video condiviso"),
+        ("team_id" = i64, Path, description = "ID della squadra di cui revocare i permessi"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/share/team/<team_id>")]
+pub fn revoke_video_team_share_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    team_id: i64,
+) -> Result<Json<VideoTeam>, ApiError> {
+    let key = key?;
+
+    let res = authorize_revoke_video_team(key.claims, video_id, team_id)?;
+    Ok(Json(res))
+}
+
+/// Modifica i permessi di una società sportiva su un video
+///
+/// I permessi concessi alla società sportiva vengono sostituiti con quelli specificati.
+/// Non è possibile concedere dei permessi che non si hanno.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Permessi modificati con successo", body = VideoClub, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non condiviso con la società sportiva", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video condiviso"),
+        ("club_id" = String, Path, description = "ID della società sportiva di cui modificare i permessi"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/share/club/<club_id>", data = "<permissions>")]
+pub fn update_video_club_share_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    club_id: String,
+    permissions: Json<VideoPermissions>,
+) -> Result<Json<VideoClub>, ApiError> {
+    let key = key?;
+
+    let res =
+        authorize_update_video_club(key.claims, video_id, &club_id, permissions.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Revoca la condivisione di un video con una società sportiva
+///
+/// Vengono tolti alla società sportiva tutti i permessi concessi con una condivisione.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per condividere
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Condivisione revocata con successo", body = VideoClub, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato o non condiviso con la società sportiva", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video condiviso"),
+        ("club_id" = String, Path, description = "ID della società sportiva di cui revocare i permessi"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/share/club/<club_id>")]
+pub fn revoke_video_club_share_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    club_id: String,
+) -> Result<Json<VideoClub>, ApiError> {
+    let key = key?;
+
+    let res = authorize_revoke_video_club(key.claims, video_id, &club_id)?;
+    Ok(Json(res))
+}
+
+/// Fissa un video
+///
+/// Il video fissato non viene più eliminato dalle regole di conservazione, e l'eventuale avviso di eliminazione viene annullato.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per eliminare
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Video fissato con successo", body = Video, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da fissare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/pin")]
+pub fn pin_video_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_pin_video(key.claims, video_id, true)?;
+    Ok(Json(res))
+}
+
+/// Rimuove un video dai video fissati
+///
+/// Il video torna a essere soggetto alle regole di conservazione, e la sua scadenza viene calcolata
+/// alla successiva pulizia dell'archivio.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Il proprietario del video
+/// - Chiunque altro abbia l'accesso al video per eliminare
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Video rimosso dai video fissati con successo", body = Video, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da rimuovere dai video fissati"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/pin")]
+pub fn unpin_video_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+) -> Result<Json<Video>, ApiError> {
+    let key = key?;
+
+    let res = authorize_pin_video(key.claims, video_id, false)?;
+    Ok(Json(res))
+}
+
+/// Avvia la stream di una telecamera
+///
+/// Avvia la ricezione della stream della telecamera e la pubblica in formato DASH.
+/// Se la stream è già attiva non viene riavviata.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Chi può modificare una prenotazione con una sessione di registrazione in corso che utilizza la telecamera
+#[utoipa::path(
+    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Stream avviata con successo", body = ActiveStream, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Telecamera non trovata", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante l'avvio della stream", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera di cui avviare la stream"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/camera/<camera_id>/start")]
+pub async fn start_camera_stream_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<ActiveStream>, ApiError> {
+    let key = key?;
+
+    let res = authorize_start_camera_stream(key.claims, camera_id, state).await?;
+    Ok(Json(res))
+}
+
+/// Termina la stream di una telecamera
+///
+/// Termina la ricezione della stream della telecamera e restituisce il suo ultimo stato.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Chi può modificare una prenotazione con una sessione di registrazione in corso che utilizza la telecamera
+#[utoipa::path(
+    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Stream terminata con successo", body = ActiveStream, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "La stream della telecamera non è attiva", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera di cui terminare la stream"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/camera/<camera_id>/stop")]
+pub async fn stop_camera_stream_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<ActiveStream>, ApiError> {
+    let key = key?;
+
+    let res = authorize_stop_camera_stream(key.claims, camera_id, state).await?;
+    Ok(Json(res))
+}
+
+/// Avvia le stream delle telecamere di una sessione di registrazione
+///
+/// Avvia la ricezione delle stream di tutte le telecamere associate alla sessione di registrazione.
+/// Le stream già attive non vengono riavviate.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile delle società sportive delle squadre coinvolta nella prenotazione.
+/// - Un allenatore delle squadre coinvolte nella prenotazione.
+#[utoipa::path(
+    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Stream avviate con successo", body = [ActiveStream], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Sessione di registrazione non trovata", body = ApiError, content_type = "application/json"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante l'avvio di una stream", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("recording_session_id" = i64, Path, description = "ID della sessione di registrazione di cui avviare le stream"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/session/<recording_session_id>/start")]
+pub async fn start_session_streams_handler(
+    key: Result<JWT, ApiError>,
+    recording_session_id: i64,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<Vec<ActiveStream>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_start_session_streams(key.claims, recording_session_id, state).await?;
+    Ok(Json(res))
+}
+
+/// Termina le stream delle telecamere di una sessione di registrazione
+///
+/// Termina la ricezione delle stream attive delle telecamere associate alla sessione di registrazione
+/// e restituisce il loro ultimo stato.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile delle società sportive delle squadre coinvolta nella prenotazione.
+/// - Un allenatore delle squadre coinvolte nella prenotazione.
+#[utoipa::path(
+    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Stream terminate con successo", body = [ActiveStream], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Sessione di registrazione non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("recording_session_id" = i64, Path, description = "ID della sessione di registrazione di cui terminare le stream"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/session/<recording_session_id>/stop")]
+pub async fn stop_session_streams_handler(
+    key: Result<JWT, ApiError>,
+    recording_session_id: i64,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<Vec<ActiveStream>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_stop_session_streams(key.claims, recording_session_id, state).await?;
+    Ok(Json(res))
+}
+
+/// Restituisce la lista delle stream attive
+///
+/// Restituisce le stream attive con la telecamera, l'URL del manifest, il tempo di attività e il PID del processo.
+/// Sono comprese le stream delle registrazioni programmate.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori vedono tutte le stream
+/// - Gli altri utenti vedono le stream delle telecamere che possono avviare e fermare
+#[utoipa::path(
+    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Stream attive trovate con successo", body = [ActiveStream], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/active")]
+pub async fn list_active_streams_handler(
+    key: Result<JWT, ApiError>,
+    state: &rocket::State<StreamMap>,
+) -> Result<Json<Vec<ActiveStream>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_active_streams(key.claims, state).await?;
+    Ok(Json(res))
+}
+
+/// Restituisce un URL firmato della diretta di una telecamera
+///
+/// Restituisce un URL del manifest DASH della telecamera che può essere usato senza l'header di autenticazione
+/// fino alla sua scadenza, ad esempio da un elemento `<video>` o da dash.js.
+/// Una volta scaduto è necessario richiederne uno nuovo.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Chi può avviare e fermare la stream della telecamera
+/// - Chi ha accesso a una sessione di registrazione in corso che utilizza la telecamera
+#[utoipa::path(
+    context_path = "/player",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "URL firmato generato con successo", body = SignedUrl, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Telecamera non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("camera_id" = i64, Path, description = "ID della telecamera della diretta"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/camera/<camera_id>/signed-url")]
+pub fn sign_camera_stream_handler(
+    key: Result<JWT, ApiError>,
+    camera_id: i64,
+) -> Result<Json<SignedUrl>, ApiError> {
+    let key = key?;
+
+    let res = authorize_sign_camera_stream(key.claims, camera_id)?;
+    Ok(Json(res))
+}
+
+/// Inserisce una nuova annotazione su uno screenshot o su un timestamp del video
+///
+/// Viene inserita un'annotazione (frecce, cerchi, zone e percorsi dei giocatori) tracciata sullo screenshot
+/// o sul fotogramma del timestamp indicato, che devono appartenere al video specificato.
+/// Le coordinate dei punti sono relative all'immagine, da 0 a 1 a partire dall'angolo in alto a sinistra,
+/// e i colori sono nel formato `#RRGGBB` o `#RRGGBBAA`. L'autore dell'annotazione è l'utente che la inserisce.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in scrittura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = CREATED, description = "Annotazione inserita con successo", body = Annotation, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video, screenshot o timestamp non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da annotare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/<video_id>/annotation", data = "<annotation>")]
+pub fn create_annotation_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    annotation: Json<AnnotationRequest>,
+) -> Result<Created<Json<Annotation>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_annotation(key.claims, video_id, annotation.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Restituisce le annotazioni di un video
+///
+/// Restituisce la lista delle annotazioni degli screenshot e dei timestamp del video specificato,
+/// in ordine di inserimento, eventualmente solo quelle di uno screenshot o di un timestamp.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Annotazioni trovate con successo", body = [Annotation], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video non trovato", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("screenshot_id" = Option<i64>, Query, description = "ID dello screenshot delle annotazioni"),
+        ("time_marker_id" = Option<i64>, Query, description = "ID del timestamp delle annotazioni"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/annotation?<screenshot_id>&<time_marker_id>")]
+pub fn list_annotations_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    screenshot_id: Option<i64>,
+    time_marker_id: Option<i64>,
+) -> Result<Json<Vec<Annotation>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_annotations(key.claims, video_id, screenshot_id, time_marker_id)?;
+    Ok(Json(res))
+}
+
+/// Restituisce un'annotazione di un video
+///
+/// Restituisce un'annotazione di uno screenshot o di un timestamp del video specificato dato il suo ID.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Annotazione trovata con successo", body = Annotation, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o annotazione non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("annotation_id" = i64, Path, description = "ID dell'annotazione da cercare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/annotation/<annotation_id>")]
+pub fn find_annotation_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    annotation_id: i64,
+) -> Result<Json<Annotation>, ApiError> {
+    let key = key?;
+
+    let res = authorize_find_annotation(key.claims, video_id, annotation_id)?;
+    Ok(Json(res))
+}
+
+/// Modifica un'annotazione di un video
+///
+/// Vengono sostituite l'etichetta e le forme dell'annotazione del video specificato.
+///
+/// ### Chi ha accesso:
+/// - L'autore dell'annotazione, se ha l'accesso al video in scrittura
+/// - Chiunque abbia il permesso di eliminare il video
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Annotazione modificata con successo", body = Annotation, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o annotazione non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("annotation_id" = i64, Path, description = "ID dell'annotazione da modificare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/<video_id>/annotation/<annotation_id>", data = "<annotation>")]
+pub fn update_annotation_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    annotation_id: i64,
+    annotation: Json<AnnotationData>,
+) -> Result<Json<Annotation>, ApiError> {
+    let key = key?;
+
+    let res =
+        authorize_update_annotation(key.claims, video_id, annotation_id, annotation.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Elimina un'annotazione di un video
+///
+/// Viene eliminata l'annotazione del video specificato.
+///
+/// ### Chi ha accesso:
+/// - L'autore dell'annotazione, se ha l'accesso al video in scrittura
+/// - Chiunque abbia il permesso di eliminare il video
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Annotazione eliminata con successo", body = Annotation, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o annotazione non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video da cercare"),
+        ("annotation_id" = i64, Path, description = "ID dell'annotazione da eliminare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/<video_id>/annotation/<annotation_id>")]
+pub fn delete_annotation_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    annotation_id: i64,
+) -> Result<Json<Annotation>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_annotation(key.claims, video_id, annotation_id)?;
+    Ok(Json(res))
+}
+
+/// Restituisce l'immagine annotata di uno screenshot
+///
+/// Restituisce l'immagine JPEG dello screenshot con tutte le sue annotazioni disegnate sopra, da esportare
+/// o condividere. L'immagine viene preparata alla prima richiesta e riutilizzata finché le annotazioni
+/// non cambiano; uno screenshot senza annotazioni viene restituito così com'è.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Immagine annotata restituita per intero"),
+        (status = PARTIAL_CONTENT, description = "Parte dell'immagine richiesta con l'header Range"),
+        (status = TEMPORARY_REDIRECT, description = "L'immagine è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o screenshot non trovato", body = ApiError, content_type = "application/json"),
+        (status = RANGE_NOT_SATISFIABLE, description = "L'intervallo richiesto è fuori dal file"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante il disegno delle annotazioni", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video dello screenshot"),
+        ("screenshot_id" = i64, Path, description = "ID dello screenshot da scaricare"),
+        ("download" = Option<bool>, Query, description = "Propone il file come download invece di mostrarlo"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/screenshot/<screenshot_id>/annotated?<download>&<token>")]
+pub async fn annotated_screenshot_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    screenshot_id: i64,
+    download: Option<bool>,
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_annotated_screenshot(token, video_id, screenshot_id).await?,
+        None => authorize_annotated_screenshot(key?.claims, video_id, screenshot_id).await?,
+    };
+
+    serve_file(file, range, download.unwrap_or(false)).await
+}
+
+/// Restituisce il fotogramma annotato di un timestamp
+///
+/// Restituisce il fotogramma JPEG del video all'istante del timestamp con tutte le annotazioni del timestamp
+/// disegnate sopra, da esportare o condividere. L'immagine viene preparata alla prima richiesta e riutilizzata
+/// finché le annotazioni non cambiano.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile della società sportiva
+/// - Un allenatore della squadra
+/// - Un giocatore
+/// - Chiunque altro abbia l'accesso al video in lettura
+/// - Chi possiede un URL firmato del video non scaduto
+#[utoipa::path(
+    context_path = "/video",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Dati registrati"],
+    responses(
+        (status = OK, description = "Immagine annotata restituita per intero"),
+        (status = PARTIAL_CONTENT, description = "Parte dell'immagine richiesta con l'header Range"),
+        (status = TEMPORARY_REDIRECT, description = "L'immagine è in un archivio remoto: reindirizzamento al suo URL firmato"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione o il token dell'URL firmato non è valido", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Video o timestamp non trovato", body = ApiError, content_type = "application/json"),
+        (status = RANGE_NOT_SATISFIABLE, description = "L'intervallo richiesto è fuori dal file"),
+        (status = INTERNAL_SERVER_ERROR, description = "Errore durante l'estrazione del fotogramma o il disegno delle annotazioni", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("video_id" = i64, Path, description = "ID del video del timestamp"),
+        ("timestamp_id" = i64, Path, description = "ID del timestamp da scaricare"),
+        ("download" = Option<bool>, Query, description = "Propone il file come download invece di mostrarlo"),
+        ("token" = Option<String>, Query, description = "Token dell'URL firmato del video"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<video_id>/timestamp/<timestamp_id>/annotated?<download>&<token>")]
+pub async fn annotated_timestamp_handler(
+    key: Result<JWT, ApiError>,
+    video_id: i64,
+    timestamp_id: i64,
+    download: Option<bool>,
+    token: Option<&str>,
+    range: ByteRange,
+) -> Result<FileResponse, ApiError> {
+    let file = match token {
+        Some(token) => signed_annotated_time_marker(token, video_id, timestamp_id).await?,
+        None => authorize_annotated_time_marker(key?.claims, video_id, timestamp_id).await?,
+    };
+
+    serve_file(file, range, download.unwrap_or(false)).await
 }
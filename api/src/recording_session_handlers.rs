@@ -6,8 +6,9 @@ use application::{
         read::{authorize_find_recording_session, authorize_list_recording_sessions_by_booking},
         update::authorize_update_recording_session_and_cameras,
     },
+    db_entities::video::read::authorize_list_session_angles,
 };
-use domain::models::others::{RecordingSessionData, RecordingSessionWithCameras};
+use domain::models::others::{RecordingSessionData, RecordingSessionWithCameras, SessionAngles};
 use rocket::{delete, get, post, put, serde::json::Json};
 use shared::response_models::ApiError;
 
@@ -191,3 +192,43 @@ pub fn delete_recording_session_handler(
     let res = authorize_delete_recording_session(key.claims, recording_session_id)?;
     Ok(Json(res))
 }
+
+/// Restituisce i video di tutte le telecamere di una sessione di registrazione, sincronizzati
+///
+/// Restituisce i video registrati dalle telecamere della sessione, senza le clip, ciascuno con il nome della telecamera
+/// e con `offset_ms`, i millisecondi tra l'inizio della sessione e l'inizio del video.
+/// Il momento `t` della sessione corrisponde all'istante `t - offset_ms` di ciascun video: così il frontend
+/// può passare da una telecamera all'altra nello stesso momento della partita o dell'allenamento.
+///
+/// ### Chi ha accesso:
+/// - Il responsabile delle società sportive delle squadre coinvolta nella prenotazione.
+/// - Un allenatore delle squadre coinvolte nella prenotazione.
+/// - Un giocatore delle squadre coinvolte nella prenotazione.
+/// - Chi ha accesso in lettura ad alcuni video della sessione, limitatamente a quei video
+#[utoipa::path(
+    context_path = "/recording-session",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Sessioni di registrazione"],
+    responses(
+        (status = OK, description = "Video della sessione di registrazione trovati con successo", body = SessionAngles, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Sessione di registrazione non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("recording_session_id" = i64, Path, description = "ID della sessione di registrazione"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/<recording_session_id>/angles")]
+pub fn list_session_angles_handler(
+    key: Result<JWT, ApiError>,
+    recording_session_id: i64,
+) -> Result<Json<SessionAngles>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_session_angles(key.claims, recording_session_id)?;
+    Ok(Json(res))
+}
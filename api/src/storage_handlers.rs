@@ -0,0 +1,376 @@
+use application::{
+    authentication::JWT,
+    db_entities::storage::{
+        quota::{
+            authorize_create_storage_quota, authorize_delete_storage_quota,
+            authorize_list_storage_quotas, authorize_update_storage_quota,
+        },
+        retention::{
+            authorize_create_retention_rule, authorize_delete_retention_rule,
+            authorize_list_expiration_notices, authorize_list_expiring_videos,
+            authorize_list_retention_rules, authorize_update_retention_rule,
+        },
+        usage::authorize_storage_usage,
+    },
+};
+use domain::models::{
+    full_tables::{RetentionRule, StorageQuota, Video, VideoExpirationNotice},
+    insertions::{NewRetentionRule, NewStorageQuota},
+    others::ClubStorageUsage,
+};
+use rocket::{delete, get, post, put, response::status::Created, serde::json::Json};
+use shared::response_models::ApiError;
+
+/// Restituisce lo spazio occupato dai video
+///
+/// Restituisce, per ogni società sportiva e per ciascuna delle sue squadre, lo spazio occupato dai video
+/// delle partite e degli allenamenti, il numero dei video e la quota di spazio, se assegnata.
+/// Senza una società sportiva indicata vengono restituite tutte le società accessibili all'utente.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Il responsabile della società sportiva, limitatamente alla sua società
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Spazio occupato calcolato con successo", body = [ClubStorageUsage], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+    ),
+    params(
+        ("club_id" = Option<String>, Query, description = "Partita IVA della società sportiva"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/usage?<club_id>")]
+pub fn storage_usage_handler(
+    key: Result<JWT, ApiError>,
+    club_id: Option<String>,
+) -> Result<Json<Vec<ClubStorageUsage>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_storage_usage(key.claims, club_id)?;
+    Ok(Json(res))
+}
+
+/// Restituisce i video in scadenza
+///
+/// Restituisce i video dei quali i proprietari sono stati avvisati della prossima eliminazione,
+/// in ordine di scadenza. Per evitare l'eliminazione un video può essere fissato.
+///
+/// ### Chi ha accesso:
+/// - Chiunque, limitatamente ai video che può eliminare
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Video in scadenza trovati con successo", body = [Video], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/expiring")]
+pub fn list_expiring_videos_handler(
+    key: Result<JWT, ApiError>,
+) -> Result<Json<Vec<Video>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_expiring_videos(key.claims)?;
+    Ok(Json(res))
+}
+
+/// Restituisce gli avvisi di scadenza dell'utente
+///
+/// Restituisce gli avvisi della prossima eliminazione dei video dei quali l'utente è proprietario o che sono
+/// condivisi con lui, direttamente o tramite una sua squadra o società sportiva, in ordine di scadenza.
+/// Gli avvisi di un video vengono rimossi quando la sua scadenza cambia o quando viene fissato.
+///
+/// ### Chi ha accesso:
+/// - Chiunque, limitatamente ai propri avvisi
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Avvisi trovati con successo", body = [VideoExpirationNotice], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/expiration-notices")]
+pub fn list_expiration_notices_handler(
+    key: Result<JWT, ApiError>,
+) -> Result<Json<Vec<VideoExpirationNotice>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_expiration_notices(key.claims)?;
+    Ok(Json(res))
+}
+
+/// Inserisce una nuova regola di conservazione
+///
+/// La regola indica per quanti giorni, dalla loro data, vengono conservati i video del tipo indicato
+/// (`recording` per le registrazioni delle telecamere, `clip` per le clip). Senza giorni i video vengono
+/// conservati per sempre. La regola può essere globale o specifica di una società sportiva o di una squadra:
+/// per ogni squadra si applica la regola della squadra, altrimenti quella della sua società, altrimenti quella globale.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Il responsabile della società sportiva, per le regole della sua società e delle sue squadre
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = CREATED, description = "Regola inserita con successo", body = RetentionRule, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "Esiste già una regola per gli stessi video", body = ApiError, content_type = "application/json")
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/retention-rule", data = "<rule>")]
+pub fn create_retention_rule_handler(
+    key: Result<JWT, ApiError>,
+    rule: Json<NewRetentionRule>,
+) -> Result<Created<Json<RetentionRule>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_retention_rule(key.claims, rule.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Restituisce la lista delle regole di conservazione
+///
+/// Restituisce le regole globali e le regole che l'utente può gestire.
+///
+/// ### Chi ha accesso:
+/// - Chiunque
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Regole trovate con successo", body = [RetentionRule], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/retention-rule")]
+pub fn list_retention_rules_handler(
+    key: Result<JWT, ApiError>,
+) -> Result<Json<Vec<RetentionRule>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_retention_rules(key.claims)?;
+    Ok(Json(res))
+}
+
+/// Aggiorna una regola di conservazione
+///
+/// Le nuove scadenze dei video vengono calcolate alla successiva pulizia dell'archivio.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Il responsabile della società sportiva, per le regole della sua società e delle sue squadre
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Regola aggiornata con successo", body = RetentionRule, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Regola non trovata", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "Esiste già una regola per gli stessi video", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("rule_id" = i64, Path, description = "ID della regola da aggiornare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/retention-rule/<rule_id>", data = "<rule>")]
+pub fn update_retention_rule_handler(
+    key: Result<JWT, ApiError>,
+    rule_id: i64,
+    rule: Json<NewRetentionRule>,
+) -> Result<Json<RetentionRule>, ApiError> {
+    let key = key?;
+
+    let res = authorize_update_retention_rule(key.claims, rule_id, rule.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Elimina una regola di conservazione
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+/// - Il responsabile della società sportiva, per le regole della sua società e delle sue squadre
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Regola eliminata con successo", body = RetentionRule, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Regola non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("rule_id" = i64, Path, description = "ID della regola da eliminare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/retention-rule/<rule_id>")]
+pub fn delete_retention_rule_handler(
+    key: Result<JWT, ApiError>,
+    rule_id: i64,
+) -> Result<Json<RetentionRule>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_retention_rule(key.claims, rule_id)?;
+    Ok(Json(res))
+}
+
+/// Inserisce una nuova quota di spazio
+///
+/// La quota indica lo spazio massimo in byte occupato dai video di una società sportiva o di una squadra.
+/// Quando la quota è esaurita non è più possibile estrarre nuove clip dai video, mentre le registrazioni
+/// delle telecamere vengono sempre salvate.
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = CREATED, description = "Quota inserita con successo", body = StorageQuota, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "Esiste già una quota per la società sportiva o la squadra", body = ApiError, content_type = "application/json")
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[post("/quota", data = "<quota>")]
+pub fn create_storage_quota_handler(
+    key: Result<JWT, ApiError>,
+    quota: Json<NewStorageQuota>,
+) -> Result<Created<Json<StorageQuota>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_create_storage_quota(key.claims, quota.into_inner())?;
+    Ok(Created::new("").body(Json(res)))
+}
+
+/// Restituisce la lista delle quote di spazio
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Quote trovate con successo", body = [StorageQuota], content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[get("/quota")]
+pub fn list_storage_quotas_handler(
+    key: Result<JWT, ApiError>,
+) -> Result<Json<Vec<StorageQuota>>, ApiError> {
+    let key = key?;
+
+    let res = authorize_list_storage_quotas(key.claims)?;
+    Ok(Json(res))
+}
+
+/// Aggiorna una quota di spazio
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Quota aggiornata con successo", body = StorageQuota, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Errore nei dati forniti", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Quota non trovata", body = ApiError, content_type = "application/json"),
+        (status = CONFLICT, description = "Esiste già una quota per la società sportiva o la squadra", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("quota_id" = i64, Path, description = "ID della quota da aggiornare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[put("/quota/<quota_id>", data = "<quota>")]
+pub fn update_storage_quota_handler(
+    key: Result<JWT, ApiError>,
+    quota_id: i64,
+    quota: Json<NewStorageQuota>,
+) -> Result<Json<StorageQuota>, ApiError> {
+    let key = key?;
+
+    let res = authorize_update_storage_quota(key.claims, quota_id, quota.into_inner())?;
+    Ok(Json(res))
+}
+
+/// Elimina una quota di spazio
+///
+/// ### Chi ha accesso:
+/// - Gli amministratori
+#[utoipa::path(
+    context_path = "/storage",      // Path di base che viene aggiunto all'inizio del path specificato nella macro get
+    tags = ["Archivio"],
+    responses(
+        (status = OK, description = "Quota eliminata con successo", body = StorageQuota, content_type = "application/json"),
+        (status = BAD_REQUEST, description = "Token di autenticazione malformato", body = ApiError, content_type = "application/json"),
+        (status = UNAUTHORIZED, description = "Non è stato fornito un token di autenticazione", body = ApiError, content_type = "application/json"),
+        (status = FORBIDDEN, description = "L'utente non è autorizzato a svolgere questa operazione", body = ApiError, content_type = "application/json"),
+        (status = NOT_FOUND, description = "Quota non trovata", body = ApiError, content_type = "application/json")
+    ),
+    params(
+        ("quota_id" = i64, Path, description = "ID della quota da eliminare"),
+    ),
+    security(
+        ("jwt_token" = [])
+    )
+)]
+#[delete("/quota/<quota_id>")]
+pub fn delete_storage_quota_handler(
+    key: Result<JWT, ApiError>,
+    quota_id: i64,
+) -> Result<Json<StorageQuota>, ApiError> {
+    let key = key?;
+
+    let res = authorize_delete_storage_quota(key.claims, quota_id)?;
+    Ok(Json(res))
+}
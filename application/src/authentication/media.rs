@@ -0,0 +1,117 @@
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use jsonwebtoken::{
+    decode, encode,
+    errors::{Error, ErrorKind},
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use shared::response_models::{ApiError, ApiErrorType};
+use std::env;
+use std::path::Path;
+
+// Destinatario dei token per i contenuti multimediali, che non possono essere usati come token di autenticazione
+const MEDIA_AUDIENCE: &str = "media";
+
+/// Dati di un token che consente di accedere ai contenuti multimediali senza l'header di autenticazione,
+/// limitatamente ai file che iniziano con `scope`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MediaClaims {
+    pub subject_id: i64, // ID dell'utente al quale è stato rilasciato il token
+    pub scope: String,   // cartella o file ai quali il token dà accesso
+    aud: String,         // destinatario del token
+    exp: usize,          // scadenza del token in secondi
+}
+
+/// Token per i contenuti multimediali con la sua scadenza.
+pub struct MediaToken {
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Crea un token di breve durata per accedere ai contenuti multimediali indicati da `scope`.
+///
+/// Il token viene inserito negli URL, per questo la sua durata è configurata separatamente
+/// da quella dei token di autenticazione.
+pub fn create_media_token(subject_id: i64, scope: String) -> Result<MediaToken, Error> {
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set.");
+
+    let token_duration = env::var("MEDIA_TOKEN_DURATION_SECONDS")
+        .expect("MEDIA_TOKEN_DURATION_SECONDS must be set.")
+        .parse::<i64>()
+        .expect("MEDIA_TOKEN_DURATION_SECONDS must be a number.");
+
+    let expiration = Utc::now()
+        .checked_add_signed(chrono::Duration::seconds(token_duration))
+        .expect("Invalid timestamp")
+        .timestamp();
+
+    let claims = MediaClaims {
+        subject_id,
+        scope,
+        aud: MEDIA_AUDIENCE.to_string(),
+        exp: expiration as usize,
+    };
+
+    let header = Header::new(Algorithm::HS512);
+
+    let token = encode(
+        &header,
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )?;
+
+    let expires_at = DateTime::from_timestamp(expiration, 0)
+        .expect("Invalid timestamp")
+        .with_timezone(&Local)
+        .naive_local();
+
+    Ok(MediaToken { token, expires_at })
+}
+
+/// Verifica un token per i contenuti multimediali e ne restituisce i dati.
+pub fn decode_media_token(token: &str) -> Result<MediaClaims, ErrorKind> {
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set.");
+
+    let mut validation = Validation::new(Algorithm::HS512);
+    validation.set_audience(&[MEDIA_AUDIENCE]);
+
+    match decode::<MediaClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &validation,
+    ) {
+        Ok(token) => Ok(token.claims),
+        Err(err) => Err(err.kind().to_owned()),
+    }
+}
+
+/// Verifica che il token sia valido e che dia accesso alla risorsa indicata da `path`, e ne restituisce i dati.
+pub fn authorize_media_token(token: &str, path: &Path) -> Result<MediaClaims, ApiError> {
+    let claims = match decode_media_token(token) {
+        Ok(claims) => claims,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::Unauthorized,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::AuthenticationError,
+                message: format!("Error validating media token - {:?}", err),
+            });
+        }
+    };
+
+    if !path.starts_with(&claims.scope) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - The media token of user {} doesn't give access to {}",
+                claims.subject_id,
+                path.display()
+            ),
+        });
+    }
+
+    return Ok(claims);
+}
@@ -1,3 +1,4 @@
+pub mod media;
 pub mod password;
 
 use chrono::Utc;
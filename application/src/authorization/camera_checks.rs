@@ -0,0 +1,54 @@
+use chrono::Local;
+use shared::response_models::ApiError;
+
+use crate::{
+    authorization::{
+        booking_checks::{can_edit_delete_booking, can_read_recording_session},
+        person_checks::is_administrator,
+    },
+    db_entities::recording_session::read::list_recording_sessions_in_progress,
+};
+
+/// Controlla se l'utente può avviare e fermare la stream della telecamera.
+///
+/// Oltre agli amministratori, possono farlo coloro che possono modificare una prenotazione
+/// con una sessione di registrazione in corso che utilizza la telecamera.
+pub fn can_control_camera(person_id: i64, camera_id: i64) -> Result<bool, ApiError> {
+    if is_administrator(person_id)? {
+        return Ok(true);
+    }
+
+    let now = Local::now().naive_local();
+
+    for session in list_recording_sessions_in_progress(now)? {
+        if session.cameras.iter().any(|camera| camera.id == camera_id)
+            && can_edit_delete_booking(person_id, session.recording_session.booking_id)?
+        {
+            return Ok(true);
+        }
+    }
+
+    return Ok(false);
+}
+
+/// Controlla se l'utente può guardare la diretta della telecamera.
+///
+/// Oltre a chi può controllare la telecamera, possono farlo coloro che hanno accesso a una sessione
+/// di registrazione in corso che utilizza la telecamera.
+pub fn can_watch_camera(person_id: i64, camera_id: i64) -> Result<bool, ApiError> {
+    if can_control_camera(person_id, camera_id)? {
+        return Ok(true);
+    }
+
+    let now = Local::now().naive_local();
+
+    for session in list_recording_sessions_in_progress(now)? {
+        if session.cameras.iter().any(|camera| camera.id == camera_id)
+            && can_read_recording_session(person_id, session.recording_session.id)?
+        {
+            return Ok(true);
+        }
+    }
+
+    return Ok(false);
+}
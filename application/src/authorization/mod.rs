@@ -1,4 +1,6 @@
 pub mod booking_checks;
+pub mod camera_checks;
 pub mod person_checks;
 pub mod team_checks;
 pub mod user_checks;
+pub mod video_checks;
@@ -0,0 +1,337 @@
+use diesel::prelude::*;
+use domain::models::{
+    full_tables::{VideoClub, VideoTeam, VideoUser},
+    others::VideoPermissions,
+};
+use infrastructure::establish_connection;
+use shared::response_models::ApiError;
+
+use crate::{
+    authorization::{
+        booking_checks::{can_edit_delete_booking, can_read_recording_session},
+        person_checks::is_administrator,
+    },
+    db_entities::{recording_session::read::find_recording_session, video::read::find_video},
+};
+
+/// Controlla se l'utente può vedere il video e i suoi dati, come screenshot e segnaposti.
+///
+/// Oltre agli amministratori, possono farlo coloro che hanno accesso alla sessione di registrazione
+/// del video e gli utenti con i quali il video è stato condiviso in lettura, direttamente o tramite
+/// una loro squadra o società sportiva.
+pub fn can_read_video(person_id: i64, video_id: i64) -> Result<bool, ApiError> {
+    if is_administrator(person_id)? {
+        return Ok(true);
+    }
+
+    let video = find_video(video_id)?;
+
+    if can_read_recording_session(person_id, video.session_id)? {
+        return Ok(true);
+    }
+
+    return Ok(shared_video_permissions(person_id, video_id)?.read);
+}
+
+/// Controlla se l'utente può modificare il video, ad esempio aggiungendo o eliminando screenshot e segnaposti.
+///
+/// Oltre agli amministratori, possono farlo coloro che possono modificare la prenotazione del video
+/// e gli utenti con i quali il video è stato condiviso in scrittura. Chi ha accesso alla sessione di
+/// registrazione, come i giocatori delle squadre, può solo vedere il video.
+pub fn can_edit_video(person_id: i64, video_id: i64) -> Result<bool, ApiError> {
+    if can_manage_video(person_id, video_id)? {
+        return Ok(true);
+    }
+
+    return Ok(shared_video_permissions(person_id, video_id)?.edit);
+}
+
+/// Controlla se l'utente può eliminare il video.
+///
+/// Oltre agli amministratori, possono farlo coloro che possono modificare la prenotazione del video,
+/// il proprietario del video e gli utenti ai quali è stato concesso di eliminarlo.
+pub fn can_delete_video(person_id: i64, video_id: i64) -> Result<bool, ApiError> {
+    if can_manage_video(person_id, video_id)? {
+        return Ok(true);
+    }
+
+    return Ok(shared_video_permissions(person_id, video_id)?.delete);
+}
+
+/// Controlla se l'utente può condividere il video con altri utenti.
+///
+/// Oltre agli amministratori, possono farlo coloro che possono modificare la prenotazione del video,
+/// il proprietario del video e gli utenti ai quali è stato concesso di condividerlo.
+pub fn can_share_video(person_id: i64, video_id: i64) -> Result<bool, ApiError> {
+    if can_manage_video(person_id, video_id)? {
+        return Ok(true);
+    }
+
+    return Ok(shared_video_permissions(person_id, video_id)?.share);
+}
+
+/// Restituisce tutti i permessi dell'utente sul video, considerando sia la sessione di registrazione
+/// del video sia le condivisioni.
+pub fn video_permissions(person_id: i64, video_id: i64) -> Result<VideoPermissions, ApiError> {
+    if can_manage_video(person_id, video_id)? {
+        return Ok(VideoPermissions::all());
+    }
+
+    let video = find_video(video_id)?;
+
+    let mut permissions = VideoPermissions::default();
+
+    if can_read_recording_session(person_id, video.session_id)? {
+        permissions.read = true;
+    }
+
+    return Ok(permissions.union(&shared_video_permissions(person_id, video_id)?));
+}
+
+/// Permessi concessi con una condivisione, il proprietario ha tutti i permessi.
+pub fn shared_permissions(video_user: &VideoUser) -> VideoPermissions {
+    if video_user.is_owner {
+        return VideoPermissions::all();
+    }
+
+    return VideoPermissions {
+        read: video_user.read,
+        edit: video_user.edit,
+        delete: video_user.delete,
+        share: video_user.share,
+    };
+}
+
+/// Permessi concessi all'utente sul video con le condivisioni: quella con l'utente e quelle con le squadre
+/// e le società sportive di cui fa parte attualmente (vedi `find_person_groups`).
+pub fn shared_video_permissions(
+    person_id: i64,
+    video_id: i64,
+) -> Result<VideoPermissions, ApiError> {
+    use domain::schema::{video_club, video_team};
+
+    let mut permissions = match find_video_user(person_id, video_id)? {
+        Some(video_user) => shared_permissions(&video_user),
+        None => VideoPermissions::default(),
+    };
+
+    let (team_ids, club_ids) = find_person_groups(person_id)?;
+
+    let connection = &mut establish_connection();
+
+    let video_teams: Vec<VideoTeam> = video_team::table
+        .filter(video_team::video_id.eq(video_id))
+        .filter(video_team::team_id.eq_any(&team_ids))
+        .select(VideoTeam::as_select())
+        .load(connection)?;
+
+    let video_clubs: Vec<VideoClub> = video_club::table
+        .filter(video_club::video_id.eq(video_id))
+        .filter(video_club::club_id.eq_any(&club_ids))
+        .select(VideoClub::as_select())
+        .load(connection)?;
+
+    for video_team in &video_teams {
+        permissions = permissions.union(&team_permissions(video_team));
+    }
+
+    for video_club in &video_clubs {
+        permissions = permissions.union(&club_permissions(video_club));
+    }
+
+    return Ok(permissions);
+}
+
+/// Restituisce gli ID dei video condivisi in lettura con l'utente, direttamente o tramite una sua squadra
+/// o società sportiva.
+pub fn list_shared_videos(person_id: i64) -> Result<Vec<i64>, ApiError> {
+    use domain::schema::{video_club, video_team, video_user};
+
+    let (team_ids, club_ids) = find_person_groups(person_id)?;
+
+    let connection = &mut establish_connection();
+
+    let mut video_ids: Vec<i64> = video_user::table
+        .filter(video_user::user_id.eq(person_id))
+        .filter(video_user::is_owner.or(video_user::read))
+        .select(video_user::video_id)
+        .load(connection)?;
+
+    video_ids.extend(
+        video_team::table
+            .filter(video_team::team_id.eq_any(&team_ids))
+            .filter(video_team::read)
+            .select(video_team::video_id)
+            .load::<i64>(connection)?,
+    );
+
+    video_ids.extend(
+        video_club::table
+            .filter(video_club::club_id.eq_any(&club_ids))
+            .filter(video_club::read)
+            .select(video_club::video_id)
+            .load::<i64>(connection)?,
+    );
+
+    video_ids.sort();
+    video_ids.dedup();
+
+    return Ok(video_ids);
+}
+
+/// Restituisce gli ID degli utenti proprietari del video o con i quali il video è condiviso,
+/// direttamente o tramite una loro squadra o società sportiva.
+///
+/// Come in `find_person_groups`, fanno parte di una squadra i suoi giocatori e allenatori attuali, e di una
+/// società sportiva i suoi responsabili attuali e i membri delle sue squadre.
+pub fn list_video_share_holders(video_id: i64) -> Result<Vec<i64>, ApiError> {
+    use domain::schema::{
+        coach_team, player_team, team, user, user_club, video_club, video_team, video_user,
+    };
+
+    let connection = &mut establish_connection();
+
+    let mut person_ids: Vec<i64> = video_user::table
+        .filter(video_user::video_id.eq(video_id))
+        .select(video_user::user_id)
+        .load(connection)?;
+
+    let club_ids: Vec<String> = video_club::table
+        .filter(video_club::video_id.eq(video_id))
+        .select(video_club::club_id)
+        .load(connection)?;
+
+    person_ids.extend(
+        user_club::table
+            .filter(user_club::club_id.eq_any(&club_ids))
+            .filter(user_club::until_date.is_null())
+            .select(user_club::user_id)
+            .load::<i64>(connection)?,
+    );
+
+    let mut team_ids: Vec<i64> = video_team::table
+        .filter(video_team::video_id.eq(video_id))
+        .select(video_team::team_id)
+        .load(connection)?;
+
+    team_ids.extend(
+        team::table
+            .filter(team::club_id.eq_any(&club_ids))
+            .select(team::id)
+            .load::<i64>(connection)?,
+    );
+
+    person_ids.extend(
+        player_team::table
+            .filter(player_team::team_id.eq_any(&team_ids))
+            .filter(player_team::until_date.is_null())
+            .select(player_team::player_id)
+            .load::<i64>(connection)?,
+    );
+
+    person_ids.extend(
+        coach_team::table
+            .filter(coach_team::team_id.eq_any(&team_ids))
+            .filter(coach_team::until_date.is_null())
+            .select(coach_team::coach_id)
+            .load::<i64>(connection)?,
+    );
+
+    // I giocatori e gli allenatori senza un account non sono utenti
+    let mut user_ids: Vec<i64> = user::table
+        .filter(user::person_id.eq_any(&person_ids))
+        .select(user::person_id)
+        .load(connection)?;
+
+    user_ids.sort();
+    user_ids.dedup();
+
+    return Ok(user_ids);
+}
+
+/// Squadre e società sportive delle quali l'utente fa parte attualmente, per le condivisioni dei video.
+///
+/// L'utente fa parte delle squadre di cui è giocatore o allenatore, delle società sportive di cui è
+/// responsabile e delle società sportive di quelle squadre.
+pub fn find_person_groups(person_id: i64) -> Result<(Vec<i64>, Vec<String>), ApiError> {
+    use domain::schema::{coach_team, player_team, team, user_club};
+
+    let connection = &mut establish_connection();
+
+    let mut team_ids: Vec<i64> = player_team::table
+        .filter(player_team::player_id.eq(person_id))
+        .filter(player_team::until_date.is_null())
+        .select(player_team::team_id)
+        .load(connection)?;
+
+    team_ids.extend(
+        coach_team::table
+            .filter(coach_team::coach_id.eq(person_id))
+            .filter(coach_team::until_date.is_null())
+            .select(coach_team::team_id)
+            .load::<i64>(connection)?,
+    );
+
+    let mut club_ids: Vec<String> = user_club::table
+        .filter(user_club::user_id.eq(person_id))
+        .filter(user_club::until_date.is_null())
+        .select(user_club::club_id)
+        .load(connection)?;
+
+    club_ids.extend(
+        team::table
+            .filter(team::id.eq_any(&team_ids))
+            .select(team::club_id)
+            .load::<String>(connection)?,
+    );
+
+    return Ok((team_ids, club_ids));
+}
+
+/// Permessi concessi con una condivisione con una squadra.
+pub fn team_permissions(video_team: &VideoTeam) -> VideoPermissions {
+    return VideoPermissions {
+        read: video_team.read,
+        edit: video_team.edit,
+        delete: video_team.delete,
+        share: video_team.share,
+    };
+}
+
+/// Permessi concessi con una condivisione con una società sportiva.
+pub fn club_permissions(video_club: &VideoClub) -> VideoPermissions {
+    return VideoPermissions {
+        read: video_club.read,
+        edit: video_club.edit,
+        delete: video_club.delete,
+        share: video_club.share,
+    };
+}
+
+/// Condivisione del video con l'utente, se presente.
+pub fn find_video_user(person_id: i64, video_id: i64) -> Result<Option<VideoUser>, ApiError> {
+    use domain::schema::video_user;
+
+    let connection = &mut establish_connection();
+
+    let video_user = video_user::table
+        .filter(video_user::user_id.eq(person_id))
+        .filter(video_user::video_id.eq(video_id))
+        .select(VideoUser::as_select())
+        .first(connection)
+        .optional()?;
+
+    return Ok(video_user);
+}
+
+// Gli amministratori e chi può modificare la prenotazione hanno tutti i permessi sui video registrati
+fn can_manage_video(person_id: i64, video_id: i64) -> Result<bool, ApiError> {
+    if is_administrator(person_id)? {
+        return Ok(true);
+    }
+
+    let video = find_video(video_id)?;
+    let session = find_recording_session(video.session_id)?;
+
+    return can_edit_delete_booking(person_id, session.recording_session.booking_id);
+}
@@ -0,0 +1,69 @@
+use diesel::prelude::*;
+use diesel::result::Error;
+use domain::models::{full_tables::Camera, insertions::NewCamera};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::person_checks::is_administrator,
+    secrets::{self, secret_error},
+};
+
+pub fn authorize_create_camera(
+    requesting_user: Claims,
+    new_camera: NewCamera,
+) -> Result<Camera, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return create_camera(new_camera);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to create a camera",
+                requesting_user.subject_id
+            ),
+        });
+    }
+}
+
+/// Inserisce una nuova telecamera nel database e la restituisce.
+pub fn create_camera(mut new_camera: NewCamera) -> Result<Camera, ApiError> {
+    use domain::schema::camera;
+
+    new_camera.validate()?;
+
+    new_camera.password = secrets::encrypt(&new_camera.password).map_err(secret_error)?;
+
+    let connection = &mut establish_connection();
+
+    let inserted_camera: Camera = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(camera::table)
+            .values(&new_camera)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno la telecamera inserita si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id della telecamera appena inserita.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        camera::table
+            .order(camera::id.desc())
+            .select(Camera::as_select())
+            .first(connection)
+    }) {
+        Ok(c) => c,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new camera - {}", err),
+            })
+        }
+    };
+
+    return Ok(inserted_camera);
+}
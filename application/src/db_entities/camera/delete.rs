@@ -0,0 +1,66 @@
+use diesel::prelude::*;
+use domain::models::full_tables::Camera;
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims,
+    authorization::person_checks::is_administrator,
+    db_entities::camera::read::find_camera,
+    player::player::{self, StreamMap},
+};
+
+pub async fn authorize_delete_camera(
+    requesting_user: Claims,
+    camera_id: i64,
+    state: &StreamMap,
+) -> Result<Camera, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return delete_camera(camera_id, state).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
+
+pub async fn delete_camera(camera_id: i64, state: &StreamMap) -> Result<Camera, ApiError> {
+    use domain::schema::{camera, video};
+
+    let connection = &mut establish_connection();
+
+    let camera_to_delete = find_camera(camera_id)?;
+
+    // I video sono eliminati a cascata insieme alla telecamera, quindi una telecamera che
+    // ha registrato dei video può essere solo disabilitata
+    let video_count = video::table
+        .filter(video::camera_id.eq(camera_id))
+        .count()
+        .get_result::<i64>(connection)?;
+
+    if video_count > 0 {
+        return Err(ApiError {
+            http_status: Status::Conflict,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: format!(
+                "Error - Camera {} has recorded {} videos and can only be disabled",
+                camera_id, video_count
+            ),
+        });
+    }
+
+    let _ = player::stop(camera_id, state).await;
+
+    // Le associazioni con le sessioni di registrazione sono eliminate a cascata
+    diesel::delete(camera::table.filter(camera::id.eq(camera_id))).execute(connection)?;
+
+    return Ok(camera_to_delete);
+}
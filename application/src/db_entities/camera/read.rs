@@ -1,7 +1,26 @@
 use diesel::prelude::*;
 use domain::models::full_tables::Camera;
 use infrastructure::establish_connection;
-use shared::response_models::ApiError;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{authentication::Claims, authorization::person_checks::is_administrator};
+
+pub fn authorize_find_camera(requesting_user: Claims, camera_id: i64) -> Result<Camera, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return find_camera(camera_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
 
 pub fn find_camera(camera_id: i64) -> Result<Camera, ApiError> {
     use domain::schema::camera;
@@ -16,6 +35,22 @@ pub fn find_camera(camera_id: i64) -> Result<Camera, ApiError> {
     return Ok(camera);
 }
 
+pub fn authorize_list_cameras(requesting_user: Claims) -> Result<Vec<Camera>, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return list_cameras();
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the cameras",
+                requesting_user.subject_id
+            ),
+        });
+    }
+}
+
 pub fn list_cameras() -> Result<Vec<Camera>, ApiError> {
     use domain::schema::camera;
 
@@ -0,0 +1,57 @@
+use diesel::prelude::*;
+use domain::models::{full_tables::Camera, insertions::NewCamera, WithId};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::person_checks::is_administrator,
+    player::player::{self, StreamMap},
+    secrets::{self, secret_error},
+};
+
+pub async fn authorize_update_camera(
+    requesting_user: Claims,
+    camera_id: i64,
+    new_camera: NewCamera,
+    state: &StreamMap,
+) -> Result<Camera, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return update_camera(camera_id, new_camera, state).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to update camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
+
+pub async fn update_camera(
+    camera_id: i64,
+    mut new_camera: NewCamera,
+    state: &StreamMap,
+) -> Result<Camera, ApiError> {
+    let connection = &mut establish_connection();
+
+    new_camera.validate()?;
+
+    new_camera.password = secrets::encrypt(&new_camera.password).map_err(secret_error)?;
+
+    let camera = new_camera.to_identified(camera_id);
+
+    let updated_camera = camera.save_changes::<Camera>(connection)?;
+
+    // La stream di una telecamera disabilitata viene terminata, se attiva
+    if !updated_camera.enabled {
+        let _ = player::stop(updated_camera.id, state).await;
+    }
+
+    return Ok(updated_camera);
+}
@@ -3,5 +3,7 @@ pub mod camera;
 pub mod club;
 pub mod person;
 pub mod recording_session;
+pub mod storage;
 pub mod team;
 pub mod user;
+pub mod video;
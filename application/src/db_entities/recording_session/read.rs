@@ -1,4 +1,4 @@
-use chrono::offset;
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use domain::{
     models::{
@@ -120,6 +120,58 @@ pub fn list_recording_sessions_by_booking(
     return Ok(recording_sessions);
 }
 
+/// Restituisce le sessioni di registrazione in corso nell'istante specificato, con le relative telecamere.
+///
+/// Una sessione è in corso se `start_datetime <= instant < end_datetime`.
+pub fn list_recording_sessions_in_progress(
+    instant: NaiveDateTime,
+) -> Result<Vec<RecordingSessionWithCameras>, ApiError> {
+    use domain::schema::{camera_session, recording_session};
+
+    let connection = &mut establish_connection();
+
+    let mut recording_sessions: Vec<RecordingSessionWithCameras> = recording_session::table
+        .filter(recording_session::start_datetime.le(instant))
+        .filter(recording_session::end_datetime.gt(instant))
+        .select(RecordingSession::as_select())
+        .load(connection)?
+        .into_iter()
+        .map(|recording_session| RecordingSessionWithCameras {
+            recording_session,
+            cameras: Vec::new(),
+        })
+        .collect();
+
+    let cameras: Vec<(i64, Camera)> = camera_session::table
+        .filter(
+            camera_session::session_id
+                .eq_any(recording_sessions.iter().map(|s| s.recording_session.id)),
+        )
+        .inner_join(camera::table)
+        .select((camera_session::session_id, Camera::as_select()))
+        .load(connection)?;
+
+    merge_cameras(&mut recording_sessions, &cameras);
+
+    return Ok(recording_sessions);
+}
+
+/// Restituisce l'inizio della prima sessione di registrazione che inizia dopo l'istante specificato, se presente.
+pub fn next_recording_session_start(
+    instant: NaiveDateTime,
+) -> Result<Option<NaiveDateTime>, ApiError> {
+    use domain::schema::recording_session;
+
+    let connection = &mut establish_connection();
+
+    let next_start = recording_session::table
+        .filter(recording_session::start_datetime.gt(instant))
+        .select(diesel::dsl::min(recording_session::start_datetime))
+        .first::<Option<NaiveDateTime>>(connection)?;
+
+    return Ok(next_start);
+}
+
 fn merge_cameras(
     recording_sessions: &mut Vec<RecordingSessionWithCameras>,
     cameras: &[(i64, Camera)],
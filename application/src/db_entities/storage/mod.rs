@@ -0,0 +1,4 @@
+pub mod purge;
+pub mod quota;
+pub mod retention;
+pub mod usage;
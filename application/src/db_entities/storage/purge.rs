@@ -0,0 +1,60 @@
+use std::env;
+use std::time::Duration;
+
+use chrono::Local;
+use log::{error, info};
+use rocket::tokio::{task, time};
+
+use crate::db_entities::storage::retention::apply_retention_rules;
+
+/// Secondi tra due esecuzioni della pulizia dell'archivio, se non indicati nell'ambiente
+const DEFAULT_INTERVAL_SECONDS: u64 = 3600;
+/// Giorni tra l'avviso ai proprietari e l'eliminazione di un video, se non indicati nell'ambiente
+const DEFAULT_WARNING_DAYS: i64 = 7;
+
+/// Processo in background che applica periodicamente le regole di conservazione, avvisando i proprietari
+/// dei video in scadenza ed eliminando i video scaduti.
+///
+/// L'intervallo tra due esecuzioni e il preavviso dell'eliminazione possono essere configurati con le variabili
+/// d'ambiente `RETENTION_PURGE_INTERVAL_SECONDS` e `RETENTION_WARNING_DAYS`.
+pub async fn run() {
+    let mut interval = time::interval(Duration::from_secs(interval_seconds()));
+    let warning = chrono::Duration::days(warning_days());
+
+    loop {
+        interval.tick().await;
+
+        // Le query e l'eliminazione dei file vengono eseguite al di fuori del runtime asincrono
+        let now = Local::now().naive_local();
+        match task::spawn_blocking(move || apply_retention_rules(now, warning)).await {
+            Ok(Ok(deleted_videos)) if !deleted_videos.is_empty() => {
+                info!("Retention: deleted {} expired videos", deleted_videos.len());
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => error!("Retention: error applying the rules - {}", e.message),
+            Err(e) => error!("Retention: error applying the rules - {}", e),
+        }
+    }
+}
+
+fn interval_seconds() -> u64 {
+    match env::var("RETENTION_PURGE_INTERVAL_SECONDS") {
+        Ok(value) => value
+            .parse::<u64>()
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .expect("RETENTION_PURGE_INTERVAL_SECONDS must be a positive number."),
+        Err(_) => DEFAULT_INTERVAL_SECONDS,
+    }
+}
+
+fn warning_days() -> i64 {
+    match env::var("RETENTION_WARNING_DAYS") {
+        Ok(value) => value
+            .parse::<i64>()
+            .ok()
+            .filter(|days| *days >= 0)
+            .expect("RETENTION_WARNING_DAYS must be a non-negative number."),
+        Err(_) => DEFAULT_WARNING_DAYS,
+    }
+}
@@ -0,0 +1,206 @@
+use diesel::{prelude::*, result::Error};
+use domain::models::{full_tables::StorageQuota, insertions::NewStorageQuota, WithId};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{authentication::Claims, authorization::person_checks::is_administrator};
+
+pub fn authorize_create_storage_quota(
+    requesting_user: Claims,
+    new_quota: NewStorageQuota,
+) -> Result<StorageQuota, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return create_storage_quota(new_quota);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to create a storage quota",
+                requesting_user.subject_id
+            ),
+        });
+    }
+}
+
+/// Inserisce una nuova quota di spazio nel database e la restituisce.
+///
+/// Ogni società sportiva e ogni squadra può avere una sola quota.
+pub fn create_storage_quota(new_quota: NewStorageQuota) -> Result<StorageQuota, ApiError> {
+    use domain::schema::storage_quota;
+
+    new_quota.validate()?;
+
+    check_duplicate_quota(&new_quota, None)?;
+
+    let connection = &mut establish_connection();
+
+    let inserted_quota: StorageQuota = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(storage_quota::table)
+            .values(&new_quota)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno la quota inserita si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id della quota appena inserita.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        storage_quota::table
+            .order(storage_quota::id.desc())
+            .select(StorageQuota::as_select())
+            .first(connection)
+    }) {
+        Ok(q) => q,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new storage quota - {}", err),
+            })
+        }
+    };
+
+    return Ok(inserted_quota);
+}
+
+pub fn authorize_list_storage_quotas(
+    requesting_user: Claims,
+) -> Result<Vec<StorageQuota>, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return list_storage_quotas();
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to list the storage quotas",
+                requesting_user.subject_id
+            ),
+        });
+    }
+}
+
+pub fn list_storage_quotas() -> Result<Vec<StorageQuota>, ApiError> {
+    use domain::schema::storage_quota;
+
+    let connection = &mut establish_connection();
+
+    let quotas = storage_quota::table
+        .order(storage_quota::id.asc())
+        .select(StorageQuota::as_select())
+        .load(connection)?;
+
+    return Ok(quotas);
+}
+
+pub fn find_storage_quota(quota_id: i64) -> Result<StorageQuota, ApiError> {
+    use domain::schema::storage_quota;
+
+    let connection = &mut establish_connection();
+
+    let quota = storage_quota::table
+        .find(quota_id)
+        .select(StorageQuota::as_select())
+        .first(connection)?;
+
+    return Ok(quota);
+}
+
+pub fn authorize_update_storage_quota(
+    requesting_user: Claims,
+    quota_id: i64,
+    new_quota: NewStorageQuota,
+) -> Result<StorageQuota, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return update_storage_quota(quota_id, new_quota);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to update storage quota {}",
+                requesting_user.subject_id, quota_id
+            ),
+        });
+    }
+}
+
+pub fn update_storage_quota(
+    quota_id: i64,
+    new_quota: NewStorageQuota,
+) -> Result<StorageQuota, ApiError> {
+    new_quota.validate()?;
+
+    find_storage_quota(quota_id)?;
+    check_duplicate_quota(&new_quota, Some(quota_id))?;
+
+    let connection = &mut establish_connection();
+
+    let quota = new_quota.to_identified(quota_id);
+
+    let updated_quota = quota.save_changes::<StorageQuota>(connection)?;
+
+    return Ok(updated_quota);
+}
+
+pub fn authorize_delete_storage_quota(
+    requesting_user: Claims,
+    quota_id: i64,
+) -> Result<StorageQuota, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return delete_storage_quota(quota_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete storage quota {}",
+                requesting_user.subject_id, quota_id
+            ),
+        });
+    }
+}
+
+pub fn delete_storage_quota(quota_id: i64) -> Result<StorageQuota, ApiError> {
+    use domain::schema::storage_quota;
+
+    let connection = &mut establish_connection();
+
+    let quota_to_delete = find_storage_quota(quota_id)?;
+
+    diesel::delete(storage_quota::table.filter(storage_quota::id.eq(quota_id)))
+        .execute(connection)?;
+
+    return Ok(quota_to_delete);
+}
+
+fn check_duplicate_quota(
+    new_quota: &NewStorageQuota,
+    quota_id: Option<i64>,
+) -> Result<(), ApiError> {
+    let duplicate = list_storage_quotas()?.into_iter().find(|quota| {
+        Some(quota.id) != quota_id
+            && quota.club_id == new_quota.club_id
+            && quota.team_id == new_quota.team_id
+    });
+
+    match duplicate {
+        Some(quota) => {
+            return Err(ApiError {
+                http_status: Status::Conflict,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Storage quota {} already applies to the same club or team",
+                    quota.id
+                ),
+            });
+        }
+        None => return Ok(()),
+    }
+}
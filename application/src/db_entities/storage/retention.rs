@@ -0,0 +1,543 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{RetentionRule, Team, Video, VideoExpirationNotice, VideoKind},
+    insertions::NewRetentionRule,
+    WithId,
+};
+use infrastructure::establish_connection;
+use log::{info, warn};
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::{
+        person_checks::is_administrator,
+        team_checks::is_responsible_of_team,
+        user_checks::is_club_responsible,
+        video_checks::{can_delete_video, list_video_share_holders},
+    },
+    db_entities::{
+        storage::usage::video_teams,
+        video::{delete::delete_video, read::find_video},
+    },
+};
+
+/// Verifica se l'utente può gestire le regole di conservazione della società sportiva o della squadra indicata.
+///
+/// Gli amministratori gestiscono tutte le regole, comprese quelle globali, mentre i responsabili attuali
+/// di una società sportiva gestiscono le regole della società e delle sue squadre.
+pub fn can_manage_retention_rule(
+    user_id: i64,
+    club_id: Option<&String>,
+    team_id: Option<i64>,
+) -> Result<bool, ApiError> {
+    if is_administrator(user_id)? {
+        return Ok(true);
+    }
+
+    match (club_id, team_id) {
+        (Some(club_id), _) => return is_club_responsible(user_id, Some(club_id.clone()), true),
+        (None, Some(team_id)) => return is_responsible_of_team(user_id, team_id, true),
+        (None, None) => return Ok(false),
+    }
+}
+
+pub fn authorize_create_retention_rule(
+    requesting_user: Claims,
+    new_rule: NewRetentionRule,
+) -> Result<RetentionRule, ApiError> {
+    if can_manage_retention_rule(
+        requesting_user.subject_id,
+        new_rule.club_id.as_ref(),
+        new_rule.team_id,
+    )? {
+        return create_retention_rule(new_rule);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to create this retention rule",
+                requesting_user.subject_id
+            ),
+        });
+    }
+}
+
+/// Inserisce una nuova regola di conservazione nel database e la restituisce.
+///
+/// Per ogni tipo di video può esistere una sola regola globale, una sola regola per ciascuna società sportiva
+/// e una sola regola per ciascuna squadra.
+pub fn create_retention_rule(new_rule: NewRetentionRule) -> Result<RetentionRule, ApiError> {
+    use domain::schema::retention_rule;
+
+    new_rule.validate()?;
+
+    check_duplicate_rule(&new_rule, None)?;
+
+    let connection = &mut establish_connection();
+
+    let inserted_rule: RetentionRule = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(retention_rule::table)
+            .values(&new_rule)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno la regola inserita si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id della regola appena inserita.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        retention_rule::table
+            .order(retention_rule::id.desc())
+            .select(RetentionRule::as_select())
+            .first(connection)
+    }) {
+        Ok(r) => r,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new retention rule - {}", err),
+            })
+        }
+    };
+
+    return Ok(inserted_rule);
+}
+
+/// Restituisce le regole di conservazione visibili all'utente: le regole globali e quelle che l'utente può gestire.
+pub fn authorize_list_retention_rules(
+    requesting_user: Claims,
+) -> Result<Vec<RetentionRule>, ApiError> {
+    let mut rules = Vec::new();
+
+    for rule in list_retention_rules()? {
+        if (rule.club_id.is_none() && rule.team_id.is_none())
+            || can_manage_retention_rule(
+                requesting_user.subject_id,
+                rule.club_id.as_ref(),
+                rule.team_id,
+            )?
+        {
+            rules.push(rule);
+        }
+    }
+
+    return Ok(rules);
+}
+
+pub fn list_retention_rules() -> Result<Vec<RetentionRule>, ApiError> {
+    use domain::schema::retention_rule;
+
+    let connection = &mut establish_connection();
+
+    let rules = retention_rule::table
+        .order(retention_rule::id.asc())
+        .select(RetentionRule::as_select())
+        .load(connection)?;
+
+    return Ok(rules);
+}
+
+pub fn find_retention_rule(rule_id: i64) -> Result<RetentionRule, ApiError> {
+    use domain::schema::retention_rule;
+
+    let connection = &mut establish_connection();
+
+    let rule = retention_rule::table
+        .find(rule_id)
+        .select(RetentionRule::as_select())
+        .first(connection)?;
+
+    return Ok(rule);
+}
+
+pub fn authorize_update_retention_rule(
+    requesting_user: Claims,
+    rule_id: i64,
+    new_rule: NewRetentionRule,
+) -> Result<RetentionRule, ApiError> {
+    let rule = find_retention_rule(rule_id)?;
+
+    // La regola non può essere spostata su una società sportiva o su una squadra che l'utente non gestisce
+    if can_manage_retention_rule(
+        requesting_user.subject_id,
+        rule.club_id.as_ref(),
+        rule.team_id,
+    )? && can_manage_retention_rule(
+        requesting_user.subject_id,
+        new_rule.club_id.as_ref(),
+        new_rule.team_id,
+    )? {
+        return update_retention_rule(rule_id, new_rule);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to update retention rule {}",
+                requesting_user.subject_id, rule_id
+            ),
+        });
+    }
+}
+
+/// Aggiorna una regola di conservazione. Le nuove scadenze dei video vengono calcolate
+/// alla successiva esecuzione della pulizia dell'archivio.
+pub fn update_retention_rule(
+    rule_id: i64,
+    new_rule: NewRetentionRule,
+) -> Result<RetentionRule, ApiError> {
+    new_rule.validate()?;
+
+    check_duplicate_rule(&new_rule, Some(rule_id))?;
+
+    let connection = &mut establish_connection();
+
+    let rule = new_rule.to_identified(rule_id);
+
+    let updated_rule = rule.save_changes::<RetentionRule>(connection)?;
+
+    return Ok(updated_rule);
+}
+
+pub fn authorize_delete_retention_rule(
+    requesting_user: Claims,
+    rule_id: i64,
+) -> Result<RetentionRule, ApiError> {
+    let rule = find_retention_rule(rule_id)?;
+
+    if can_manage_retention_rule(
+        requesting_user.subject_id,
+        rule.club_id.as_ref(),
+        rule.team_id,
+    )? {
+        return delete_retention_rule(rule_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete retention rule {}",
+                requesting_user.subject_id, rule_id
+            ),
+        });
+    }
+}
+
+pub fn delete_retention_rule(rule_id: i64) -> Result<RetentionRule, ApiError> {
+    use domain::schema::retention_rule;
+
+    let connection = &mut establish_connection();
+
+    let rule_to_delete = find_retention_rule(rule_id)?;
+
+    diesel::delete(retention_rule::table.filter(retention_rule::id.eq(rule_id)))
+        .execute(connection)?;
+
+    return Ok(rule_to_delete);
+}
+
+// Una regola con lo stesso tipo di video e la stessa società sportiva o squadra renderebbe ambigua la scadenza dei video
+fn check_duplicate_rule(new_rule: &NewRetentionRule, rule_id: Option<i64>) -> Result<(), ApiError> {
+    let duplicate = list_retention_rules()?.into_iter().find(|rule| {
+        Some(rule.id) != rule_id
+            && rule.video_kind == new_rule.video_kind
+            && rule.club_id == new_rule.club_id
+            && rule.team_id == new_rule.team_id
+    });
+
+    match duplicate {
+        Some(rule) => {
+            return Err(ApiError {
+                http_status: Status::Conflict,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Retention rule {} already applies to the same videos",
+                    rule.id
+                ),
+            });
+        }
+        None => return Ok(()),
+    }
+}
+
+/// Restituisce per quanti giorni viene conservato un video del tipo indicato delle squadre indicate,
+/// o None se viene conservato per sempre.
+///
+/// Per ogni squadra si applica la regola della squadra, se esiste, altrimenti quella della sua società sportiva
+/// o, in mancanza di entrambe, la regola globale. Un video di più squadre viene conservato per il periodo
+/// più lungo tra quelli delle sue squadre. Senza alcuna regola applicabile il video viene conservato per sempre.
+pub fn retention_days(rules: &[RetentionRule], kind: VideoKind, teams: &[Team]) -> Option<i32> {
+    let applied_rules: Vec<Option<&RetentionRule>> = if teams.is_empty() {
+        vec![applied_rule(rules, kind, None)]
+    } else {
+        teams
+            .iter()
+            .map(|team| applied_rule(rules, kind, Some(team)))
+            .collect()
+    };
+
+    let mut longest = None;
+    for rule in applied_rules {
+        match rule.and_then(|rule| rule.retention_days) {
+            Some(days) => longest = longest.max(Some(days)),
+            None => return None,
+        }
+    }
+
+    return longest;
+}
+
+fn applied_rule<'a>(
+    rules: &'a [RetentionRule],
+    kind: VideoKind,
+    team: Option<&Team>,
+) -> Option<&'a RetentionRule> {
+    let rules_of_kind = || rules.iter().filter(move |rule| rule.video_kind == kind);
+
+    return team
+        .and_then(|team| rules_of_kind().find(|rule| rule.team_id == Some(team.id)))
+        .or_else(|| {
+            team.and_then(|team| {
+                rules_of_kind().find(|rule| rule.club_id.as_ref() == Some(&team.club_id))
+            })
+        })
+        .or_else(|| rules_of_kind().find(|rule| rule.club_id.is_none() && rule.team_id.is_none()));
+}
+
+/// Applica le regole di conservazione a tutti i video non fissati e restituisce gli ID dei video eliminati.
+///
+/// Per ogni video viene aggiornata la scadenza. Quando mancano meno di `warning` alla scadenza viene registrato
+/// un avviso per i proprietari e per chi ha accesso al video tramite una condivisione, e il video compare
+/// tra quelli in scadenza. Un video viene eliminato, insieme ai suoi file,
+/// solo quando è scaduto e sono passati almeno `warning` dall'avviso. Se la scadenza cambia, l'avviso
+/// viene ripetuto.
+pub fn apply_retention_rules(now: NaiveDateTime, warning: Duration) -> Result<Vec<i64>, ApiError> {
+    use domain::schema::{clip_video, video, video_expiration_notice};
+
+    let connection = &mut establish_connection();
+
+    let rules = list_retention_rules()?;
+    let video_teams = video_teams(connection)?;
+    let clip_ids: HashSet<i64> = clip_video::table
+        .select(clip_video::clip_id)
+        .load::<i64>(connection)?
+        .into_iter()
+        .collect();
+
+    let videos = video::table
+        .filter(video::pinned.eq(false))
+        .select(Video::as_select())
+        .load(connection)?;
+
+    let mut deleted_videos = Vec::new();
+
+    for video in videos {
+        let kind = if clip_ids.contains(&video.id) {
+            VideoKind::Clip
+        } else {
+            VideoKind::Recording
+        };
+        let teams = video_teams
+            .get(&video.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let expires_at = retention_days(&rules, kind, teams)
+            .map(|days| video.date + Duration::days(days as i64));
+        let mut warned_at = video.expiration_warned_at;
+
+        if expires_at != video.expires_at {
+            warned_at = None;
+            diesel::update(video::table.find(video.id))
+                .set((
+                    video::expires_at.eq(expires_at),
+                    video::expiration_warned_at.eq(warned_at),
+                ))
+                .execute(connection)?;
+            delete_expiration_notices(connection, video.id)?;
+        }
+
+        let expires_at = match expires_at {
+            Some(expires_at) => expires_at,
+            None => continue,
+        };
+
+        match warned_at {
+            None if now + warning >= expires_at => {
+                let user_ids = list_video_share_holders(video.id)?;
+
+                connection.transaction::<_, Error, _>(|connection| {
+                    diesel::update(video::table.find(video.id))
+                        .set(video::expiration_warned_at.eq(now))
+                        .execute(connection)?;
+
+                    delete_expiration_notices(connection, video.id)?;
+
+                    let notices: Vec<VideoExpirationNotice> = user_ids
+                        .iter()
+                        .map(|user_id| VideoExpirationNotice {
+                            video_id: video.id,
+                            user_id: *user_id,
+                            expires_at,
+                            notified_at: now,
+                        })
+                        .collect();
+
+                    diesel::insert_into(video_expiration_notice::table)
+                        .values(&notices)
+                        .execute(connection)?;
+
+                    return Ok(());
+                })?;
+
+                info!(
+                    "Retention: video {} expires at {}, {} owners and share holders have been notified",
+                    video.id,
+                    expires_at,
+                    user_ids.len()
+                );
+            }
+            Some(warned_at) if now >= expires_at && now >= warned_at + warning => {
+                match delete_video(video.id) {
+                    Ok(_) => {
+                        info!("Retention: video {} expired and has been deleted", video.id);
+                        deleted_videos.push(video.id);
+                    }
+                    Err(e) => warn!(
+                        "Retention: failed to delete video {} - {}",
+                        video.id, e.message
+                    ),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    return Ok(deleted_videos);
+}
+
+pub fn authorize_pin_video(
+    requesting_user: Claims,
+    video_id: i64,
+    pinned: bool,
+) -> Result<Video, ApiError> {
+    if can_delete_video(requesting_user.subject_id, video_id)? {
+        return pin_video(video_id, pinned);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to pin video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Fissa un video, che non viene più eliminato dalle regole di conservazione, o lo rimuove dai video fissati.
+///
+/// In entrambi i casi la scadenza e l'avviso vengono annullati: per un video rimosso dai video fissati
+/// vengono calcolati di nuovo alla successiva esecuzione della pulizia dell'archivio.
+pub fn pin_video(video_id: i64, pinned: bool) -> Result<Video, ApiError> {
+    use domain::schema::video;
+
+    let connection = &mut establish_connection();
+
+    find_video(video_id)?;
+
+    connection.transaction::<_, Error, _>(|connection| {
+        diesel::update(video::table.find(video_id))
+            .set((
+                video::pinned.eq(pinned),
+                video::expires_at.eq(None::<NaiveDateTime>),
+                video::expiration_warned_at.eq(None::<NaiveDateTime>),
+            ))
+            .execute(connection)?;
+
+        delete_expiration_notices(connection, video_id)?;
+
+        return Ok(());
+    })?;
+
+    return find_video(video_id);
+}
+
+/// Restituisce i video in scadenza che l'utente può eliminare, e che quindi può anche fissare.
+///
+/// Sono in scadenza i video per i quali è già stato inviato l'avviso di eliminazione.
+pub fn authorize_list_expiring_videos(requesting_user: Claims) -> Result<Vec<Video>, ApiError> {
+    let videos = list_expiring_videos()?;
+
+    if is_administrator(requesting_user.subject_id)? {
+        return Ok(videos);
+    }
+
+    let mut deletable_videos = Vec::new();
+    for video in videos {
+        if can_delete_video(requesting_user.subject_id, video.id)? {
+            deletable_videos.push(video);
+        }
+    }
+
+    return Ok(deletable_videos);
+}
+
+pub fn list_expiring_videos() -> Result<Vec<Video>, ApiError> {
+    use domain::schema::video;
+
+    let connection = &mut establish_connection();
+
+    let videos = video::table
+        .filter(video::pinned.eq(false))
+        .filter(video::expiration_warned_at.is_not_null())
+        .order(video::expires_at.asc())
+        .select(Video::as_select())
+        .load(connection)?;
+
+    return Ok(videos);
+}
+
+pub fn authorize_list_expiration_notices(
+    requesting_user: Claims,
+) -> Result<Vec<VideoExpirationNotice>, ApiError> {
+    return list_expiration_notices(requesting_user.subject_id);
+}
+
+/// Restituisce gli avvisi ricevuti dall'utente per i video in scadenza, dal video che scade per primo.
+pub fn list_expiration_notices(user_id: i64) -> Result<Vec<VideoExpirationNotice>, ApiError> {
+    use domain::schema::video_expiration_notice;
+
+    let connection = &mut establish_connection();
+
+    let notices = video_expiration_notice::table
+        .filter(video_expiration_notice::user_id.eq(user_id))
+        .order(video_expiration_notice::expires_at.asc())
+        .select(VideoExpirationNotice::as_select())
+        .load(connection)?;
+
+    return Ok(notices);
+}
+
+// Gli avvisi di un video non sono più validi quando cambia la sua scadenza o quando viene fissato
+fn delete_expiration_notices(connection: &mut MysqlConnection, video_id: i64) -> Result<(), Error> {
+    use domain::schema::video_expiration_notice;
+
+    diesel::delete(
+        video_expiration_notice::table.filter(video_expiration_notice::video_id.eq(video_id)),
+    )
+    .execute(connection)?;
+
+    return Ok(());
+}
@@ -0,0 +1,375 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::prelude::*;
+use domain::models::{
+    full_tables::{SportsClub, StorageQuota, Team},
+    others::{ClubStorageUsage, TeamStorageUsage},
+};
+use infrastructure::{establish_connection, storage::storage};
+use log::warn;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims,
+    authorization::{person_checks::is_administrator, user_checks::is_club_responsible},
+    player::{
+        annotations::annotations_prefix, archive::vod_prefix, chapters::chapters_prefix,
+        editing::screenshots_prefix, thumbnails::thumbnails_prefix,
+    },
+};
+
+// Cartelle dell'archivio con i file ottenuti da un video, contati nello spazio occupato dal video
+const VIDEO_FILE_PREFIXES: [fn(i64) -> String; 5] = [
+    vod_prefix,
+    thumbnails_prefix,
+    screenshots_prefix,
+    chapters_prefix,
+    annotations_prefix,
+];
+
+pub fn authorize_storage_usage(
+    requesting_user: Claims,
+    club_id: Option<String>,
+) -> Result<Vec<ClubStorageUsage>, ApiError> {
+    use domain::schema::user_club;
+
+    if is_administrator(requesting_user.subject_id)? {
+        return storage_usage(club_id.map(|club_id| vec![club_id]));
+    }
+
+    if let Some(club_id) = club_id {
+        if is_club_responsible(requesting_user.subject_id, Some(club_id.clone()), true)? {
+            return storage_usage(Some(vec![club_id]));
+        } else {
+            return Err(ApiError {
+                http_status: Status::Forbidden,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::AuthorizationError,
+                message: format!(
+                    "Error - User {} is not authorized to read the storage usage of club {}",
+                    requesting_user.subject_id, club_id
+                ),
+            });
+        }
+    }
+
+    let connection = &mut establish_connection();
+
+    // Senza una società sportiva indicata, vengono restituite tutte le società delle quali l'utente è responsabile
+    let club_ids = user_club::table
+        .filter(user_club::user_id.eq(requesting_user.subject_id))
+        .filter(user_club::until_date.is_null())
+        .select(user_club::club_id)
+        .load::<String>(connection)?;
+
+    if club_ids.is_empty() {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the storage usage",
+                requesting_user.subject_id
+            ),
+        });
+    }
+
+    return storage_usage(Some(club_ids));
+}
+
+/// Restituisce lo spazio occupato dai video delle società sportive indicate (o di tutte le società)
+/// e delle loro squadre, insieme alle loro quote.
+pub fn storage_usage(club_ids: Option<Vec<String>>) -> Result<Vec<ClubStorageUsage>, ApiError> {
+    use domain::schema::{sports_club, storage_quota, team};
+
+    let connection = &mut establish_connection();
+
+    let mut clubs_query = sports_club::table.into_boxed();
+    let mut teams_query = team::table.into_boxed();
+    if let Some(club_ids) = club_ids {
+        clubs_query = clubs_query.filter(sports_club::vat_number.eq_any(club_ids.clone()));
+        teams_query = teams_query.filter(team::club_id.eq_any(club_ids));
+    }
+
+    let clubs = clubs_query
+        .order(sports_club::name.asc())
+        .select(SportsClub::as_select())
+        .load(connection)?;
+    let teams = teams_query
+        .order(team::name.asc())
+        .select(Team::as_select())
+        .load(connection)?;
+    let quotas = storage_quota::table
+        .select(StorageQuota::as_select())
+        .load(connection)?;
+
+    let video_teams = video_teams(connection)?;
+    let sizes = video_sizes(connection, video_teams.keys().copied().collect())?;
+
+    let mut teams_by_club: HashMap<String, Vec<Team>> = HashMap::new();
+    for team in teams {
+        teams_by_club
+            .entry(team.club_id.clone())
+            .or_default()
+            .push(team);
+    }
+
+    return Ok(clubs
+        .into_iter()
+        .map(|club| {
+            let (used_bytes, video_count) =
+                usage_of(&video_teams, &sizes, |team| team.club_id == club.vat_number);
+
+            let teams = teams_by_club
+                .remove(&club.vat_number)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|team| {
+                    let (used_bytes, video_count) =
+                        usage_of(&video_teams, &sizes, |t| t.id == team.id);
+
+                    TeamStorageUsage {
+                        quota_bytes: quotas
+                            .iter()
+                            .find(|q| q.team_id == Some(team.id))
+                            .map(|q| q.quota_bytes),
+                        team,
+                        used_bytes,
+                        video_count,
+                    }
+                })
+                .collect();
+
+            ClubStorageUsage {
+                quota_bytes: quotas
+                    .iter()
+                    .find(|q| q.club_id.as_deref() == Some(club.vat_number.as_str()))
+                    .map(|q| q.quota_bytes),
+                club,
+                used_bytes,
+                video_count,
+                teams,
+            }
+        })
+        .collect());
+}
+
+/// Verifica che le squadre del video e le loro società sportive non abbiano esaurito la loro quota di spazio,
+/// prima di salvare un nuovo file ottenuto dal video.
+pub fn check_storage_quota(video_id: i64) -> Result<(), ApiError> {
+    use domain::schema::storage_quota;
+
+    let connection = &mut establish_connection();
+
+    let video_teams = video_teams(connection)?;
+    let teams = match video_teams.get(&video_id) {
+        Some(teams) => teams,
+        None => return Ok(()),
+    };
+
+    let quotas: Vec<StorageQuota> = storage_quota::table
+        .select(StorageQuota::as_select())
+        .load::<StorageQuota>(connection)?
+        .into_iter()
+        .filter(|quota| {
+            teams.iter().any(|team| {
+                quota.team_id == Some(team.id)
+                    || quota.club_id.as_deref() == Some(team.club_id.as_str())
+            })
+        })
+        .collect();
+
+    // Le dimensioni dei file vengono calcolate solo se c'è una quota da verificare
+    if quotas.is_empty() {
+        return Ok(());
+    }
+
+    let sizes = video_sizes(connection, video_teams.keys().copied().collect())?;
+
+    for quota in quotas {
+        let (used_bytes, _) = usage_of(&video_teams, &sizes, |team| {
+            quota.team_id == Some(team.id) || quota.club_id.as_ref() == Some(&team.club_id)
+        });
+
+        if used_bytes >= quota.quota_bytes {
+            let owner = match (&quota.club_id, quota.team_id) {
+                (Some(club_id), _) => format!("club {}", club_id),
+                (None, Some(team_id)) => format!("team {}", team_id),
+                (None, None) => String::new(),
+            };
+
+            return Err(ApiError {
+                http_status: Status::InsufficientStorage,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - The videos of {} use {} of {} bytes of their storage quota",
+                    owner, used_bytes, quota.quota_bytes
+                ),
+            });
+        }
+    }
+
+    return Ok(());
+}
+
+/// Restituisce, per ciascun video, le squadre dell'allenamento o della partita durante i quali è stato registrato.
+///
+/// Le clip appartengono alle stesse squadre del video dal quale sono state estratte, perché fanno parte
+/// della stessa sessione di registrazione. I video senza squadre non compaiono nel risultato.
+pub fn video_teams(connection: &mut MysqlConnection) -> Result<HashMap<i64, Vec<Team>>, ApiError> {
+    return load_video_teams(connection, None);
+}
+
+/// Restituisce le squadre dell'allenamento o della partita durante i quali è stato registrato il video.
+pub fn teams_of_video(
+    connection: &mut MysqlConnection,
+    video_id: i64,
+) -> Result<Vec<Team>, ApiError> {
+    return Ok(load_video_teams(connection, Some(video_id))?
+        .remove(&video_id)
+        .unwrap_or_default());
+}
+
+// Squadre dei video, di tutti o solo di quello indicato
+fn load_video_teams(
+    connection: &mut MysqlConnection,
+    video_id: Option<i64>,
+) -> Result<HashMap<i64, Vec<Team>>, ApiError> {
+    use domain::schema::{formation, game, recording_session, team, training, video};
+
+    let mut training_teams = video::table
+        .inner_join(recording_session::table)
+        .inner_join(training::table.on(training::booking_id.eq(recording_session::booking_id)))
+        .inner_join(team::table.on(team::id.eq(training::team_id)))
+        .select((video::id, Team::as_select()))
+        .into_boxed();
+
+    let mut home_teams = video::table
+        .inner_join(recording_session::table)
+        .inner_join(game::table.on(game::booking_id.eq(recording_session::booking_id)))
+        .inner_join(formation::table.on(formation::id.eq(game::home_formation_id)))
+        .inner_join(team::table.on(team::id.eq(formation::team_id)))
+        .select((video::id, Team::as_select()))
+        .into_boxed();
+
+    let mut visiting_teams = video::table
+        .inner_join(recording_session::table)
+        .inner_join(game::table.on(game::booking_id.eq(recording_session::booking_id)))
+        .inner_join(formation::table.on(game::visiting_formation_id.eq(formation::id.nullable())))
+        .inner_join(team::table.on(team::id.eq(formation::team_id)))
+        .select((video::id, Team::as_select()))
+        .into_boxed();
+
+    if let Some(video_id) = video_id {
+        training_teams = training_teams.filter(video::id.eq(video_id));
+        home_teams = home_teams.filter(video::id.eq(video_id));
+        visiting_teams = visiting_teams.filter(video::id.eq(video_id));
+    }
+
+    let mut video_teams: HashMap<i64, Vec<Team>> = HashMap::new();
+    for (video_id, team) in training_teams
+        .load::<(i64, Team)>(connection)?
+        .into_iter()
+        .chain(home_teams.load::<(i64, Team)>(connection)?)
+        .chain(visiting_teams.load::<(i64, Team)>(connection)?)
+    {
+        let teams = video_teams.entry(video_id).or_default();
+        if !teams.contains(&team) {
+            teams.push(team);
+        }
+    }
+
+    return Ok(video_teams);
+}
+
+// Dimensione in byte dei file di ciascun video: il file del video, la cui dimensione è salvata tra le sue
+// caratteristiche tecniche (e per i video dei quali non è nota viene letta dall'archivio dei file multimediali),
+// e i file ottenuti dal video, cioè la versione on demand, gli screenshot, le anteprime, i capitoli e le annotazioni.
+// Le clip e i video di highlights sono video a loro volta e non vengono contati
+fn video_sizes(
+    connection: &mut MysqlConnection,
+    video_ids: HashSet<i64>,
+) -> Result<HashMap<i64, i64>, ApiError> {
+    use domain::schema::video;
+
+    let locations = video::table
+        .filter(video::id.eq_any(video_ids))
+        .select((video::id, video::file_location, video::file_size))
+        .load::<(i64, String, Option<i64>)>(connection)?;
+
+    let storage = storage();
+
+    let mut sizes: HashMap<i64, i64> = locations
+        .iter()
+        .map(|(video_id, file_location, file_size)| {
+            if let Some(file_size) = file_size {
+                return (*video_id, *file_size);
+            }
+
+            // Un file mancante non occupa spazio
+            let size = match storage.size(file_location) {
+                Ok(size) => size as i64,
+                Err(e) => {
+                    warn!(
+                        "Storage: failed to read the size of video {} - {}",
+                        video_id, e
+                    );
+                    0
+                }
+            };
+            (*video_id, size)
+        })
+        .collect();
+
+    // Ogni cartella viene elencata una sola volta, e i suoi file vengono assegnati ai video
+    // in base alla sottocartella del video (come `vod/video_1`) che li contiene
+    for prefix in VIDEO_FILE_PREFIXES {
+        let video_dirs: HashMap<String, i64> = locations
+            .iter()
+            .map(|(video_id, _, _)| (prefix(*video_id), *video_id))
+            .collect();
+
+        let Some((folder, _)) = video_dirs
+            .keys()
+            .next()
+            .and_then(|dir| dir.rsplit_once('/'))
+        else {
+            continue;
+        };
+
+        let objects = match storage.list_sizes(&format!("{}/", folder)) {
+            Ok(objects) => objects,
+            Err(e) => {
+                warn!("Storage: failed to list the files in {} - {}", folder, e);
+                continue;
+            }
+        };
+
+        for (key, size) in objects {
+            // Le cartelle dei video sono sempre al secondo livello, come `vod/video_1/manifest.mpd`
+            let video_dir = key.splitn(3, '/').take(2).collect::<Vec<_>>().join("/");
+
+            if let Some(video_id) = video_dirs.get(&video_dir) {
+                *sizes.entry(*video_id).or_default() += size as i64;
+            }
+        }
+    }
+
+    return Ok(sizes);
+}
+
+// Byte occupati e numero dei video che appartengono ad almeno una delle squadre accettate da `belongs`
+fn usage_of(
+    video_teams: &HashMap<i64, Vec<Team>>,
+    sizes: &HashMap<i64, i64>,
+    belongs: impl Fn(&Team) -> bool,
+) -> (i64, i64) {
+    return video_teams
+        .iter()
+        .filter(|(_, teams)| teams.iter().any(&belongs))
+        .fold((0, 0), |(bytes, count), (video_id, _)| {
+            (bytes + sizes.get(video_id).copied().unwrap_or(0), count + 1)
+        });
+}
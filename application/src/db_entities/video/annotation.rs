@@ -0,0 +1,550 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{Local, NaiveTime};
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{Annotation, AnnotationShapes},
+    insertions::NewAnnotation,
+    others::{AnnotationData, AnnotationRequest},
+};
+use infrastructure::{establish_connection, storage::read_location};
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::{media::authorize_media_token, Claims},
+    authorization::video_checks::{can_delete_video, can_edit_video, can_read_video},
+    db_entities::video::{
+        file::{media_file, not_authorized_to_read, video_scope, MediaFile},
+        read::find_video,
+        rendered::{discard_rendered, is_rendered, render_file, rendered_key},
+        screenshot::find_screenshot,
+        time_marker::find_time_marker,
+    },
+    player::{
+        annotations::{annotations_prefix, render_annotations, AnnotationLayer},
+        editing::{extract_frame, ffmpeg_position},
+    },
+};
+
+/// Numero massimo di annotazioni di uno screenshot o di un segnaposto, che vengono disegnate tutte
+/// sulla stessa immagine
+pub const MAX_ANNOTATIONS_PER_TARGET: i64 = 20;
+
+pub fn authorize_create_annotation(
+    requesting_user: Claims,
+    video_id: i64,
+    request: AnnotationRequest,
+) -> Result<Annotation, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return create_annotation(video_id, requesting_user.subject_id, request);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to add annotations to video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Inserisce una nuova annotazione dell'utente `author_id` su uno screenshot o sul fotogramma di un
+/// segnaposto del video e la restituisce.
+///
+/// L'annotazione non viene inserita se lo screenshot o il segnaposto ha già `MAX_ANNOTATIONS_PER_TARGET` annotazioni.
+pub fn create_annotation(
+    video_id: i64,
+    author_id: i64,
+    request: AnnotationRequest,
+) -> Result<Annotation, ApiError> {
+    use domain::schema::annotation;
+
+    request.validate()?;
+
+    // Lo screenshot o il segnaposto devono appartenere al video
+    if let Some(screenshot_id) = request.screenshot_id {
+        find_screenshot(video_id, screenshot_id)?;
+    }
+    if let Some(time_marker_id) = request.time_marker_id {
+        find_time_marker(video_id, time_marker_id)?;
+    }
+
+    let connection = &mut establish_connection();
+
+    let annotation_count = annotation::table
+        .filter(
+            annotation::screenshot_id
+                .eq(request.screenshot_id)
+                .or(annotation::time_marker_id.eq(request.time_marker_id)),
+        )
+        .count()
+        .get_result::<i64>(connection)?;
+
+    if annotation_count >= MAX_ANNOTATIONS_PER_TARGET {
+        return Err(ApiError {
+            http_status: Status::BadRequest,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: format!(
+                "Error - The screenshot or the time marker already has {} annotations",
+                MAX_ANNOTATIONS_PER_TARGET
+            ),
+        });
+    }
+
+    let now = Local::now().naive_local();
+    let new_annotation = NewAnnotation {
+        screenshot_id: request.screenshot_id,
+        time_marker_id: request.time_marker_id,
+        author_id,
+        label: request.annotation.label,
+        shapes: AnnotationShapes(request.annotation.shapes),
+        created_at: now,
+        updated_at: now,
+    };
+
+    let inserted_annotation: Annotation =
+        match connection.transaction::<_, Error, _>(|connection| {
+            diesel::insert_into(annotation::table)
+                .values(&new_annotation)
+                .execute(connection)?;
+
+            // NB: questo metodo per ottenere in ritorno l'annotazione inserita si affida al fatto che gli id siano autoincrementali.
+            // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id dell'annotazione appena inserita.
+            // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+            annotation::table
+                .order(annotation::id.desc())
+                .select(Annotation::as_select())
+                .first(connection)
+        }) {
+            Ok(a) => a,
+            Err(err) => {
+                return Err(ApiError {
+                    http_status: Status::InternalServerError,
+                    error_code: 123,
+                    error_type: ApiErrorType::ApplicationError,
+                    message: format!("Error while inserting new annotation - {}", err),
+                });
+            }
+        };
+
+    discard_annotated_images(
+        video_id,
+        inserted_annotation.screenshot_id,
+        inserted_annotation.time_marker_id,
+    );
+
+    return Ok(inserted_annotation);
+}
+
+pub fn authorize_list_annotations(
+    requesting_user: Claims,
+    video_id: i64,
+    screenshot_id: Option<i64>,
+    time_marker_id: Option<i64>,
+) -> Result<Vec<Annotation>, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return list_annotations(video_id, screenshot_id, time_marker_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce le annotazioni degli screenshot e dei segnaposti del video, in ordine di inserimento.
+///
+/// Le annotazioni possono essere filtrate per screenshot o per segnaposto.
+pub fn list_annotations(
+    video_id: i64,
+    screenshot_id: Option<i64>,
+    time_marker_id: Option<i64>,
+) -> Result<Vec<Annotation>, ApiError> {
+    use domain::schema::{annotation, screenshot, time_marker};
+
+    let connection = &mut establish_connection();
+
+    // Il video deve esistere, altrimenti la lista vuota non sarebbe distinguibile da un video senza annotazioni
+    find_video(video_id)?;
+
+    let mut query = annotation::table
+        .left_join(screenshot::table)
+        .left_join(time_marker::table)
+        .filter(
+            screenshot::video_id
+                .nullable()
+                .eq(video_id)
+                .or(time_marker::video_id.nullable().eq(video_id)),
+        )
+        .select(Annotation::as_select())
+        .into_boxed();
+
+    if let Some(screenshot_id) = screenshot_id {
+        query = query.filter(annotation::screenshot_id.eq(screenshot_id));
+    }
+    if let Some(time_marker_id) = time_marker_id {
+        query = query.filter(annotation::time_marker_id.eq(time_marker_id));
+    }
+
+    let annotations = query.order(annotation::id.asc()).load(connection)?;
+
+    return Ok(annotations);
+}
+
+pub fn authorize_find_annotation(
+    requesting_user: Claims,
+    video_id: i64,
+    annotation_id: i64,
+) -> Result<Annotation, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return find_annotation(video_id, annotation_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce un'annotazione di uno screenshot o di un segnaposto del video dato il suo ID.
+pub fn find_annotation(video_id: i64, annotation_id: i64) -> Result<Annotation, ApiError> {
+    use domain::schema::{annotation, screenshot, time_marker};
+
+    let connection = &mut establish_connection();
+
+    let annotation = annotation::table
+        .left_join(screenshot::table)
+        .left_join(time_marker::table)
+        .filter(annotation::id.eq(annotation_id))
+        .filter(
+            screenshot::video_id
+                .nullable()
+                .eq(video_id)
+                .or(time_marker::video_id.nullable().eq(video_id)),
+        )
+        .select(Annotation::as_select())
+        .get_result(connection)?;
+
+    return Ok(annotation);
+}
+
+pub fn authorize_update_annotation(
+    requesting_user: Claims,
+    video_id: i64,
+    annotation_id: i64,
+    data: AnnotationData,
+) -> Result<Annotation, ApiError> {
+    if can_change_annotation(requesting_user.subject_id, video_id, annotation_id)? {
+        return update_annotation(video_id, annotation_id, data);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to edit annotation {} of video {}",
+                requesting_user.subject_id, annotation_id, video_id
+            ),
+        });
+    }
+}
+
+/// Sostituisce l'etichetta e le forme di un'annotazione del video e la restituisce aggiornata.
+pub fn update_annotation(
+    video_id: i64,
+    annotation_id: i64,
+    data: AnnotationData,
+) -> Result<Annotation, ApiError> {
+    use domain::schema::annotation;
+
+    data.validate()?;
+
+    let mut annotation_to_update = find_annotation(video_id, annotation_id)?;
+    annotation_to_update.label = data.label;
+    annotation_to_update.shapes = AnnotationShapes(data.shapes);
+    annotation_to_update.updated_at = Local::now().naive_local();
+
+    let connection = &mut establish_connection();
+
+    diesel::update(annotation::table.filter(annotation::id.eq(annotation_id)))
+        .set(&annotation_to_update)
+        .execute(connection)?;
+
+    discard_annotated_images(
+        video_id,
+        annotation_to_update.screenshot_id,
+        annotation_to_update.time_marker_id,
+    );
+
+    return Ok(annotation_to_update);
+}
+
+pub fn authorize_delete_annotation(
+    requesting_user: Claims,
+    video_id: i64,
+    annotation_id: i64,
+) -> Result<Annotation, ApiError> {
+    if can_change_annotation(requesting_user.subject_id, video_id, annotation_id)? {
+        return delete_annotation(video_id, annotation_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete annotation {} of video {}",
+                requesting_user.subject_id, annotation_id, video_id
+            ),
+        });
+    }
+}
+
+/// Elimina un'annotazione del video e la restituisce.
+pub fn delete_annotation(video_id: i64, annotation_id: i64) -> Result<Annotation, ApiError> {
+    use domain::schema::annotation;
+
+    let annotation_to_delete = find_annotation(video_id, annotation_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::delete(annotation::table.filter(annotation::id.eq(annotation_id)))
+        .execute(connection)?;
+
+    discard_annotated_images(
+        video_id,
+        annotation_to_delete.screenshot_id,
+        annotation_to_delete.time_marker_id,
+    );
+
+    return Ok(annotation_to_delete);
+}
+
+// L'autore di un'annotazione può modificarla finché può modificare il video,
+// chi può eliminare il video può modificare anche le annotazioni degli altri
+fn can_change_annotation(
+    person_id: i64,
+    video_id: i64,
+    annotation_id: i64,
+) -> Result<bool, ApiError> {
+    if can_delete_video(person_id, video_id)? {
+        return Ok(true);
+    }
+
+    if !can_edit_video(person_id, video_id)? {
+        return Ok(false);
+    }
+
+    let annotation = find_annotation(video_id, annotation_id)?;
+
+    return Ok(annotation.author_id == person_id);
+}
+
+pub async fn authorize_annotated_screenshot(
+    requesting_user: Claims,
+    video_id: i64,
+    screenshot_id: i64,
+) -> Result<MediaFile, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return annotated_screenshot(video_id, screenshot_id).await;
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce lo screenshot annotato richiesto tramite un URL firmato del video.
+pub async fn signed_annotated_screenshot(
+    token: &str,
+    video_id: i64,
+    screenshot_id: i64,
+) -> Result<MediaFile, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return annotated_screenshot(video_id, screenshot_id).await;
+}
+
+/// Restituisce l'immagine dello screenshot con le sue annotazioni disegnate sopra, da esportare o condividere.
+///
+/// Uno screenshot senza annotazioni viene restituito così com'è.
+pub async fn annotated_screenshot(
+    video_id: i64,
+    screenshot_id: i64,
+) -> Result<MediaFile, ApiError> {
+    let screenshot = find_screenshot(video_id, screenshot_id)?;
+    let annotations = list_annotations(video_id, Some(screenshot_id), None)?;
+
+    if annotations.is_empty() {
+        return Ok(media_file(&screenshot.file_location, &screenshot.name));
+    }
+
+    let file_location = render_annotated_image(
+        video_id,
+        &screenshot_target(screenshot_id),
+        FrameSource::Image(screenshot.file_location),
+        annotations,
+    )
+    .await?;
+
+    return Ok(media_file(&file_location, &screenshot.name));
+}
+
+pub async fn authorize_annotated_time_marker(
+    requesting_user: Claims,
+    video_id: i64,
+    marker_id: i64,
+) -> Result<MediaFile, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return annotated_time_marker(video_id, marker_id).await;
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce il fotogramma annotato di un segnaposto richiesto tramite un URL firmato del video.
+pub async fn signed_annotated_time_marker(
+    token: &str,
+    video_id: i64,
+    marker_id: i64,
+) -> Result<MediaFile, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return annotated_time_marker(video_id, marker_id).await;
+}
+
+/// Restituisce il fotogramma del video all'istante del segnaposto con le annotazioni del segnaposto
+/// disegnate sopra, da esportare o condividere.
+pub async fn annotated_time_marker(video_id: i64, marker_id: i64) -> Result<MediaFile, ApiError> {
+    let video = find_video(video_id)?;
+    let marker = find_time_marker(video_id, marker_id)?;
+    let annotations = list_annotations(video_id, None, Some(marker_id))?;
+
+    let file_location = render_annotated_image(
+        video_id,
+        &time_marker_target(marker_id),
+        FrameSource::Video(video.file_location, marker.instant),
+        annotations,
+    )
+    .await?;
+
+    return Ok(media_file(&file_location, &marker.name));
+}
+
+/// Elimina le immagini annotate dello screenshot o del segnaposto indicato, quando le sue annotazioni
+/// o l'immagine di partenza cambiano.
+pub fn discard_annotated_images(
+    video_id: i64,
+    screenshot_id: Option<i64>,
+    time_marker_id: Option<i64>,
+) {
+    let targets = screenshot_id
+        .map(screenshot_target)
+        .into_iter()
+        .chain(time_marker_id.map(time_marker_target));
+
+    for target in targets {
+        discard_rendered(&format!("{}/{}", annotations_prefix(video_id), target));
+    }
+}
+
+// Cartelle delle annotazioni del video con le immagini annotate di uno screenshot e di un segnaposto
+fn screenshot_target(screenshot_id: i64) -> String {
+    format!("screenshot_{}", screenshot_id)
+}
+
+fn time_marker_target(marker_id: i64) -> String {
+    format!("marker_{}", marker_id)
+}
+
+// Immagine sulla quale vengono disegnate le annotazioni
+enum FrameSource {
+    /// Chiave di un'immagine nell'archivio dei file multimediali
+    Image(String),
+    /// Chiave di un video nell'archivio e istante del fotogramma
+    Video(String, NaiveTime),
+}
+
+// Disegna le annotazioni sull'immagine e restituisce la chiave dell'immagine annotata nell'archivio.
+//
+// L'immagine viene salvata nella cartella `target` delle annotazioni del video e riutilizzata finché
+// le annotazioni e l'immagine di partenza non cambiano.
+async fn render_annotated_image(
+    video_id: i64,
+    target: &str,
+    source: FrameSource,
+    annotations: Vec<Annotation>,
+) -> Result<String, ApiError> {
+    let layers: Vec<AnnotationLayer> = annotations
+        .into_iter()
+        .map(|annotation| AnnotationLayer {
+            label: annotation.label,
+            shapes: annotation.shapes.0,
+        })
+        .collect();
+
+    // La chiave dell'immagine dipende dall'immagine di partenza e dalle annotazioni
+    let source_id = match &source {
+        FrameSource::Image(file_location) => file_location.clone(),
+        FrameSource::Video(file_location, instant) => {
+            format!("{}@{}", file_location, ffmpeg_position(*instant))
+        }
+    };
+    let layers_json = serde_json::to_string(
+        &layers
+            .iter()
+            .map(|layer| (&layer.label, &layer.shapes))
+            .collect::<Vec<_>>(),
+    )
+    .unwrap_or_default();
+    let file_location = rendered_key(
+        &format!("{}/{}", annotations_prefix(video_id), target),
+        &[source_id.as_bytes(), layers_json.as_bytes()],
+        "jpg",
+    );
+
+    if is_rendered(&file_location) {
+        return Ok(file_location);
+    }
+
+    let rendered = render_file(file_location.clone(), move |output| {
+        let dir = output.with_extension("parts");
+
+        // Il fotogramma di un segnaposto viene estratto accanto all'immagine annotata
+        let frame = output.with_extension("frame.jpg");
+        let image = match source {
+            FrameSource::Image(file_location) => {
+                read_location(&file_location).map_err(|e| e.to_string())?
+            }
+            FrameSource::Video(file_location, instant) => {
+                let video = read_location(&file_location).map_err(|e| e.to_string())?;
+                if !extract_frame(&video, instant, &frame)? {
+                    return Err(format!(
+                        "the video has no frame at {}",
+                        ffmpeg_position(instant)
+                    ));
+                }
+                frame.to_string_lossy().to_string()
+            }
+        };
+
+        let result = render_annotations(&image, &layers, &dir, output);
+        let _ = fs::remove_file(&frame);
+        result
+    })
+    .await;
+
+    match rendered {
+        Ok(()) => return Ok(file_location),
+        Err(e) => return Err(annotations_error(video_id, e)),
+    }
+}
+
+fn annotations_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while drawing the annotations of video {} - {}",
+            video_id, message
+        ),
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+use domain::models::full_tables::Video;
+use infrastructure::storage::read_location;
+use log::{info, warn};
+use rocket::{http::Status, tokio::task};
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::{media::authorize_media_token, Claims},
+    authorization::video_checks::can_read_video,
+    db_entities::video::{
+        file::{media_file, not_authorized_to_read, video_scope, MediaFile},
+        metadata::probe_video_file,
+        read::find_video,
+        rendered::{is_rendered, render_file, rendered_key},
+        time_marker::list_time_markers,
+    },
+    player::chapters::{
+        chapters_from_markers, chapters_prefix, embed_chapters, ffmetadata_chapters,
+        webvtt_chapters, Chapter,
+    },
+};
+
+// Copie dei video con i capitoli in preparazione, per chiave: None mentre la copia viene preparata,
+// il motivo se la preparazione non è riuscita
+static CHAPTERS_JOBS: LazyLock<Mutex<HashMap<String, Option<String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Formati nei quali vengono esportati i capitoli di un video.
+#[derive(Debug, Clone, Copy)]
+pub enum ChaptersFormat {
+    /// Traccia WebVTT dei capitoli
+    WebVtt,
+    /// File di metadati di FFmpeg
+    FfMetadata,
+}
+
+pub async fn authorize_chapters(
+    requesting_user: Claims,
+    video_id: i64,
+    format: ChaptersFormat,
+) -> Result<String, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return chapters(video_id, format).await;
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce i capitoli di un video richiesti tramite un URL firmato del video.
+pub async fn signed_chapters(
+    token: &str,
+    video_id: i64,
+    format: ChaptersFormat,
+) -> Result<String, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return chapters(video_id, format).await;
+}
+
+/// Restituisce i capitoli del video nel formato richiesto.
+///
+/// Ogni segnaposto del video inizia un capitolo, con il nome del segnaposto, che termina al segnaposto
+/// successivo o alla fine del video.
+pub async fn chapters(video_id: i64, format: ChaptersFormat) -> Result<String, ApiError> {
+    let video = find_video(video_id)?;
+
+    let chapters = video_chapters(&video).await?;
+
+    match format {
+        ChaptersFormat::WebVtt => return Ok(webvtt_chapters(&chapters)),
+        ChaptersFormat::FfMetadata => return Ok(ffmetadata_chapters(&video.name, &chapters)),
+    }
+}
+
+pub async fn authorize_chapters_video(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<Option<MediaFile>, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return chapters_video(video_id).await;
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce il file del video con i capitoli richiesto tramite un URL firmato del video.
+pub async fn signed_chapters_video(
+    token: &str,
+    video_id: i64,
+) -> Result<Option<MediaFile>, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return chapters_video(video_id).await;
+}
+
+/// Restituisce una copia del file del video con i capitoli incorporati, che i player desktop
+/// mostrano anche senza connessione, o None se la copia non è ancora pronta.
+///
+/// La copia viene preparata in background alla prima richiesta, salvata nell'archivio dei file multimediali
+/// e riutilizzata finché i segnaposti del video non cambiano. Se la preparazione non è riuscita viene
+/// restituito l'errore, e la richiesta successiva la avvia di nuovo. Un video senza segnaposti viene
+/// restituito così com'è.
+pub async fn chapters_video(video_id: i64) -> Result<Option<MediaFile>, ApiError> {
+    let video = find_video(video_id)?;
+
+    let chapters = video_chapters(&video).await?;
+    if chapters.is_empty() {
+        return Ok(Some(media_file(&video.file_location, &video.name)));
+    }
+
+    let metadata = ffmetadata_chapters(&video.name, &chapters);
+    let file_location = rendered_key(
+        &chapters_prefix(video_id),
+        &[video.file_location.as_bytes(), metadata.as_bytes()],
+        "mp4",
+    );
+
+    if is_rendered(&file_location) {
+        return Ok(Some(media_file(&file_location, &video.name)));
+    }
+
+    let mut jobs = CHAPTERS_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    match jobs.get(&file_location) {
+        Some(None) => return Ok(None),
+        Some(Some(_)) => {
+            let error = jobs.remove(&file_location).flatten().unwrap_or_default();
+            return Err(chapters_error(video_id, error));
+        }
+        None => {}
+    }
+
+    jobs.insert(file_location.clone(), None);
+    task::spawn(embed_video_chapters(
+        video_id,
+        video.file_location,
+        metadata,
+        file_location,
+    ));
+
+    return Ok(None);
+}
+
+// Prepara la copia del video con i capitoli e ne registra l'esito tra le copie in preparazione
+async fn embed_video_chapters(
+    video_id: i64,
+    video_location: String,
+    metadata: String,
+    file_location: String,
+) {
+    let result = match read_location(&video_location) {
+        Ok(input) => {
+            render_file(file_location.clone(), move |output| {
+                embed_chapters(&input, &metadata, output)
+            })
+            .await
+        }
+        Err(e) => Err(e.to_string()),
+    };
+
+    let mut jobs = CHAPTERS_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    match result {
+        Ok(()) => {
+            info!("Chapters: embedded the chapters of video {}", video_id);
+            jobs.remove(&file_location);
+        }
+        Err(e) => {
+            warn!(
+                "Chapters: failed to embed the chapters of video {} - {}",
+                video_id, e
+            );
+            jobs.insert(file_location, Some(e));
+        }
+    }
+}
+
+// Capitoli del video ottenuti dai suoi segnaposti.
+// Se la durata del video non è nota viene letta dal file, per terminare l'ultimo capitolo
+async fn video_chapters(video: &Video) -> Result<Vec<Chapter>, ApiError> {
+    let markers = list_time_markers(video.id)?;
+    if markers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let duration_ms = match video.metadata.duration_ms {
+        Some(duration_ms) => duration_ms,
+        None => match probe_video_file(&video.file_location).await {
+            Ok(metadata) => match metadata.duration_ms {
+                Some(duration_ms) => duration_ms,
+                None => {
+                    return Err(chapters_error(
+                        video.id,
+                        "the duration of the video is not known".to_string(),
+                    ))
+                }
+            },
+            Err(e) => return Err(chapters_error(video.id, e)),
+        },
+    };
+
+    return Ok(chapters_from_markers(&markers, duration_ms));
+}
+
+fn chapters_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while exporting the chapters of video {} - {}",
+            video_id, message
+        ),
+    }
+}
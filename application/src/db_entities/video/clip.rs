@@ -0,0 +1,233 @@
+use chrono::{Local, NaiveTime};
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{ClipVideo, Video, VideoClub, VideoTeam, VideoUser},
+    insertions::NewVideo,
+    others::ClipRequest,
+};
+use infrastructure::{
+    establish_connection,
+    storage::{read_location, storage, working_path},
+};
+use log::warn;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::can_edit_video,
+    db_entities::{
+        storage::usage::check_storage_quota,
+        video::{
+            metadata::{check_instant, probe_new_video_file},
+            read::find_video,
+            thumbnails::create_thumbnails,
+        },
+    },
+    player::{
+        blocking::run_blocking,
+        editing::{clips_prefix, cut_clip, ffmpeg_position},
+    },
+};
+
+pub async fn authorize_create_clip(
+    requesting_user: Claims,
+    video_id: i64,
+    request: ClipRequest,
+) -> Result<Video, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return create_clip(video_id, request).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to cut clips from video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Estrae una parte del video in un nuovo file e la inserisce nel database come un nuovo video,
+/// collegato al video originale tramite la tabella `clip_video`.
+///
+/// La clip viene condivisa con gli stessi utenti e con gli stessi permessi del video originale,
+/// e ne vengono generate le anteprime. La clip non viene estratta se le squadre del video o le loro
+/// società sportive hanno esaurito la loro quota di spazio.
+pub async fn create_clip(video_id: i64, request: ClipRequest) -> Result<Video, ApiError> {
+    use domain::schema::{clip_video, video, video_club, video_team, video_user};
+
+    request.validate()?;
+
+    let original_video = find_video(video_id)?;
+
+    // Le registrazioni vengono sempre salvate, mentre le nuove clip vengono rifiutate a quota esaurita
+    check_storage_quota(video_id)?;
+
+    // Una clip che termina oltre la fine del video viene estratta fino alla fine del video
+    check_instant(&original_video, request.start)?;
+
+    let file_location = format!(
+        "{}/{}.mp4",
+        clips_prefix(video_id),
+        Local::now().naive_local().format("%Y%m%d-%H%M%S%3f")
+    );
+    let output = working_path(&file_location);
+
+    let input = read_location(&original_video.file_location)
+        .map_err(|e| clip_error(video_id, e.to_string()))?;
+
+    let (start, end, mode) = (request.start, request.end, request.mode);
+    let clip_output = output.clone();
+    let cut = run_blocking(move || cut_clip(&input, start, end, mode, &clip_output)).await;
+
+    match cut {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Video {} ends before {}",
+                    video_id,
+                    ffmpeg_position(start)
+                ),
+            });
+        }
+        Err(e) => return Err(clip_error(video_id, e)),
+    }
+
+    storage()
+        .put(&file_location, &output)
+        .map_err(|e| clip_error(video_id, e.to_string()))?;
+
+    let metadata = probe_new_video_file(&file_location).await;
+
+    let new_video = NewVideo {
+        file_location: file_location.clone(),
+        name: request.name,
+        // La data della clip è il momento nel quale è stata registrata la sua prima immagine
+        date: original_video.date + (start - NaiveTime::MIN),
+        capture_start: original_video
+            .capture_start
+            .map(|capture_start| capture_start + (start - NaiveTime::MIN)),
+        notes: request.notes,
+        session_id: original_video.session_id,
+        camera_id: original_video.camera_id,
+        metadata,
+    };
+
+    let connection = &mut establish_connection();
+
+    let inserted_clip: Video = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(video::table)
+            .values(&new_video)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno la clip inserita si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id della clip appena inserita.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        let clip: Video = video::table
+            .order(video::id.desc())
+            .select(Video::as_select())
+            .first(connection)?;
+
+        diesel::insert_into(clip_video::table)
+            .values(&ClipVideo {
+                original_video_id: original_video.id,
+                clip_id: clip.id,
+                start_instant: Some(start),
+            })
+            .execute(connection)?;
+
+        let clip_users: Vec<VideoUser> = video_user::table
+            .filter(video_user::video_id.eq(original_video.id))
+            .select(VideoUser::as_select())
+            .load(connection)?
+            .into_iter()
+            .map(|video_user| VideoUser {
+                video_id: clip.id,
+                ..video_user
+            })
+            .collect();
+
+        diesel::insert_into(video_user::table)
+            .values(&clip_users)
+            .execute(connection)?;
+
+        let clip_teams: Vec<VideoTeam> = video_team::table
+            .filter(video_team::video_id.eq(original_video.id))
+            .select(VideoTeam::as_select())
+            .load(connection)?
+            .into_iter()
+            .map(|video_team| VideoTeam {
+                video_id: clip.id,
+                ..video_team
+            })
+            .collect();
+
+        diesel::insert_into(video_team::table)
+            .values(&clip_teams)
+            .execute(connection)?;
+
+        let clip_clubs: Vec<VideoClub> = video_club::table
+            .filter(video_club::video_id.eq(original_video.id))
+            .select(VideoClub::as_select())
+            .load(connection)?
+            .into_iter()
+            .map(|video_club| VideoClub {
+                video_id: clip.id,
+                ..video_club
+            })
+            .collect();
+
+        diesel::insert_into(video_club::table)
+            .values(&clip_clubs)
+            .execute(connection)?;
+
+        Ok(clip)
+    }) {
+        Ok(v) => v,
+        Err(err) => {
+            // Senza la riga nel database il file della clip non sarebbe più raggiungibile
+            if let Err(e) = storage().delete(&file_location) {
+                warn!("Clip: failed to remove {} - {}", file_location, e);
+            }
+
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new clip - {}", err),
+            });
+        }
+    };
+
+    // La clip è utilizzabile anche senza anteprime, che possono essere generate di nuovo in seguito
+    match create_thumbnails(inserted_clip.id).await {
+        Ok(clip) => return Ok(clip),
+        Err(e) => {
+            warn!(
+                "Clip: failed to generate the thumbnails of clip {} - {}",
+                inserted_clip.id, e.message
+            );
+            return Ok(inserted_clip);
+        }
+    }
+}
+
+fn clip_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while cutting a clip from video {} - {}",
+            video_id, message
+        ),
+    }
+}
@@ -0,0 +1,603 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{VideoComment, VideoCommentMention, VideoCommentRevision},
+    insertions::{NewVideoComment, NewVideoCommentRevision},
+    others::{VideoCommentData, VideoCommentDetails, VideoCommentRequest, VideoCommentThread},
+};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::{can_delete_video, can_edit_video, can_read_video},
+    db_entities::video::{file::not_authorized_to_read, metadata::check_instant, read::find_video},
+};
+
+pub fn authorize_create_video_comment(
+    requesting_user: Claims,
+    video_id: i64,
+    request: VideoCommentRequest,
+) -> Result<VideoCommentThread, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return create_video_comment(video_id, requesting_user.subject_id, request);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Inserisce una nuova discussione dell'utente `author_id` nel video, ancorata all'istante o all'intervallo
+/// richiesto, e la restituisce.
+pub fn create_video_comment(
+    video_id: i64,
+    author_id: i64,
+    request: VideoCommentRequest,
+) -> Result<VideoCommentThread, ApiError> {
+    request.validate()?;
+
+    let video = find_video(video_id)?;
+    for instant in [request.start_instant, request.end_instant]
+        .into_iter()
+        .flatten()
+    {
+        check_instant(&video, instant)?;
+    }
+
+    let new_comment = NewVideoComment {
+        video_id,
+        author_id,
+        parent_id: None,
+        start_instant: request.start_instant,
+        end_instant: request.end_instant,
+        body: request.comment.body,
+        created_at: Local::now().naive_local(),
+    };
+
+    let comment = insert_video_comment(new_comment, &request.comment.mentions)?;
+
+    return find_video_comment_thread(video_id, comment.id);
+}
+
+pub fn authorize_reply_video_comment(
+    requesting_user: Claims,
+    video_id: i64,
+    comment_id: i64,
+    data: VideoCommentData,
+) -> Result<VideoCommentThread, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return reply_video_comment(video_id, requesting_user.subject_id, comment_id, data);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Inserisce la risposta dell'utente `author_id` nella discussione del commento e restituisce la discussione.
+///
+/// Le risposte a una risposta vengono aggiunte alla stessa discussione, che ha un solo livello di risposte.
+pub fn reply_video_comment(
+    video_id: i64,
+    author_id: i64,
+    comment_id: i64,
+    data: VideoCommentData,
+) -> Result<VideoCommentThread, ApiError> {
+    data.validate()?;
+
+    let comment = find_video_comment(video_id, comment_id)?;
+    let thread_id = comment.parent_id.unwrap_or(comment.id);
+
+    let new_comment = NewVideoComment {
+        video_id,
+        author_id,
+        parent_id: Some(thread_id),
+        start_instant: None,
+        end_instant: None,
+        body: data.body,
+        created_at: Local::now().naive_local(),
+    };
+
+    insert_video_comment(new_comment, &data.mentions)?;
+
+    return find_video_comment_thread(video_id, thread_id);
+}
+
+// Inserisce il commento insieme alle sue menzioni, dopo aver controllato che gli utenti menzionati
+// possano leggere il video
+fn insert_video_comment(
+    new_comment: NewVideoComment,
+    mentions: &[i64],
+) -> Result<VideoComment, ApiError> {
+    use domain::schema::{video_comment, video_comment_mention};
+
+    let mentions = check_mentions(new_comment.video_id, mentions)?;
+
+    let connection = &mut establish_connection();
+
+    let inserted_comment: VideoComment = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(video_comment::table)
+            .values(&new_comment)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno il commento inserito si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id del commento appena inserito.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        let comment = video_comment::table
+            .order(video_comment::id.desc())
+            .select(VideoComment::as_select())
+            .first(connection)?;
+
+        let new_mentions: Vec<VideoCommentMention> = mentions
+            .iter()
+            .map(|user_id| VideoCommentMention {
+                comment_id: comment.id,
+                user_id: *user_id,
+            })
+            .collect();
+
+        diesel::insert_into(video_comment_mention::table)
+            .values(&new_mentions)
+            .execute(connection)?;
+
+        Ok(comment)
+    }) {
+        Ok(c) => c,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new comment - {}", err),
+            });
+        }
+    };
+
+    return Ok(inserted_comment);
+}
+
+pub fn authorize_list_video_comments(
+    requesting_user: Claims,
+    video_id: i64,
+    resolved: Option<bool>,
+) -> Result<Vec<VideoCommentThread>, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return list_video_comments(video_id, resolved);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce le discussioni del video ordinate per istante, a partire da quelle sul video intero,
+/// eventualmente solo quelle risolte o non risolte.
+pub fn list_video_comments(
+    video_id: i64,
+    resolved: Option<bool>,
+) -> Result<Vec<VideoCommentThread>, ApiError> {
+    use domain::schema::video_comment;
+
+    let connection = &mut establish_connection();
+
+    // Il video deve esistere, altrimenti la lista vuota non sarebbe distinguibile da un video senza commenti
+    find_video(video_id)?;
+
+    let mut query = video_comment::table
+        .filter(video_comment::video_id.eq(video_id))
+        .filter(video_comment::parent_id.is_null())
+        .select(VideoComment::as_select())
+        .into_boxed();
+
+    if let Some(resolved) = resolved {
+        query = query.filter(video_comment::resolved.eq(resolved));
+    }
+
+    let threads = query
+        .order((
+            video_comment::start_instant.asc(),
+            video_comment::created_at.asc(),
+            video_comment::id.asc(),
+        ))
+        .load(connection)?;
+
+    let replies = video_comment::table
+        .filter(video_comment::video_id.eq(video_id))
+        .filter(video_comment::parent_id.eq_any(threads.iter().map(|c| c.id).collect::<Vec<_>>()))
+        .order(video_comment::id.asc())
+        .select(VideoComment::as_select())
+        .load(connection)?;
+
+    return build_threads(threads, replies);
+}
+
+pub fn authorize_find_video_comment(
+    requesting_user: Claims,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<VideoCommentThread, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return find_video_comment_thread(video_id, comment_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce la discussione del video alla quale appartiene il commento, che può esserne il primo
+/// commento o una risposta.
+pub fn find_video_comment_thread(
+    video_id: i64,
+    comment_id: i64,
+) -> Result<VideoCommentThread, ApiError> {
+    use domain::schema::video_comment;
+
+    let comment = find_video_comment(video_id, comment_id)?;
+    let thread = match comment.parent_id {
+        Some(parent_id) => find_video_comment(video_id, parent_id)?,
+        None => comment,
+    };
+
+    let connection = &mut establish_connection();
+
+    let replies = video_comment::table
+        .filter(video_comment::parent_id.eq(thread.id))
+        .order(video_comment::id.asc())
+        .select(VideoComment::as_select())
+        .load(connection)?;
+
+    let mut threads = build_threads(vec![thread], replies)?;
+
+    return Ok(threads.remove(0));
+}
+
+/// Restituisce un commento del video dato il suo ID.
+pub fn find_video_comment(video_id: i64, comment_id: i64) -> Result<VideoComment, ApiError> {
+    use domain::schema::video_comment;
+
+    let connection = &mut establish_connection();
+
+    let comment = video_comment::table
+        .filter(video_comment::id.eq(comment_id))
+        .filter(video_comment::video_id.eq(video_id))
+        .select(VideoComment::as_select())
+        .get_result(connection)?;
+
+    return Ok(comment);
+}
+
+pub fn authorize_update_video_comment(
+    requesting_user: Claims,
+    video_id: i64,
+    comment_id: i64,
+    data: VideoCommentData,
+) -> Result<VideoCommentDetails, ApiError> {
+    // L'autore può modificare il commento solo finché può leggere il video
+    if can_read_video(requesting_user.subject_id, video_id)?
+        && find_video_comment(video_id, comment_id)?.author_id == requesting_user.subject_id
+    {
+        return update_video_comment(video_id, comment_id, data);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to edit comment {} of video {}",
+                requesting_user.subject_id, comment_id, video_id
+            ),
+        });
+    }
+}
+
+/// Sostituisce il testo e le menzioni di un commento del video e lo restituisce aggiornato.
+///
+/// Se il testo cambia, quello precedente viene salvato nella cronologia delle modifiche del commento.
+pub fn update_video_comment(
+    video_id: i64,
+    comment_id: i64,
+    data: VideoCommentData,
+) -> Result<VideoCommentDetails, ApiError> {
+    use domain::schema::{video_comment, video_comment_mention, video_comment_revision};
+
+    data.validate()?;
+
+    let mut comment = find_video_comment(video_id, comment_id)?;
+    let mentions = check_mentions(video_id, &data.mentions)?;
+
+    let connection = &mut establish_connection();
+
+    connection.transaction::<_, Error, _>(|connection| {
+        if comment.body != data.body {
+            let now = Local::now().naive_local();
+
+            diesel::insert_into(video_comment_revision::table)
+                .values(&NewVideoCommentRevision {
+                    comment_id,
+                    body: comment.body.clone(),
+                    replaced_at: now,
+                })
+                .execute(connection)?;
+
+            comment.body = data.body;
+            comment.edited_at = Some(now);
+
+            diesel::update(video_comment::table.filter(video_comment::id.eq(comment_id)))
+                .set(&comment)
+                .execute(connection)?;
+        }
+
+        diesel::delete(
+            video_comment_mention::table.filter(video_comment_mention::comment_id.eq(comment_id)),
+        )
+        .execute(connection)?;
+
+        let new_mentions: Vec<VideoCommentMention> = mentions
+            .iter()
+            .map(|user_id| VideoCommentMention {
+                comment_id,
+                user_id: *user_id,
+            })
+            .collect();
+
+        diesel::insert_into(video_comment_mention::table)
+            .values(&new_mentions)
+            .execute(connection)?;
+
+        Ok(())
+    })?;
+
+    return Ok(VideoCommentDetails { comment, mentions });
+}
+
+pub fn authorize_video_comment_history(
+    requesting_user: Claims,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Vec<VideoCommentRevision>, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return video_comment_history(video_id, comment_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce i testi precedenti di un commento del video, dal più vecchio al più recente.
+pub fn video_comment_history(
+    video_id: i64,
+    comment_id: i64,
+) -> Result<Vec<VideoCommentRevision>, ApiError> {
+    use domain::schema::video_comment_revision;
+
+    find_video_comment(video_id, comment_id)?;
+
+    let connection = &mut establish_connection();
+
+    let revisions = video_comment_revision::table
+        .filter(video_comment_revision::comment_id.eq(comment_id))
+        .order(video_comment_revision::id.asc())
+        .select(VideoCommentRevision::as_select())
+        .load(connection)?;
+
+    return Ok(revisions);
+}
+
+pub fn authorize_delete_video_comment(
+    requesting_user: Claims,
+    video_id: i64,
+    comment_id: i64,
+) -> Result<VideoComment, ApiError> {
+    let is_author = can_read_video(requesting_user.subject_id, video_id)?
+        && find_video_comment(video_id, comment_id)?.author_id == requesting_user.subject_id;
+
+    if is_author || can_delete_video(requesting_user.subject_id, video_id)? {
+        return delete_video_comment(video_id, comment_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete comment {} of video {}",
+                requesting_user.subject_id, comment_id, video_id
+            ),
+        });
+    }
+}
+
+/// Elimina un commento del video e lo restituisce. Eliminando il primo commento di una discussione
+/// vengono eliminate anche le sue risposte.
+pub fn delete_video_comment(video_id: i64, comment_id: i64) -> Result<VideoComment, ApiError> {
+    use domain::schema::video_comment;
+
+    let comment_to_delete = find_video_comment(video_id, comment_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::delete(video_comment::table.filter(video_comment::id.eq(comment_id)))
+        .execute(connection)?;
+
+    return Ok(comment_to_delete);
+}
+
+pub fn authorize_resolve_video_comment(
+    requesting_user: Claims,
+    video_id: i64,
+    comment_id: i64,
+    resolved: bool,
+) -> Result<VideoCommentThread, ApiError> {
+    // Può risolvere una discussione chi l'ha iniziata o chi può modificare il video
+    let comment = find_video_comment(video_id, comment_id)?;
+    let thread = match comment.parent_id {
+        Some(parent_id) => find_video_comment(video_id, parent_id)?,
+        None => comment,
+    };
+
+    let is_author = can_read_video(requesting_user.subject_id, video_id)?
+        && thread.author_id == requesting_user.subject_id;
+
+    if is_author || can_edit_video(requesting_user.subject_id, video_id)? {
+        return resolve_video_comment(video_id, thread.id, requesting_user.subject_id, resolved);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to resolve comment {} of video {}",
+                requesting_user.subject_id, comment_id, video_id
+            ),
+        });
+    }
+}
+
+/// Segna come risolta o non risolta la discussione del commento, da parte dell'utente `user_id`,
+/// e restituisce la discussione.
+pub fn resolve_video_comment(
+    video_id: i64,
+    comment_id: i64,
+    user_id: i64,
+    resolved: bool,
+) -> Result<VideoCommentThread, ApiError> {
+    use domain::schema::video_comment;
+
+    let comment = find_video_comment(video_id, comment_id)?;
+    let thread_id = comment.parent_id.unwrap_or(comment.id);
+
+    let (resolved_by, resolved_at) = match resolved {
+        true => (Some(user_id), Some(Local::now().naive_local())),
+        false => (None, None),
+    };
+
+    let connection = &mut establish_connection();
+
+    diesel::update(video_comment::table.filter(video_comment::id.eq(thread_id)))
+        .set((
+            video_comment::resolved.eq(resolved),
+            video_comment::resolved_by.eq(resolved_by),
+            video_comment::resolved_at.eq(resolved_at),
+        ))
+        .execute(connection)?;
+
+    return find_video_comment_thread(video_id, thread_id);
+}
+
+/// Restituisce i commenti nei quali l'utente è stato menzionato, dal più recente, dei soli video
+/// che l'utente può ancora leggere.
+pub fn authorize_list_video_comment_mentions(
+    requesting_user: Claims,
+) -> Result<Vec<VideoCommentDetails>, ApiError> {
+    use domain::schema::{video_comment, video_comment_mention};
+
+    let connection = &mut establish_connection();
+
+    let comments = video_comment::table
+        .inner_join(video_comment_mention::table)
+        .filter(video_comment_mention::user_id.eq(requesting_user.subject_id))
+        .order(video_comment::id.desc())
+        .select(VideoComment::as_select())
+        .load(connection)?;
+
+    // I permessi vengono controllati una sola volta per ogni video
+    let mut readable_videos: HashMap<i64, bool> = HashMap::new();
+    let mut readable_comments = Vec::new();
+    for comment in comments {
+        let readable = match readable_videos.get(&comment.video_id) {
+            Some(readable) => *readable,
+            None => {
+                let readable = can_read_video(requesting_user.subject_id, comment.video_id)?;
+                readable_videos.insert(comment.video_id, readable);
+                readable
+            }
+        };
+
+        if readable {
+            readable_comments.push(comment);
+        }
+    }
+
+    return with_mentions(readable_comments);
+}
+
+// Restituisce gli utenti da menzionare senza ripetizioni, dopo aver controllato che possano leggere il video
+fn check_mentions(video_id: i64, mentions: &[i64]) -> Result<Vec<i64>, ApiError> {
+    let mut checked: Vec<i64> = Vec::new();
+
+    for user_id in mentions {
+        if checked.contains(user_id) {
+            continue;
+        }
+
+        if !can_read_video(*user_id, video_id)? {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - User {} can't be mentioned because they can't read video {}",
+                    user_id, video_id
+                ),
+            });
+        }
+
+        checked.push(*user_id);
+    }
+
+    return Ok(checked);
+}
+
+// Aggiunge ai commenti gli utenti menzionati
+fn with_mentions(comments: Vec<VideoComment>) -> Result<Vec<VideoCommentDetails>, ApiError> {
+    use domain::schema::video_comment_mention;
+
+    let connection = &mut establish_connection();
+
+    let mentions = video_comment_mention::table
+        .filter(
+            video_comment_mention::comment_id
+                .eq_any(comments.iter().map(|c| c.id).collect::<Vec<_>>()),
+        )
+        .select(VideoCommentMention::as_select())
+        .load(connection)?;
+
+    let mut mentions_by_comment: HashMap<i64, Vec<i64>> = HashMap::new();
+    for mention in mentions {
+        mentions_by_comment
+            .entry(mention.comment_id)
+            .or_default()
+            .push(mention.user_id);
+    }
+
+    let details = comments
+        .into_iter()
+        .map(|comment| VideoCommentDetails {
+            mentions: mentions_by_comment.remove(&comment.id).unwrap_or_default(),
+            comment,
+        })
+        .collect();
+
+    return Ok(details);
+}
+
+// Raggruppa le risposte sotto il primo commento della rispettiva discussione, mantenendo l'ordine delle discussioni
+fn build_threads(
+    threads: Vec<VideoComment>,
+    replies: Vec<VideoComment>,
+) -> Result<Vec<VideoCommentThread>, ApiError> {
+    let mut replies_by_thread: HashMap<i64, Vec<VideoCommentDetails>> = HashMap::new();
+    for reply in with_mentions(replies)? {
+        if let Some(parent_id) = reply.comment.parent_id {
+            replies_by_thread.entry(parent_id).or_default().push(reply);
+        }
+    }
+
+    let threads = with_mentions(threads)?
+        .into_iter()
+        .map(|thread| VideoCommentThread {
+            replies: replies_by_thread
+                .remove(&thread.comment.id)
+                .unwrap_or_default(),
+            comment: thread,
+        })
+        .collect();
+
+    return Ok(threads);
+}
@@ -0,0 +1,38 @@
+use diesel::{prelude::*, result::Error};
+use domain::models::{full_tables::Video, insertions::NewVideo};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+/// Inserisce un nuovo video nel database e lo restituisce.
+pub fn create_video(new_video: NewVideo) -> Result<Video, ApiError> {
+    use domain::schema::video;
+
+    let connection = &mut establish_connection();
+
+    let inserted_video: Video = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(video::table)
+            .values(&new_video)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno il video inserito si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id del video appena inserito.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        video::table
+            .order(video::id.desc())
+            .select(Video::as_select())
+            .first(connection)
+    }) {
+        Ok(v) => v,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new video - {}", err),
+            })
+        }
+    };
+
+    return Ok(inserted_video);
+}
@@ -0,0 +1,77 @@
+use diesel::prelude::*;
+use domain::models::full_tables::Video;
+use infrastructure::{
+    establish_connection,
+    storage::{delete_dir, storage},
+};
+use log::warn;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::can_delete_video,
+    db_entities::video::read::find_video,
+    player::{
+        annotations::annotations_prefix, archive::vod_prefix, chapters::chapters_prefix,
+        thumbnails::thumbnails_prefix,
+    },
+};
+
+pub fn authorize_delete_video(requesting_user: Claims, video_id: i64) -> Result<Video, ApiError> {
+    if can_delete_video(requesting_user.subject_id, video_id)? {
+        return delete_video(video_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Elimina il video dal database insieme al suo file, ai file dei suoi screenshot, alle sue anteprime,
+/// alla sua versione on demand e alle sue copie con i capitoli, e lo restituisce.
+///
+/// Screenshot, segnaposti e condivisioni vengono eliminati a cascata. Le clip estratte dal video
+/// sono video indipendenti e non vengono eliminate.
+pub fn delete_video(video_id: i64) -> Result<Video, ApiError> {
+    use domain::schema::{screenshot, video};
+
+    let connection = &mut establish_connection();
+
+    let video_to_delete = find_video(video_id)?;
+
+    let screenshot_locations = screenshot::table
+        .filter(screenshot::video_id.eq(video_id))
+        .select(screenshot::file_location)
+        .load::<String>(connection)?;
+
+    diesel::delete(video::table.filter(video::id.eq(video_id))).execute(connection)?;
+
+    // Un file che non può essere eliminato non impedisce l'eliminazione del video
+    for file_location in
+        std::iter::once(&video_to_delete.file_location).chain(&screenshot_locations)
+    {
+        if let Err(e) = storage().delete(file_location) {
+            warn!("Video: failed to remove {} - {}", file_location, e);
+        }
+    }
+
+    for prefix in [
+        thumbnails_prefix(video_id),
+        vod_prefix(video_id),
+        chapters_prefix(video_id),
+        annotations_prefix(video_id),
+    ] {
+        if let Err(e) = delete_dir(&prefix) {
+            warn!("Video: failed to remove {} - {}", prefix, e);
+        }
+    }
+
+    return Ok(video_to_delete);
+}
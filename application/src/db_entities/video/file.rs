@@ -0,0 +1,155 @@
+use std::path::Path;
+
+use domain::models::others::SignedUrl;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::{
+        media::{authorize_media_token, create_media_token},
+        Claims,
+    },
+    authorization::video_checks::can_read_video,
+    db_entities::video::{read::find_video, screenshot::find_screenshot},
+};
+
+/// File di un video o di uno screenshot da inviare al client.
+pub struct MediaFile {
+    /// Chiave del file nell'archivio dei file multimediali
+    pub key: String,
+    /// Nome proposto al client per salvare il file
+    pub file_name: String,
+}
+
+pub fn authorize_video_file(requesting_user: Claims, video_id: i64) -> Result<MediaFile, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return video_file(video_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce il file di un video richiesto tramite un URL firmato.
+pub fn signed_video_file(token: &str, video_id: i64) -> Result<MediaFile, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return video_file(video_id);
+}
+
+/// Restituisce il file del video, che sia una registrazione o una clip.
+pub fn video_file(video_id: i64) -> Result<MediaFile, ApiError> {
+    let video = find_video(video_id)?;
+
+    return Ok(media_file(&video.file_location, &video.name));
+}
+
+pub fn authorize_screenshot_file(
+    requesting_user: Claims,
+    video_id: i64,
+    screenshot_id: i64,
+) -> Result<MediaFile, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return screenshot_file(video_id, screenshot_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce l'immagine di uno screenshot richiesta tramite un URL firmato del suo video.
+pub fn signed_screenshot_file(
+    token: &str,
+    video_id: i64,
+    screenshot_id: i64,
+) -> Result<MediaFile, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return screenshot_file(video_id, screenshot_id);
+}
+
+/// Restituisce l'immagine di uno screenshot del video.
+pub fn screenshot_file(video_id: i64, screenshot_id: i64) -> Result<MediaFile, ApiError> {
+    let screenshot = find_screenshot(video_id, screenshot_id)?;
+
+    return Ok(media_file(&screenshot.file_location, &screenshot.name));
+}
+
+pub fn authorize_sign_video(requesting_user: Claims, video_id: i64) -> Result<SignedUrl, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return sign_video(requesting_user.subject_id, video_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce un URL firmato del file del video, utilizzabile senza l'header di autenticazione
+/// fino alla sua scadenza.
+///
+/// Lo stesso token dà accesso anche alle immagini degli screenshot del video, aggiungendolo
+/// come parametro `token` all'URL dell'immagine.
+pub fn sign_video(subject_id: i64, video_id: i64) -> Result<SignedUrl, ApiError> {
+    find_video(video_id)?;
+
+    let media_token = match create_media_token(subject_id, video_scope(video_id)) {
+        Ok(media_token) => media_token,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error while signing the URL of video {} - {}",
+                    video_id, err
+                ),
+            });
+        }
+    };
+
+    return Ok(SignedUrl {
+        url: format!("/video/{}/file?token={}", video_id, media_token.token),
+        expires_at: media_token.expires_at,
+    });
+}
+
+// Risorsa alla quale dà accesso il token di un video
+pub(crate) fn video_scope(video_id: i64) -> String {
+    format!("video_{}", video_id)
+}
+
+// Il nome proposto per il file è quello dato dall'utente, limitato ai caratteri sicuri negli header,
+// con l'estensione del file salvato
+pub(crate) fn media_file(file_location: &str, name: &str) -> MediaFile {
+    let mut file_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if let Some(extension) = Path::new(file_location)
+        .extension()
+        .and_then(|e| e.to_str())
+    {
+        file_name = format!("{}.{}", file_name, extension);
+    }
+
+    return MediaFile {
+        key: file_location.to_string(),
+        file_name,
+    };
+}
+
+pub(crate) fn not_authorized_to_read(person_id: i64, video_id: i64) -> ApiError {
+    ApiError {
+        http_status: Status::Forbidden,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::AuthorizationError,
+        message: format!(
+            "Error - User {} is not authorized to read video {}",
+            person_id, video_id
+        ),
+    }
+}
@@ -0,0 +1,571 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, Local, NaiveTime};
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{
+        ClipVideo, HighlightJob, HighlightSegment, HighlightStatus, TimeMarker, Video, VideoUser,
+    },
+    insertions::{NewHighlightJob, NewHighlightSegment, NewVideo},
+    others::{HighlightJobDetails, HighlightRequest, HighlightSegmentRequest},
+};
+use infrastructure::{
+    establish_connection,
+    storage::{read_location, storage, working_path},
+};
+use log::{info, warn};
+use rocket::{http::Status, tokio::task};
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::{person_checks::is_administrator, video_checks::can_edit_video},
+    db_entities::{
+        storage::usage::check_storage_quota,
+        video::{
+            metadata::{check_instant, probe_new_video_file},
+            read::find_video,
+            thumbnails::create_thumbnails,
+        },
+    },
+    player::{
+        blocking::run_blocking,
+        highlight::{compile_highlight, highlight_key, HighlightPart},
+    },
+};
+
+pub fn authorize_create_highlight_job(
+    requesting_user: Claims,
+    request: HighlightRequest,
+) -> Result<HighlightJobDetails, ApiError> {
+    request.validate()?;
+
+    let segments = resolve_segments(&request)?;
+
+    for video_id in source_videos(&segments) {
+        if !can_edit_video(requesting_user.subject_id, video_id)? {
+            return Err(ApiError {
+                http_status: Status::Forbidden,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::AuthorizationError,
+                message: format!(
+                    "Error - User {} is not authorized to cut highlights from video {}",
+                    requesting_user.subject_id, video_id
+                ),
+            });
+        }
+    }
+
+    return create_highlight_job(requesting_user.subject_id, request, segments);
+}
+
+/// Inserisce nel database una nuova richiesta di compilazione di un video di highlight con le sue parti,
+/// avvia la compilazione in background e restituisce la richiesta.
+///
+/// La richiesta non viene inserita se le squadre di uno dei video o le loro società sportive hanno esaurito
+/// la loro quota di spazio.
+pub fn create_highlight_job(
+    user_id: i64,
+    request: HighlightRequest,
+    segments: Vec<NewHighlightSegment>,
+) -> Result<HighlightJobDetails, ApiError> {
+    use domain::schema::{highlight_job, highlight_segment};
+
+    for video_id in source_videos(&segments) {
+        check_storage_quota(video_id)?;
+    }
+
+    let new_job = NewHighlightJob {
+        user_id,
+        name: request.name,
+        notes: request.notes,
+        status: HighlightStatus::Pending,
+        created_at: Local::now().naive_local(),
+    };
+
+    let connection = &mut establish_connection();
+
+    let inserted_job: HighlightJob = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(highlight_job::table)
+            .values(&new_job)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno la richiesta inserita si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id della richiesta appena inserita.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        let job: HighlightJob = highlight_job::table
+            .order(highlight_job::id.desc())
+            .select(HighlightJob::as_select())
+            .first(connection)?;
+
+        let job_segments: Vec<NewHighlightSegment> = segments
+            .into_iter()
+            .map(|segment| NewHighlightSegment {
+                job_id: job.id,
+                ..segment
+            })
+            .collect();
+
+        diesel::insert_into(highlight_segment::table)
+            .values(&job_segments)
+            .execute(connection)?;
+
+        Ok(job)
+    }) {
+        Ok(j) => j,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new highlight job - {}", err),
+            })
+        }
+    };
+
+    task::spawn(run_highlight_job(inserted_job.id));
+
+    return find_highlight_job(inserted_job.id);
+}
+
+pub fn authorize_find_highlight_job(
+    requesting_user: Claims,
+    job_id: i64,
+) -> Result<HighlightJobDetails, ApiError> {
+    let details = find_highlight_job(job_id)?;
+
+    if details.job.user_id == requesting_user.subject_id
+        || is_administrator(requesting_user.subject_id)?
+    {
+        return Ok(details);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read highlight job {}",
+                requesting_user.subject_id, job_id
+            ),
+        });
+    }
+}
+
+/// Restituisce una richiesta di compilazione di un video di highlight con le sue parti, in ordine di riproduzione.
+pub fn find_highlight_job(job_id: i64) -> Result<HighlightJobDetails, ApiError> {
+    use domain::schema::{highlight_job, highlight_segment};
+
+    let connection = &mut establish_connection();
+
+    let job = highlight_job::table
+        .find(job_id)
+        .select(HighlightJob::as_select())
+        .first(connection)?;
+
+    let segments = highlight_segment::table
+        .filter(highlight_segment::job_id.eq(job_id))
+        .order(highlight_segment::position.asc())
+        .select(HighlightSegment::as_select())
+        .load(connection)?;
+
+    return Ok(HighlightJobDetails { job, segments });
+}
+
+pub fn authorize_list_highlight_jobs(
+    requesting_user: Claims,
+) -> Result<Vec<HighlightJob>, ApiError> {
+    return list_highlight_jobs(requesting_user.subject_id);
+}
+
+/// Restituisce le richieste di compilazione di video di highlight dell'utente, dalla più recente.
+pub fn list_highlight_jobs(user_id: i64) -> Result<Vec<HighlightJob>, ApiError> {
+    use domain::schema::highlight_job;
+
+    let connection = &mut establish_connection();
+
+    let jobs = highlight_job::table
+        .filter(highlight_job::user_id.eq(user_id))
+        .order(highlight_job::created_at.desc())
+        .select(HighlightJob::as_select())
+        .load(connection)?;
+
+    return Ok(jobs);
+}
+
+/// Avvia di nuovo la compilazione delle richieste rimaste in attesa o interrotte dall'arresto del server.
+pub fn resume_highlight_jobs() -> Result<(), ApiError> {
+    use domain::schema::highlight_job;
+
+    let connection = &mut establish_connection();
+
+    let job_ids = highlight_job::table
+        .filter(highlight_job::status.eq_any([HighlightStatus::Pending, HighlightStatus::Running]))
+        .order(highlight_job::id.asc())
+        .select(highlight_job::id)
+        .load::<i64>(connection)?;
+
+    for job_id in job_ids {
+        info!("Highlight: resuming job {}", job_id);
+        task::spawn(run_highlight_job(job_id));
+    }
+
+    return Ok(());
+}
+
+/// Compila il video di highlight della richiesta e ne aggiorna lo stato: al termine la richiesta
+/// è completata, con il video prodotto, o non è riuscita, con il motivo.
+pub async fn run_highlight_job(job_id: i64) {
+    if let Err(e) = update_job_status(job_id, HighlightStatus::Running, None, None) {
+        warn!("Highlight: failed to start job {} - {}", job_id, e.message);
+        return;
+    }
+
+    let result = match compile_highlight_job(job_id).await {
+        Ok(video) => {
+            info!("Highlight: job {} compiled video {}", job_id, video.id);
+            update_job_status(job_id, HighlightStatus::Completed, Some(video.id), None)
+        }
+        Err(e) => {
+            warn!("Highlight: job {} failed - {}", job_id, e.message);
+            update_job_status(job_id, HighlightStatus::Failed, None, Some(e.message))
+        }
+    };
+
+    if let Err(e) = result {
+        warn!(
+            "Highlight: failed to update the status of job {} - {}",
+            job_id, e.message
+        );
+    }
+}
+
+// Compila il video di highlight, lo inserisce nel database collegandolo tramite la tabella `clip_video`
+// a ciascuno dei video dai quali è stato ottenuto, e lo restituisce
+async fn compile_highlight_job(job_id: i64) -> Result<Video, ApiError> {
+    use domain::schema::{clip_video, video, video_user};
+
+    let HighlightJobDetails { job, segments } = find_highlight_job(job_id)?;
+
+    // Le parti vengono eliminate insieme ai video dai quali sono ottenute
+    if segments.is_empty() {
+        return Err(highlight_error(
+            job_id,
+            "the source videos have been deleted".to_string(),
+        ));
+    }
+
+    let mut parts = Vec::new();
+    for segment in &segments {
+        let source = find_video(segment.video_id)?;
+
+        parts.push(HighlightPart {
+            video: read_location(&source.file_location)
+                .map_err(|e| highlight_error(job_id, e.to_string()))?,
+            start: segment.start_instant,
+            end: segment.end_instant,
+            has_audio: source.metadata.audio_codec.is_some(),
+            title: segment.title.clone(),
+        });
+    }
+
+    // Tutte le parti provengono dalla stessa sessione di registrazione, alla quale appartiene il video di highlight
+    let first_video = find_video(segments[0].video_id)?;
+
+    let file_location = highlight_key(job_id);
+    let output = working_path(&file_location);
+
+    let highlight_output = output.clone();
+    let compiled = run_blocking(move || {
+        let dir = highlight_output.with_extension("parts");
+        compile_highlight(&parts, &dir, &highlight_output)
+    })
+    .await;
+
+    match compiled {
+        Ok(()) => {}
+        Err(e) => return Err(highlight_error(job_id, e)),
+    }
+
+    storage()
+        .put(&file_location, &output)
+        .map_err(|e| highlight_error(job_id, e.to_string()))?;
+
+    let metadata = probe_new_video_file(&file_location).await;
+
+    let new_video = NewVideo {
+        file_location: file_location.clone(),
+        name: job.name,
+        date: first_video.date + (segments[0].start_instant - NaiveTime::MIN),
+        // Le parti possono provenire da video diversi, quindi il video non ha un unico inizio della registrazione
+        capture_start: None,
+        notes: job.notes,
+        session_id: first_video.session_id,
+        camera_id: first_video.camera_id,
+        metadata,
+    };
+
+    let source_video_ids: HashSet<i64> = segments.iter().map(|s| s.video_id).collect();
+
+    let connection = &mut establish_connection();
+
+    let inserted_video: Video = match connection.transaction::<_, Error, _>(|connection| {
+        diesel::insert_into(video::table)
+            .values(&new_video)
+            .execute(connection)?;
+
+        // NB: questo metodo per ottenere in ritorno il video inserito si affida al fatto che gli id siano autoincrementali.
+        // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id del video appena inserito.
+        // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+        let highlight: Video = video::table
+            .order(video::id.desc())
+            .select(Video::as_select())
+            .first(connection)?;
+
+        let clip_videos: Vec<ClipVideo> = source_video_ids
+            .iter()
+            .map(|original_video_id| ClipVideo {
+                original_video_id: *original_video_id,
+                clip_id: highlight.id,
+                start_instant: None,
+            })
+            .collect();
+
+        diesel::insert_into(clip_video::table)
+            .values(&clip_videos)
+            .execute(connection)?;
+
+        // L'utente che ha richiesto il video di highlight ne è il proprietario, e può condividerlo
+        diesel::insert_into(video_user::table)
+            .values(&VideoUser {
+                user_id: job.user_id,
+                video_id: highlight.id,
+                is_owner: true,
+                read: true,
+                edit: true,
+                delete: true,
+                share: true,
+            })
+            .execute(connection)?;
+
+        Ok(highlight)
+    }) {
+        Ok(v) => v,
+        Err(err) => {
+            // Senza la riga nel database il file del video non sarebbe più raggiungibile
+            if let Err(e) = storage().delete(&file_location) {
+                warn!("Highlight: failed to remove {} - {}", file_location, e);
+            }
+
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while inserting new highlight video - {}", err),
+            });
+        }
+    };
+
+    // Il video è utilizzabile anche senza anteprime, che possono essere generate di nuovo in seguito
+    match create_thumbnails(inserted_video.id).await {
+        Ok(highlight) => return Ok(highlight),
+        Err(e) => {
+            warn!(
+                "Highlight: failed to generate the thumbnails of video {} - {}",
+                inserted_video.id, e.message
+            );
+            return Ok(inserted_video);
+        }
+    }
+}
+
+fn update_job_status(
+    job_id: i64,
+    status: HighlightStatus,
+    video_id: Option<i64>,
+    error: Option<String>,
+) -> Result<(), ApiError> {
+    use domain::schema::highlight_job;
+
+    let connection = &mut establish_connection();
+
+    let completed_at = match status {
+        HighlightStatus::Completed | HighlightStatus::Failed => Some(Local::now().naive_local()),
+        HighlightStatus::Pending | HighlightStatus::Running => None,
+    };
+
+    diesel::update(highlight_job::table.filter(highlight_job::id.eq(job_id)))
+        .set((
+            highlight_job::status.eq(status),
+            highlight_job::video_id.eq(video_id),
+            highlight_job::error.eq(error),
+            highlight_job::completed_at.eq(completed_at),
+        ))
+        .execute(connection)?;
+
+    return Ok(());
+}
+
+// Trasforma i segnaposti e le clip della richiesta nelle parti dei video che compongono il video di highlight.
+// L'ID della richiesta di compilazione viene assegnato alle parti al momento del loro inserimento.
+fn resolve_segments(request: &HighlightRequest) -> Result<Vec<NewHighlightSegment>, ApiError> {
+    use domain::schema::{clip_video, time_marker};
+
+    let connection = &mut establish_connection();
+
+    let mut segments = Vec::new();
+    let mut session_id = None;
+    for (position, segment) in request.segments.iter().enumerate() {
+        let pre_roll = segment.pre_roll_ms.unwrap_or(request.pre_roll_ms) as i64;
+        let post_roll = segment.post_roll_ms.unwrap_or(request.post_roll_ms) as i64;
+
+        let (source, start_ms, end_ms, name) = match (segment.time_marker_id, segment.clip_id) {
+            (Some(marker_id), _) => {
+                let marker = time_marker::table
+                    .find(marker_id)
+                    .select(TimeMarker::as_select())
+                    .first(connection)?;
+                let source = find_video(marker.video_id)?;
+
+                check_instant(&source, marker.instant)?;
+
+                let instant_ms = (marker.instant - NaiveTime::MIN).num_milliseconds();
+                (
+                    source,
+                    instant_ms - pre_roll,
+                    instant_ms + post_roll,
+                    marker.name,
+                )
+            }
+            (None, Some(clip_id)) => {
+                let clip = find_video(clip_id)?;
+
+                // Un video di highlight è collegato a tutti i video dai quali è stato compilato,
+                // e non ha quindi un unico video originale
+                let originals: Vec<(i64, Option<NaiveTime>)> = clip_video::table
+                    .filter(clip_video::clip_id.eq(clip_id))
+                    .select((clip_video::original_video_id, clip_video::start_instant))
+                    .load(connection)?;
+                let (source, start_instant) = match originals.as_slice() {
+                    [(original_video_id, Some(start_instant))] => {
+                        (find_video(*original_video_id)?, *start_instant)
+                    }
+                    [] => return Err(segment_error(position, segment, "is not a clip")),
+                    _ => {
+                        return Err(segment_error(
+                            position,
+                            segment,
+                            "is not a clip of a single video",
+                        ))
+                    }
+                };
+                let duration_ms = match clip.metadata.duration_ms {
+                    Some(duration_ms) => duration_ms,
+                    None => {
+                        return Err(segment_error(
+                            position,
+                            segment,
+                            "has no known duration, update its metadata first",
+                        ))
+                    }
+                };
+
+                let offset_ms = (start_instant - NaiveTime::MIN).num_milliseconds();
+                (
+                    source,
+                    offset_ms - pre_roll,
+                    offset_ms + duration_ms + post_roll,
+                    clip.name,
+                )
+            }
+            (None, None) => {
+                return Err(segment_error(
+                    position,
+                    segment,
+                    "has neither a time marker nor a clip",
+                ))
+            }
+        };
+
+        // Il pre-roll e il post-roll non vanno oltre l'inizio e la fine del video
+        let start_ms = start_ms.max(0);
+        let end_ms = match source.metadata.duration_ms {
+            Some(duration_ms) => end_ms.min(duration_ms),
+            None => end_ms,
+        };
+        if start_ms >= end_ms {
+            return Err(segment_error(
+                position,
+                segment,
+                "is empty, add a pre-roll or a post-roll",
+            ));
+        }
+
+        let title = match &segment.title {
+            Some(title) => Some(title.clone()),
+            None if request.title_cards => Some(name),
+            None => None,
+        };
+
+        // Il video di highlight appartiene alla sessione di registrazione delle sue parti, ed è visibile
+        // a chi ha accesso alla sessione: le parti non possono quindi provenire da altre sessioni
+        match session_id {
+            Some(session_id) if session_id != source.session_id => {
+                return Err(segment_error(
+                    position,
+                    segment,
+                    "belongs to a different recording session than the previous segments",
+                ))
+            }
+            _ => session_id = Some(source.session_id),
+        }
+
+        segments.push(NewHighlightSegment {
+            job_id: 0,
+            position: position as i32,
+            video_id: source.id,
+            start_instant: NaiveTime::MIN + Duration::milliseconds(start_ms),
+            end_instant: NaiveTime::MIN + Duration::milliseconds(end_ms),
+            title,
+            time_marker_id: segment.time_marker_id,
+            clip_id: segment.clip_id,
+        });
+    }
+
+    return Ok(segments);
+}
+
+// Video dai quali vengono estratte le parti, senza ripetizioni
+fn source_videos(segments: &[NewHighlightSegment]) -> HashSet<i64> {
+    return segments.iter().map(|segment| segment.video_id).collect();
+}
+
+fn segment_error(position: usize, segment: &HighlightSegmentRequest, reason: &str) -> ApiError {
+    let source = match (segment.time_marker_id, segment.clip_id) {
+        (Some(marker_id), _) => format!("time marker {}", marker_id),
+        (None, Some(clip_id)) => format!("clip {}", clip_id),
+        (None, None) => String::from("nothing"),
+    };
+
+    ApiError {
+        http_status: Status::BadRequest,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error - Segment {} of the highlight, from {}, {}",
+            position, source, reason
+        ),
+    }
+}
+
+fn highlight_error(job_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while compiling the highlight of job {} - {}",
+            job_id, message
+        ),
+    }
+}
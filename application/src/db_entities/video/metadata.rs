@@ -0,0 +1,133 @@
+use chrono::NaiveTime;
+use diesel::prelude::*;
+use domain::models::full_tables::{Video, VideoMetadata};
+use infrastructure::{
+    establish_connection,
+    storage::{read_location, storage},
+};
+use log::warn;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::can_edit_video,
+    db_entities::video::read::find_video,
+    player::{blocking::run_blocking, editing::ffmpeg_position, metadata::probe_metadata},
+};
+
+pub async fn authorize_update_video_metadata(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<Video, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return update_video_metadata(video_id).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to update the metadata of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Legge di nuovo le caratteristiche tecniche del file del video, le salva nel video e lo restituisce.
+///
+/// Serve per i video registrati prima che le caratteristiche venissero lette, o per i quali la lettura non è riuscita.
+pub async fn update_video_metadata(video_id: i64) -> Result<Video, ApiError> {
+    use domain::schema::video;
+
+    let video_to_update = find_video(video_id)?;
+
+    let metadata = probe_video_file(&video_to_update.file_location)
+        .await
+        .map_err(|e| metadata_error(video_id, e))?;
+
+    let connection = &mut establish_connection();
+
+    diesel::update(video::table.filter(video::id.eq(video_id)))
+        .set(&metadata)
+        .execute(connection)?;
+
+    return find_video(video_id);
+}
+
+/// Legge con FFprobe le caratteristiche tecniche del file con la chiave `file_location` nell'archivio
+/// dei file multimediali.
+///
+/// La dimensione del file viene chiesta all'archivio, e solo se non è disponibile viene usata quella letta da FFprobe.
+pub async fn probe_video_file(file_location: &str) -> Result<VideoMetadata, String> {
+    let input = read_location(file_location).map_err(|e| e.to_string())?;
+
+    let key = file_location.to_string();
+    return run_blocking(move || {
+        let mut metadata = probe_metadata(&input)?;
+
+        if let Ok(size) = storage().size(&key) {
+            metadata.file_size = Some(size as i64);
+        }
+
+        Ok(metadata)
+    })
+    .await;
+}
+
+/// Come `probe_video_file`, ma se le caratteristiche tecniche non possono essere lette restituisce
+/// delle caratteristiche vuote.
+///
+/// Viene usata per i nuovi video, che sono utilizzabili anche senza caratteristiche tecniche:
+/// possono essere lette di nuovo in seguito con `update_video_metadata`.
+pub async fn probe_new_video_file(file_location: &str) -> VideoMetadata {
+    match probe_video_file(file_location).await {
+        Ok(metadata) => return metadata,
+        Err(e) => {
+            warn!(
+                "Metadata: failed to read the metadata of {} - {}",
+                file_location, e
+            );
+            return VideoMetadata::default();
+        }
+    }
+}
+
+/// Verifica che l'istante `instant` sia compreso nella durata del video.
+///
+/// Se la durata del video non è nota l'istante viene accettato, e la verifica è lasciata a FFmpeg.
+pub fn check_instant(video: &Video, instant: NaiveTime) -> Result<(), ApiError> {
+    let duration_ms = match video.metadata.duration_ms {
+        Some(duration_ms) => duration_ms,
+        None => return Ok(()),
+    };
+
+    if (instant - NaiveTime::MIN).num_milliseconds() >= duration_ms {
+        return Err(ApiError {
+            http_status: Status::BadRequest,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: format!(
+                "Error - Instant {} is beyond the end of video {}, that lasts {} ms",
+                ffmpeg_position(instant),
+                video.id,
+                duration_ms
+            ),
+        });
+    }
+
+    return Ok(());
+}
+
+fn metadata_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while reading the metadata of video {} - {}",
+            video_id, message
+        ),
+    }
+}
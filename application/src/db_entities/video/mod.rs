@@ -0,0 +1,16 @@
+pub mod annotation;
+pub mod chapters;
+pub mod clip;
+pub mod comment;
+pub mod create;
+pub mod delete;
+pub mod file;
+pub mod highlight;
+pub mod metadata;
+pub mod read;
+pub mod rendered;
+pub mod screenshot;
+pub mod share;
+pub mod thumbnails;
+pub mod time_marker;
+pub mod vod;
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use diesel::{dsl::count_star, prelude::*};
+use domain::models::{
+    full_tables::Video,
+    others::{SessionAngles, VideoAngle, VideoSummary},
+};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims,
+    authorization::{
+        booking_checks::can_read_recording_session,
+        person_checks::is_administrator,
+        video_checks::{can_read_video, list_shared_videos, shared_video_permissions},
+    },
+    db_entities::recording_session::read::find_recording_session,
+};
+
+pub fn authorize_find_video(requesting_user: Claims, video_id: i64) -> Result<Video, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return find_video(video_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+pub fn find_video(video_id: i64) -> Result<Video, ApiError> {
+    use domain::schema::video;
+
+    let connection = &mut establish_connection();
+
+    let video = video::table
+        .filter(video::id.eq(video_id))
+        .select(Video::as_select())
+        .get_result(connection)?;
+
+    return Ok(video);
+}
+
+/// Restituisce i video registrati dalle altre telecamere della stessa sessione di registrazione del video.
+///
+/// Le clip non sono considerate.
+pub fn list_other_angles(video: &Video) -> Result<Vec<Video>, ApiError> {
+    use domain::schema::{clip_video, video};
+
+    let connection = &mut establish_connection();
+
+    let clip_ids = clip_video::table.select(clip_video::clip_id);
+
+    let angles = video::table
+        .filter(video::session_id.eq(video.session_id))
+        .filter(video::camera_id.ne(video.camera_id))
+        .filter(video::id.ne_all(clip_ids))
+        .order(video::camera_id.asc())
+        .select(Video::as_select())
+        .load(connection)?;
+
+    return Ok(angles);
+}
+
+pub fn authorize_list_session_angles(
+    requesting_user: Claims,
+    session_id: i64,
+) -> Result<SessionAngles, ApiError> {
+    let mut session_angles = list_session_angles(session_id)?;
+
+    if is_administrator(requesting_user.subject_id)?
+        || can_read_recording_session(requesting_user.subject_id, session_id)?
+    {
+        return Ok(session_angles);
+    }
+
+    // Senza accesso alla sessione sono visibili solo i video condivisi con l'utente in lettura
+    let mut shared_angles = Vec::new();
+    for angle in session_angles.angles {
+        if shared_video_permissions(requesting_user.subject_id, angle.video.id)?.read {
+            shared_angles.push(angle);
+        }
+    }
+
+    if shared_angles.is_empty() {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the videos of recording session {}",
+                requesting_user.subject_id, session_id
+            ),
+        });
+    }
+
+    session_angles.angles = shared_angles;
+    return Ok(session_angles);
+}
+
+/// Restituisce i video registrati dalle telecamere della sessione di registrazione, ordinati per telecamera,
+/// con l'inizio di ciascun video rispetto all'inizio della sessione.
+///
+/// Con lo stesso orologio per tutti i video è possibile passare da una telecamera all'altra
+/// nello stesso momento della registrazione. Le clip non sono considerate.
+pub fn list_session_angles(session_id: i64) -> Result<SessionAngles, ApiError> {
+    use domain::schema::{camera, clip_video, video};
+
+    let session = find_recording_session(session_id)?.recording_session;
+
+    let connection = &mut establish_connection();
+
+    let clip_ids = clip_video::table.select(clip_video::clip_id);
+
+    let videos: Vec<(Video, String)> = video::table
+        .inner_join(camera::table)
+        .filter(video::session_id.eq(session_id))
+        .filter(video::id.ne_all(clip_ids))
+        .order((video::camera_id.asc(), video::date.asc()))
+        .select((Video::as_select(), camera::name))
+        .load(connection)?;
+
+    let angles = videos
+        .into_iter()
+        .map(|(video, camera_name)| VideoAngle {
+            offset_ms: (video.capture_start.unwrap_or(video.date) - session.start_datetime)
+                .num_milliseconds(),
+            camera_name,
+            video,
+        })
+        .collect();
+
+    return Ok(SessionAngles {
+        recording_session: session,
+        angles,
+    });
+}
+
+pub fn authorize_list_videos_by_booking(
+    requesting_user: Claims,
+    booking_id: i64,
+    camera_id: Option<i64>,
+    session_id: Option<i64>,
+) -> Result<Vec<VideoSummary>, ApiError> {
+    use domain::schema::recording_session;
+
+    let videos = list_videos_by_booking(booking_id, camera_id, session_id)?;
+
+    if is_administrator(requesting_user.subject_id)? {
+        return Ok(videos);
+    }
+
+    let connection = &mut establish_connection();
+
+    // Sono visibili tutti i video delle sessioni di registrazione a cui l'utente ha accesso
+    // e i video che sono stati condivisi con l'utente in lettura
+    let session_ids = recording_session::table
+        .filter(recording_session::booking_id.eq(booking_id))
+        .select(recording_session::id)
+        .load::<i64>(connection)?;
+
+    let mut readable_sessions = Vec::new();
+    for session_id in session_ids {
+        if can_read_recording_session(requesting_user.subject_id, session_id)? {
+            readable_sessions.push(session_id);
+        }
+    }
+
+    let shared_videos = list_shared_videos(requesting_user.subject_id)?;
+
+    if readable_sessions.is_empty() && shared_videos.is_empty() {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the videos of booking {}",
+                requesting_user.subject_id, booking_id
+            ),
+        });
+    }
+
+    return Ok(videos
+        .into_iter()
+        .filter(|v| {
+            readable_sessions.contains(&v.video.session_id) || shared_videos.contains(&v.video.id)
+        })
+        .collect());
+}
+
+/// Restituisce i video registrati durante le sessioni di registrazione della prenotazione, compresi le clip,
+/// con il numero di clip e di segnaposti di ciascun video.
+///
+/// I video possono essere filtrati per telecamera e per sessione di registrazione.
+pub fn list_videos_by_booking(
+    booking_id: i64,
+    camera_id: Option<i64>,
+    session_id: Option<i64>,
+) -> Result<Vec<VideoSummary>, ApiError> {
+    use domain::schema::{clip_video, recording_session, time_marker, video};
+
+    let connection = &mut establish_connection();
+
+    let mut query = video::table
+        .inner_join(recording_session::table)
+        .filter(recording_session::booking_id.eq(booking_id))
+        .select(Video::as_select())
+        .order((video::date.asc(), video::id.asc()))
+        .into_boxed();
+
+    if let Some(camera_id) = camera_id {
+        query = query.filter(video::camera_id.eq(camera_id));
+    }
+
+    if let Some(session_id) = session_id {
+        query = query.filter(video::session_id.eq(session_id));
+    }
+
+    let videos: Vec<Video> = query.load(connection)?;
+    let video_ids: Vec<i64> = videos.iter().map(|v| v.id).collect();
+
+    let clip_counts: HashMap<i64, i64> = clip_video::table
+        .filter(clip_video::original_video_id.eq_any(&video_ids))
+        .group_by(clip_video::original_video_id)
+        .select((clip_video::original_video_id, count_star()))
+        .load::<(i64, i64)>(connection)?
+        .into_iter()
+        .collect();
+
+    let marker_counts: HashMap<i64, i64> = time_marker::table
+        .filter(time_marker::video_id.eq_any(&video_ids))
+        .group_by(time_marker::video_id)
+        .select((time_marker::video_id, count_star()))
+        .load::<(i64, i64)>(connection)?
+        .into_iter()
+        .collect();
+
+    let original_videos: HashMap<i64, i64> = clip_video::table
+        .filter(clip_video::clip_id.eq_any(&video_ids))
+        .select((clip_video::clip_id, clip_video::original_video_id))
+        .load::<(i64, i64)>(connection)?
+        .into_iter()
+        .collect();
+
+    let summaries = videos
+        .into_iter()
+        .map(|video| VideoSummary {
+            original_video_id: original_videos.get(&video.id).copied(),
+            clip_count: clip_counts.get(&video.id).copied().unwrap_or(0),
+            marker_count: marker_counts.get(&video.id).copied().unwrap_or(0),
+            video,
+        })
+        .collect();
+
+    return Ok(summaries);
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use infrastructure::storage::{delete_dir, storage, working_path};
+use log::warn;
+use sha2::{Digest, Sha256};
+
+use crate::player::blocking::run_blocking;
+
+// Numero progressivo dei file preparati, per dare a ciascuno un file di lavoro diverso
+static RENDER_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Restituisce la chiave, nella cartella `prefix` dell'archivio, di un file ottenuto dai dati `inputs`.
+///
+/// Il nome del file è l'hash SHA-256 dei dati, così che un file non più aggiornato non venga riutilizzato
+/// e che richieste per gli stessi dati condividano lo stesso file.
+pub fn rendered_key(prefix: &str, inputs: &[&[u8]], extension: &str) -> String {
+    let mut hasher = Sha256::new();
+    for input in inputs {
+        // La lunghezza separa i dati, così che dati diversi non diano mai la stessa sequenza di byte
+        hasher.update((input.len() as u64).to_be_bytes());
+        hasher.update(input);
+    }
+
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    return format!("{}/{}.{}", prefix, hash, extension);
+}
+
+/// Controlla se il file con la chiave `key` è già stato preparato e salvato nell'archivio.
+pub fn is_rendered(key: &str) -> bool {
+    return storage().size(key).is_ok();
+}
+
+/// Prepara con `render` il file con la chiave `key` e lo salva nell'archivio, su un thread riservato alle
+/// operazioni bloccanti.
+///
+/// `render` scrive il file nel percorso ricevuto, che è diverso per ogni chiamata: due richieste contemporanee
+/// per la stessa chiave non scrivono mai sullo stesso file di lavoro, e l'ultima sostituisce la prima nell'archivio.
+pub async fn render_file<F>(key: String, render: F) -> Result<(), String>
+where
+    F: FnOnce(&Path) -> Result<(), String> + Send + 'static,
+{
+    let (name, extension) = key.rsplit_once('.').unwrap_or((key.as_str(), ""));
+    let counter = RENDER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let output = working_path(&format!("{}.{}.{}", name, counter, extension));
+
+    return run_blocking(move || {
+        render(&output)?;
+        storage().put(&key, &output).map_err(|e| e.to_string())
+    })
+    .await;
+}
+
+/// Elimina i file preparati nella cartella `prefix` dell'archivio, quando i dati dai quali sono ottenuti cambiano.
+///
+/// I file vengono preparati di nuovo alla richiesta successiva, quindi un errore viene solo segnalato nel log.
+pub fn discard_rendered(prefix: &str) {
+    if let Err(e) = delete_dir(prefix) {
+        warn!("Storage: failed to remove {} - {}", prefix, e);
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::Local;
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::Screenshot, insertions::NewScreenshot, others::ScreenshotRequest,
+};
+use infrastructure::{
+    establish_connection,
+    storage::{read_location, storage, working_path},
+};
+use log::warn;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::can_edit_video,
+    db_entities::video::{
+        annotation::discard_annotated_images, metadata::check_instant, read::find_video,
+    },
+    player::{
+        blocking::run_blocking,
+        editing::{extract_frame, ffmpeg_position, screenshots_prefix},
+    },
+};
+
+pub async fn authorize_create_screenshot(
+    requesting_user: Claims,
+    video_id: i64,
+    request: ScreenshotRequest,
+) -> Result<Screenshot, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return create_screenshot(video_id, request).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to add screenshots to video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Estrae dal video il fotogramma all'istante richiesto, lo salva nell'archivio dei file multimediali
+/// e inserisce il nuovo screenshot nel database.
+pub async fn create_screenshot(
+    video_id: i64,
+    request: ScreenshotRequest,
+) -> Result<Screenshot, ApiError> {
+    use domain::schema::screenshot;
+
+    request.validate()?;
+
+    let video = find_video(video_id)?;
+
+    check_instant(&video, request.instant)?;
+
+    let file_location = format!(
+        "{}/{}.jpg",
+        screenshots_prefix(video_id),
+        Local::now().naive_local().format("%Y%m%d-%H%M%S%3f")
+    );
+    let output = working_path(&file_location);
+
+    let input = read_location(&video.file_location)
+        .map_err(|e| screenshot_error(video_id, e.to_string()))?;
+
+    let instant = request.instant;
+    let frame_output = output.clone();
+    let extraction = run_blocking(move || extract_frame(&input, instant, &frame_output)).await;
+
+    match extraction {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Video {} has no frame at {}",
+                    video_id,
+                    ffmpeg_position(instant)
+                ),
+            });
+        }
+        Err(e) => return Err(screenshot_error(video_id, e)),
+    }
+
+    storage()
+        .put(&file_location, &output)
+        .map_err(|e| screenshot_error(video_id, e.to_string()))?;
+
+    let new_screenshot = NewScreenshot {
+        file_location: file_location.clone(),
+        video_id,
+        instant: request.instant,
+        name: request.name,
+        notes: request.notes,
+    };
+
+    let connection = &mut establish_connection();
+
+    let inserted_screenshot: Screenshot =
+        match connection.transaction::<_, Error, _>(|connection| {
+            diesel::insert_into(screenshot::table)
+                .values(&new_screenshot)
+                .execute(connection)?;
+
+            // NB: questo metodo per ottenere in ritorno lo screenshot inserito si affida al fatto che gli id siano autoincrementali.
+            // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id dello screenshot appena inserito.
+            // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+            screenshot::table
+                .order(screenshot::id.desc())
+                .select(Screenshot::as_select())
+                .first(connection)
+        }) {
+            Ok(s) => s,
+            Err(err) => {
+                // Senza la riga nel database l'immagine non sarebbe più raggiungibile
+                remove_screenshot_file(&file_location);
+
+                return Err(ApiError {
+                    http_status: Status::InternalServerError,
+                    error_code: 123,
+                    error_type: ApiErrorType::ApplicationError,
+                    message: format!("Error while inserting new screenshot - {}", err),
+                });
+            }
+        };
+
+    return Ok(inserted_screenshot);
+}
+
+pub fn authorize_delete_screenshot(
+    requesting_user: Claims,
+    video_id: i64,
+    screenshot_id: i64,
+) -> Result<Screenshot, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return delete_screenshot(video_id, screenshot_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete screenshots of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Elimina lo screenshot dal database insieme alla sua immagine e lo restituisce.
+pub fn delete_screenshot(video_id: i64, screenshot_id: i64) -> Result<Screenshot, ApiError> {
+    use domain::schema::screenshot;
+
+    let connection = &mut establish_connection();
+
+    let screenshot_to_delete = find_screenshot(video_id, screenshot_id)?;
+
+    diesel::delete(screenshot::table.filter(screenshot::id.eq(screenshot_id)))
+        .execute(connection)?;
+
+    remove_screenshot_file(&screenshot_to_delete.file_location);
+    discard_annotated_images(video_id, Some(screenshot_id), None);
+
+    return Ok(screenshot_to_delete);
+}
+
+/// Restituisce uno screenshot del video dato il suo ID.
+pub fn find_screenshot(video_id: i64, screenshot_id: i64) -> Result<Screenshot, ApiError> {
+    use domain::schema::screenshot;
+
+    let connection = &mut establish_connection();
+
+    let screenshot = screenshot::table
+        .filter(screenshot::id.eq(screenshot_id))
+        .filter(screenshot::video_id.eq(video_id))
+        .select(Screenshot::as_select())
+        .get_result(connection)?;
+
+    return Ok(screenshot);
+}
+
+// Un'immagine che non può essere eliminata non impedisce l'eliminazione dello screenshot
+fn remove_screenshot_file(file_location: &str) {
+    if let Err(e) = storage().delete(file_location) {
+        warn!("Screenshot: failed to remove {} - {}", file_location, e);
+    }
+}
+
+fn screenshot_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while extracting a screenshot from video {} - {}",
+            video_id, message
+        ),
+    }
+}
@@ -0,0 +1,739 @@
+use std::collections::BTreeSet;
+
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{VideoClub, VideoTeam, VideoUser},
+    others::{VideoPermissions, VideoShareRequest, VideoShares},
+};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::{
+        can_share_video, club_permissions, find_video_user, shared_permissions, team_permissions,
+        video_permissions,
+    },
+    db_entities::video::read::find_video,
+};
+
+pub fn authorize_share_video(
+    requesting_user: Claims,
+    video_id: i64,
+    request: VideoShareRequest,
+) -> Result<VideoShares, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    if !granted.contains(&request.permissions) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't grant permissions on video {} that they don't have",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+
+    return share_video(video_id, request, requesting_user.subject_id);
+}
+
+/// Concede i permessi richiesti sul video agli utenti, alle squadre e alle società sportive indicate
+/// e restituisce le condivisioni inserite o aggiornate.
+///
+/// I permessi vengono aggiunti a quelli delle condivisioni esistenti, che non vengono mai ridotti:
+/// per togliere dei permessi va modificata la condivisione. Chi condivide non modifica i propri permessi.
+pub fn share_video(
+    video_id: i64,
+    request: VideoShareRequest,
+    sharing_user_id: i64,
+) -> Result<VideoShares, ApiError> {
+    use domain::schema::{video_club, video_team, video_user};
+
+    request.validate()?;
+
+    find_video(video_id)?;
+
+    check_recipients(&request)?;
+
+    let mut user_ids: BTreeSet<i64> = request.user_ids.iter().copied().collect();
+    user_ids.remove(&sharing_user_id);
+    let team_ids: BTreeSet<i64> = request.team_ids.iter().copied().collect();
+    let club_ids: BTreeSet<String> = request.club_ids.iter().cloned().collect();
+
+    let permissions = request.permissions;
+
+    let connection = &mut establish_connection();
+
+    let video_shares = match connection.transaction::<_, Error, _>(|connection| {
+        let existing_video_users: Vec<VideoUser> = video_user::table
+            .filter(video_user::video_id.eq(video_id))
+            .filter(video_user::user_id.eq_any(&user_ids))
+            .select(VideoUser::as_select())
+            .load(connection)?;
+
+        // Il proprietario ha già tutti i permessi
+        let mut users = Vec::new();
+        for user_id in user_ids {
+            let existing = existing_video_users.iter().find(|v| v.user_id == user_id);
+            if existing.is_some_and(|v| v.is_owner) {
+                continue;
+            }
+
+            let merged = existing
+                .map(shared_permissions)
+                .unwrap_or_default()
+                .union(&permissions);
+
+            users.push(VideoUser {
+                user_id,
+                video_id,
+                is_owner: false,
+                read: merged.read,
+                edit: merged.edit,
+                delete: merged.delete,
+                share: merged.share,
+            });
+        }
+
+        let existing_video_teams: Vec<VideoTeam> = video_team::table
+            .filter(video_team::video_id.eq(video_id))
+            .filter(video_team::team_id.eq_any(&team_ids))
+            .select(VideoTeam::as_select())
+            .load(connection)?;
+
+        let teams: Vec<VideoTeam> = team_ids
+            .into_iter()
+            .map(|team_id| {
+                let merged = existing_video_teams
+                    .iter()
+                    .find(|v| v.team_id == team_id)
+                    .map(team_permissions)
+                    .unwrap_or_default()
+                    .union(&permissions);
+
+                VideoTeam {
+                    video_id,
+                    team_id,
+                    read: merged.read,
+                    edit: merged.edit,
+                    delete: merged.delete,
+                    share: merged.share,
+                }
+            })
+            .collect();
+
+        let existing_video_clubs: Vec<VideoClub> = video_club::table
+            .filter(video_club::video_id.eq(video_id))
+            .filter(video_club::club_id.eq_any(&club_ids))
+            .select(VideoClub::as_select())
+            .load(connection)?;
+
+        let clubs: Vec<VideoClub> = club_ids
+            .into_iter()
+            .map(|club_id| {
+                let merged = existing_video_clubs
+                    .iter()
+                    .find(|v| v.club_id == club_id)
+                    .map(club_permissions)
+                    .unwrap_or_default()
+                    .union(&permissions);
+
+                VideoClub {
+                    video_id,
+                    club_id,
+                    read: merged.read,
+                    edit: merged.edit,
+                    delete: merged.delete,
+                    share: merged.share,
+                }
+            })
+            .collect();
+
+        diesel::replace_into(video_user::table)
+            .values(&users)
+            .execute(connection)?;
+
+        diesel::replace_into(video_team::table)
+            .values(&teams)
+            .execute(connection)?;
+
+        diesel::replace_into(video_club::table)
+            .values(&clubs)
+            .execute(connection)?;
+
+        Ok(VideoShares {
+            users,
+            teams,
+            clubs,
+        })
+    }) {
+        Ok(v) => v,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123,
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while sharing video {} - {}", video_id, err),
+            })
+        }
+    };
+
+    return Ok(video_shares);
+}
+
+pub fn authorize_list_video_shares(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<VideoShares, ApiError> {
+    if can_share_video(requesting_user.subject_id, video_id)? {
+        return list_video_shares(video_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the shares of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Restituisce le condivisioni del video con gli utenti, compresa quella del proprietario,
+/// con le squadre e con le società sportive.
+pub fn list_video_shares(video_id: i64) -> Result<VideoShares, ApiError> {
+    use domain::schema::{video_club, video_team, video_user};
+
+    let connection = &mut establish_connection();
+
+    find_video(video_id)?;
+
+    let users = video_user::table
+        .filter(video_user::video_id.eq(video_id))
+        .order((video_user::is_owner.desc(), video_user::user_id.asc()))
+        .select(VideoUser::as_select())
+        .load(connection)?;
+
+    let teams = video_team::table
+        .filter(video_team::video_id.eq(video_id))
+        .order(video_team::team_id.asc())
+        .select(VideoTeam::as_select())
+        .load(connection)?;
+
+    let clubs = video_club::table
+        .filter(video_club::video_id.eq(video_id))
+        .order(video_club::club_id.asc())
+        .select(VideoClub::as_select())
+        .load(connection)?;
+
+    return Ok(VideoShares {
+        users,
+        teams,
+        clubs,
+    });
+}
+
+pub fn authorize_update_video_user(
+    requesting_user: Claims,
+    video_id: i64,
+    user_id: i64,
+    permissions: VideoPermissions,
+) -> Result<VideoUser, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    let video_user = find_shared_video_user(video_id, user_id)?;
+
+    // Non è possibile concedere dei permessi che non si hanno, né togliere dei permessi a chi ne ha più di sé
+    if !granted.contains(&permissions) || !granted.contains(&shared_permissions(&video_user)) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't change the permissions of user {} on video {} beyond their own",
+                requesting_user.subject_id, user_id, video_id
+            ),
+        });
+    }
+
+    return update_video_user(video_id, user_id, permissions);
+}
+
+/// Sostituisce i permessi concessi all'utente sul video.
+pub fn update_video_user(
+    video_id: i64,
+    user_id: i64,
+    permissions: VideoPermissions,
+) -> Result<VideoUser, ApiError> {
+    use domain::schema::video_user;
+
+    permissions.validate()?;
+
+    find_shared_video_user(video_id, user_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::update(
+        video_user::table
+            .filter(video_user::video_id.eq(video_id))
+            .filter(video_user::user_id.eq(user_id)),
+    )
+    .set((
+        video_user::read.eq(permissions.read),
+        video_user::edit.eq(permissions.edit),
+        video_user::delete.eq(permissions.delete),
+        video_user::share.eq(permissions.share),
+    ))
+    .execute(connection)?;
+
+    return find_shared_video_user(video_id, user_id);
+}
+
+pub fn authorize_revoke_video_user(
+    requesting_user: Claims,
+    video_id: i64,
+    user_id: i64,
+) -> Result<VideoUser, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    let video_user = find_shared_video_user(video_id, user_id)?;
+
+    if !granted.contains(&shared_permissions(&video_user)) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't revoke the permissions of user {} on video {}, that are beyond their own",
+                requesting_user.subject_id, user_id, video_id
+            ),
+        });
+    }
+
+    return revoke_video_user(video_id, user_id);
+}
+
+/// Elimina la condivisione del video con l'utente e la restituisce.
+pub fn revoke_video_user(video_id: i64, user_id: i64) -> Result<VideoUser, ApiError> {
+    use domain::schema::video_user;
+
+    let video_user_to_delete = find_shared_video_user(video_id, user_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::delete(
+        video_user::table
+            .filter(video_user::video_id.eq(video_id))
+            .filter(video_user::user_id.eq(user_id)),
+    )
+    .execute(connection)?;
+
+    return Ok(video_user_to_delete);
+}
+
+pub fn authorize_update_video_team(
+    requesting_user: Claims,
+    video_id: i64,
+    team_id: i64,
+    permissions: VideoPermissions,
+) -> Result<VideoTeam, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    let video_team = find_video_team(video_id, team_id)?;
+
+    // Non è possibile concedere dei permessi che non si hanno, né togliere dei permessi a chi ne ha più di sé
+    if !granted.contains(&permissions) || !granted.contains(&team_permissions(&video_team)) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't change the permissions of team {} on video {} beyond their own",
+                requesting_user.subject_id, team_id, video_id
+            ),
+        });
+    }
+
+    return update_video_team(video_id, team_id, permissions);
+}
+
+/// Sostituisce i permessi concessi alla squadra sul video.
+pub fn update_video_team(
+    video_id: i64,
+    team_id: i64,
+    permissions: VideoPermissions,
+) -> Result<VideoTeam, ApiError> {
+    use domain::schema::video_team;
+
+    permissions.validate()?;
+
+    find_video_team(video_id, team_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::update(
+        video_team::table
+            .filter(video_team::video_id.eq(video_id))
+            .filter(video_team::team_id.eq(team_id)),
+    )
+    .set((
+        video_team::read.eq(permissions.read),
+        video_team::edit.eq(permissions.edit),
+        video_team::delete.eq(permissions.delete),
+        video_team::share.eq(permissions.share),
+    ))
+    .execute(connection)?;
+
+    return find_video_team(video_id, team_id);
+}
+
+pub fn authorize_revoke_video_team(
+    requesting_user: Claims,
+    video_id: i64,
+    team_id: i64,
+) -> Result<VideoTeam, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    let video_team = find_video_team(video_id, team_id)?;
+
+    if !granted.contains(&team_permissions(&video_team)) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't revoke the permissions of team {} on video {}, that are beyond their own",
+                requesting_user.subject_id, team_id, video_id
+            ),
+        });
+    }
+
+    return revoke_video_team(video_id, team_id);
+}
+
+/// Elimina la condivisione del video con la squadra e la restituisce.
+///
+/// I membri della squadra mantengono i permessi concessi loro in altro modo.
+pub fn revoke_video_team(video_id: i64, team_id: i64) -> Result<VideoTeam, ApiError> {
+    use domain::schema::video_team;
+
+    let video_team_to_delete = find_video_team(video_id, team_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::delete(
+        video_team::table
+            .filter(video_team::video_id.eq(video_id))
+            .filter(video_team::team_id.eq(team_id)),
+    )
+    .execute(connection)?;
+
+    return Ok(video_team_to_delete);
+}
+
+pub fn authorize_update_video_club(
+    requesting_user: Claims,
+    video_id: i64,
+    club_id: &str,
+    permissions: VideoPermissions,
+) -> Result<VideoClub, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    let video_club = find_video_club(video_id, club_id)?;
+
+    // Non è possibile concedere dei permessi che non si hanno, né togliere dei permessi a chi ne ha più di sé
+    if !granted.contains(&permissions) || !granted.contains(&club_permissions(&video_club)) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't change the permissions of club {} on video {} beyond their own",
+                requesting_user.subject_id, club_id, video_id
+            ),
+        });
+    }
+
+    return update_video_club(video_id, club_id, permissions);
+}
+
+/// Sostituisce i permessi concessi alla società sportiva sul video.
+pub fn update_video_club(
+    video_id: i64,
+    club_id: &str,
+    permissions: VideoPermissions,
+) -> Result<VideoClub, ApiError> {
+    use domain::schema::video_club;
+
+    permissions.validate()?;
+
+    find_video_club(video_id, club_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::update(
+        video_club::table
+            .filter(video_club::video_id.eq(video_id))
+            .filter(video_club::club_id.eq(club_id)),
+    )
+    .set((
+        video_club::read.eq(permissions.read),
+        video_club::edit.eq(permissions.edit),
+        video_club::delete.eq(permissions.delete),
+        video_club::share.eq(permissions.share),
+    ))
+    .execute(connection)?;
+
+    return find_video_club(video_id, club_id);
+}
+
+pub fn authorize_revoke_video_club(
+    requesting_user: Claims,
+    video_id: i64,
+    club_id: &str,
+) -> Result<VideoClub, ApiError> {
+    let granted = video_permissions(requesting_user.subject_id, video_id)?;
+
+    if !granted.share {
+        return Err(not_authorized_to_share(
+            requesting_user.subject_id,
+            video_id,
+        ));
+    }
+
+    let video_club = find_video_club(video_id, club_id)?;
+
+    if !granted.contains(&club_permissions(&video_club)) {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} can't revoke the permissions of club {} on video {}, that are beyond their own",
+                requesting_user.subject_id, club_id, video_id
+            ),
+        });
+    }
+
+    return revoke_video_club(video_id, club_id);
+}
+
+/// Elimina la condivisione del video con la società sportiva e la restituisce.
+///
+/// I membri della società sportiva mantengono i permessi concessi loro in altro modo.
+pub fn revoke_video_club(video_id: i64, club_id: &str) -> Result<VideoClub, ApiError> {
+    use domain::schema::video_club;
+
+    let video_club_to_delete = find_video_club(video_id, club_id)?;
+
+    let connection = &mut establish_connection();
+
+    diesel::delete(
+        video_club::table
+            .filter(video_club::video_id.eq(video_id))
+            .filter(video_club::club_id.eq(club_id)),
+    )
+    .execute(connection)?;
+
+    return Ok(video_club_to_delete);
+}
+
+// Condivisione del video con l'utente, che non può essere quella del proprietario
+fn find_shared_video_user(video_id: i64, user_id: i64) -> Result<VideoUser, ApiError> {
+    match find_video_user(user_id, video_id)? {
+        Some(video_user) if video_user.is_owner => {
+            return Err(ApiError {
+                http_status: Status::Conflict,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - User {} is the owner of video {}, their permissions can't be changed",
+                    user_id, video_id
+                ),
+            });
+        }
+        Some(video_user) => return Ok(video_user),
+        None => {
+            return Err(ApiError {
+                http_status: Status::NotFound,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Video {} is not shared with user {}",
+                    video_id, user_id
+                ),
+            });
+        }
+    }
+}
+
+// Condivisione del video con la squadra
+fn find_video_team(video_id: i64, team_id: i64) -> Result<VideoTeam, ApiError> {
+    use domain::schema::video_team;
+
+    let connection = &mut establish_connection();
+
+    let video_team = video_team::table
+        .filter(video_team::video_id.eq(video_id))
+        .filter(video_team::team_id.eq(team_id))
+        .select(VideoTeam::as_select())
+        .first(connection)
+        .optional()?;
+
+    match video_team {
+        Some(video_team) => return Ok(video_team),
+        None => {
+            return Err(ApiError {
+                http_status: Status::NotFound,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Video {} is not shared with team {}",
+                    video_id, team_id
+                ),
+            });
+        }
+    }
+}
+
+// Condivisione del video con la società sportiva
+fn find_video_club(video_id: i64, club_id: &str) -> Result<VideoClub, ApiError> {
+    use domain::schema::video_club;
+
+    let connection = &mut establish_connection();
+
+    let video_club = video_club::table
+        .filter(video_club::video_id.eq(video_id))
+        .filter(video_club::club_id.eq(club_id))
+        .select(VideoClub::as_select())
+        .first(connection)
+        .optional()?;
+
+    match video_club {
+        Some(video_club) => return Ok(video_club),
+        None => {
+            return Err(ApiError {
+                http_status: Status::NotFound,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Video {} is not shared with club {}",
+                    video_id, club_id
+                ),
+            });
+        }
+    }
+}
+
+// Controlla che gli utenti, le squadre e le società sportive della richiesta esistano
+fn check_recipients(request: &VideoShareRequest) -> Result<(), ApiError> {
+    use domain::schema::{sports_club, team, user};
+
+    let connection = &mut establish_connection();
+
+    let found_users: Vec<i64> = user::table
+        .filter(user::person_id.eq_any(&request.user_ids))
+        .select(user::person_id)
+        .load(connection)?;
+    check_all_found("users", &request.user_ids, &found_users)?;
+
+    let found_teams: Vec<i64> = team::table
+        .filter(team::id.eq_any(&request.team_ids))
+        .select(team::id)
+        .load(connection)?;
+    check_all_found("teams", &request.team_ids, &found_teams)?;
+
+    let found_clubs: Vec<String> = sports_club::table
+        .filter(sports_club::vat_number.eq_any(&request.club_ids))
+        .select(sports_club::vat_number)
+        .load(connection)?;
+    check_all_found("clubs", &request.club_ids, &found_clubs)?;
+
+    return Ok(());
+}
+
+fn check_all_found<T: PartialEq + std::fmt::Display>(
+    kind: &str,
+    requested: &[T],
+    found: &[T],
+) -> Result<(), ApiError> {
+    let missing: Vec<String> = requested
+        .iter()
+        .filter(|id| !found.contains(id))
+        .map(|id| id.to_string())
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    return Err(ApiError {
+        http_status: Status::NotFound,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error - The following {} don't exist: {}",
+            kind,
+            missing.join(", ")
+        ),
+    });
+}
+
+fn not_authorized_to_share(person_id: i64, video_id: i64) -> ApiError {
+    ApiError {
+        http_status: Status::Forbidden,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::AuthorizationError,
+        message: format!(
+            "Error - User {} is not authorized to share video {}",
+            person_id, video_id
+        ),
+    }
+}
@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use diesel::prelude::*;
+use domain::models::full_tables::Video;
+use infrastructure::{
+    establish_connection,
+    storage::{put_dir, read, read_location, working_path},
+};
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::{media::authorize_media_token, Claims},
+    authorization::video_checks::{can_edit_video, can_read_video},
+    db_entities::video::{
+        file::{media_file, not_authorized_to_read, video_scope, MediaFile},
+        read::find_video,
+    },
+    player::{
+        blocking::run_blocking,
+        thumbnails::{generate_thumbnails, thumbnails_prefix, SPRITE_NAME},
+    },
+};
+
+/// Immagini generate per le anteprime di un video.
+#[derive(Debug, Clone, Copy)]
+pub enum ThumbnailImage {
+    /// Immagine di copertina
+    Poster,
+    /// Immagine con le miniature a intervalli regolari
+    Sprite,
+}
+
+pub async fn authorize_create_thumbnails(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<Video, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return create_thumbnails(video_id).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to generate the thumbnails of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Genera l'immagine di copertina, le miniature e la traccia WebVTT del video, le salva nell'archivio
+/// dei file multimediali insieme alle loro chiavi nel video e lo restituisce.
+///
+/// Viene eseguita quando un video viene registrato o estratto come clip, e può essere ripetuta
+/// per i video che ne sono privi: i file generati in precedenza vengono sostituiti.
+pub async fn create_thumbnails(video_id: i64) -> Result<Video, ApiError> {
+    use domain::schema::video;
+
+    let original_video = find_video(video_id)?;
+
+    let input = read_location(&original_video.file_location)
+        .map_err(|e| thumbnails_error(video_id, e.to_string()))?;
+
+    let prefix = thumbnails_prefix(video_id);
+    let generated = run_blocking(move || {
+        let dir = working_path(&prefix);
+        let thumbnails = generate_thumbnails(&input, &dir)?;
+        put_dir(&prefix, &dir).map_err(|e| e.to_string())?;
+
+        // Le chiavi hanno gli stessi nomi dei file generati
+        let key = |file: &Path| match file.file_name().and_then(|name| name.to_str()) {
+            Some(name) => Ok(format!("{}/{}", prefix, name)),
+            None => Err(format!("Invalid file {}", file.display())),
+        };
+
+        Ok((
+            key(&thumbnails.poster)?,
+            key(&thumbnails.sprite)?,
+            key(&thumbnails.track)?,
+        ))
+    })
+    .await;
+
+    let (poster, sprite, track) = match generated {
+        Ok(keys) => keys,
+        Err(e) => return Err(thumbnails_error(video_id, e)),
+    };
+
+    let connection = &mut establish_connection();
+
+    diesel::update(video::table.filter(video::id.eq(video_id)))
+        .set((
+            video::poster_location.eq(Some(poster)),
+            video::sprite_location.eq(Some(sprite)),
+            video::thumbnails_location.eq(Some(track)),
+        ))
+        .execute(connection)?;
+
+    return find_video(video_id);
+}
+
+pub fn authorize_thumbnail_image(
+    requesting_user: Claims,
+    video_id: i64,
+    image: ThumbnailImage,
+) -> Result<MediaFile, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return thumbnail_image(video_id, image);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce un'immagine delle anteprime richiesta tramite un URL firmato del suo video.
+pub fn signed_thumbnail_image(
+    token: &str,
+    video_id: i64,
+    image: ThumbnailImage,
+) -> Result<MediaFile, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return thumbnail_image(video_id, image);
+}
+
+/// Restituisce l'immagine di copertina o le miniature del video.
+pub fn thumbnail_image(video_id: i64, image: ThumbnailImage) -> Result<MediaFile, ApiError> {
+    let video = find_video(video_id)?;
+
+    let location = match image {
+        ThumbnailImage::Poster => video.poster_location,
+        ThumbnailImage::Sprite => video.sprite_location,
+    };
+
+    match location {
+        Some(location) => return Ok(media_file(&location, &video.name)),
+        None => return Err(thumbnails_not_found(video_id)),
+    }
+}
+
+pub fn authorize_thumbnails_track(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<String, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return thumbnails_track(video_id, None);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce la traccia WebVTT delle miniature richiesta tramite un URL firmato del suo video.
+pub fn signed_thumbnails_track(token: &str, video_id: i64) -> Result<String, ApiError> {
+    authorize_media_token(token, Path::new(&video_scope(video_id)))?;
+
+    return thumbnails_track(video_id, Some(token));
+}
+
+/// Restituisce la traccia WebVTT delle miniature del video.
+///
+/// Le miniature sono indicate con un URL relativo a quello della traccia. Se la traccia è richiesta
+/// con un URL firmato, il token viene aggiunto agli URL delle miniature, così che il player possa scaricarle.
+pub fn thumbnails_track(video_id: i64, token: Option<&str>) -> Result<String, ApiError> {
+    let video = find_video(video_id)?;
+
+    let location = match video.thumbnails_location {
+        Some(location) => location,
+        None => return Err(thumbnails_not_found(video_id)),
+    };
+
+    let track = match read(&location) {
+        Ok(track) => String::from_utf8_lossy(&track).to_string(),
+        Err(e) => {
+            return Err(ApiError {
+                http_status: Status::NotFound,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Thumbnails track of video {} not available - {}",
+                    video_id, e
+                ),
+            });
+        }
+    };
+
+    match token {
+        Some(token) => {
+            return Ok(track.replace(
+                &format!("{}#", SPRITE_NAME),
+                &format!("{}?token={}#", SPRITE_NAME, token),
+            ))
+        }
+        None => return Ok(track),
+    }
+}
+
+fn thumbnails_not_found(video_id: i64) -> ApiError {
+    ApiError {
+        http_status: Status::NotFound,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error - The thumbnails of video {} have not been generated",
+            video_id
+        ),
+    }
+}
+
+fn thumbnails_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while generating the thumbnails of video {} - {}",
+            video_id, message
+        ),
+    }
+}
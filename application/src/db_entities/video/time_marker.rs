@@ -0,0 +1,638 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+use diesel::{prelude::*, result::Error};
+use domain::models::{
+    full_tables::{Formation, Game, TimeMarker, TimeMarkerPlayer, Video},
+    insertions::NewTimeMarker,
+    others::{
+        PlayerMoment, PlayerMomentsFilter, TimeMarkerData, TimeMarkerDetails, TimeMarkerRequest,
+    },
+    WithId,
+};
+use infrastructure::establish_connection;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+use validator::Validate;
+
+use crate::{
+    authentication::Claims,
+    authorization::video_checks::{can_edit_video, can_read_video},
+    db_entities::{
+        storage::usage::teams_of_video,
+        video::{
+            annotation::discard_annotated_images,
+            read::{find_video, list_other_angles},
+            rendered::discard_rendered,
+        },
+    },
+    player::chapters::chapters_prefix,
+};
+
+pub fn authorize_create_time_marker(
+    requesting_user: Claims,
+    video_id: i64,
+    request: TimeMarkerRequest,
+) -> Result<Vec<TimeMarkerDetails>, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        // Il segnaposto viene inserito solo nei video delle altre telecamere che l'utente può modificare
+        let mut authorized_angles = Vec::new();
+        if request.all_angles {
+            for angle in list_other_angles(&find_video(video_id)?)? {
+                if can_edit_video(requesting_user.subject_id, angle.id)? {
+                    authorized_angles.push(angle);
+                }
+            }
+        }
+
+        return create_time_marker(video_id, request.marker, authorized_angles);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to add markers to video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Inserisce un nuovo segnaposto nel video e negli altri video indicati, che devono essere della stessa
+/// sessione di registrazione, e restituisce i segnaposti inseriti.
+///
+/// Negli altri video il segnaposto viene inserito nello stesso momento della registrazione, tenendo conto
+/// dell'inizio di ciascun video. I video che non comprendono quel momento vengono saltati.
+/// I giocatori, la squadra e la formazione vengono copiati in tutti i segnaposti inseriti.
+pub fn create_time_marker(
+    video_id: i64,
+    marker: TimeMarkerData,
+    other_angles: Vec<Video>,
+) -> Result<Vec<TimeMarkerDetails>, ApiError> {
+    use domain::schema::{time_marker, time_marker_player};
+
+    marker.validate()?;
+
+    let video = find_video(video_id)?;
+    let (players, team_id) = check_marker_links(&video, &marker)?;
+
+    let mut new_markers = vec![NewTimeMarker {
+        instant: marker.instant,
+        video_id,
+        name: marker.name.clone(),
+        notes: marker.notes.clone(),
+        team_id,
+        formation_id: marker.formation_id,
+    }];
+
+    let recorded_at = video.date + (marker.instant - NaiveTime::MIN);
+    for angle in other_angles {
+        let offset = recorded_at - angle.date;
+        if offset >= TimeDelta::zero() && offset < TimeDelta::days(1) {
+            new_markers.push(NewTimeMarker {
+                instant: NaiveTime::MIN + offset,
+                video_id: angle.id,
+                name: marker.name.clone(),
+                notes: marker.notes.clone(),
+                team_id,
+                formation_id: marker.formation_id,
+            });
+        }
+    }
+
+    let connection = &mut establish_connection();
+
+    let inserted_markers: Vec<TimeMarkerDetails> =
+        match connection.transaction::<_, Error, _>(|connection| {
+            let mut inserted_markers = Vec::new();
+
+            for new_marker in &new_markers {
+                diesel::insert_into(time_marker::table)
+                    .values(new_marker)
+                    .execute(connection)?;
+
+                // NB: questo metodo per ottenere in ritorno il segnaposto inserito si affida al fatto che gli id siano autoincrementali.
+                // Purtroppo attualmente è l'unico modo con MySQL per ottenere l'id del segnaposto appena inserito.
+                // Valutare il passaggio a PostgreSQL per utilizzare il metodo `returning` di Diesel o attendere un supporto a MariaDB.
+                let inserted_marker = time_marker::table
+                    .order(time_marker::id.desc())
+                    .select(TimeMarker::as_select())
+                    .first(connection)?;
+
+                let marker_players: Vec<TimeMarkerPlayer> = players
+                    .iter()
+                    .map(|player_id| TimeMarkerPlayer {
+                        time_marker_id: inserted_marker.id,
+                        player_id: *player_id,
+                    })
+                    .collect();
+
+                diesel::insert_into(time_marker_player::table)
+                    .values(&marker_players)
+                    .execute(connection)?;
+
+                inserted_markers.push(TimeMarkerDetails {
+                    marker: inserted_marker,
+                    players: players.clone(),
+                });
+            }
+
+            Ok(inserted_markers)
+        }) {
+            Ok(m) => m,
+            Err(err) => {
+                return Err(ApiError {
+                    http_status: Status::InternalServerError,
+                    error_code: 123,
+                    error_type: ApiErrorType::ApplicationError,
+                    message: format!("Error while inserting new time marker - {}", err),
+                })
+            }
+        };
+
+    // I capitoli dei video cambiano con i loro segnaposti
+    for inserted_marker in &inserted_markers {
+        discard_rendered(&chapters_prefix(inserted_marker.marker.video_id));
+    }
+
+    return Ok(inserted_markers);
+}
+
+pub fn authorize_find_time_marker(
+    requesting_user: Claims,
+    video_id: i64,
+    marker_id: i64,
+) -> Result<TimeMarkerDetails, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        let marker = find_time_marker(video_id, marker_id)?;
+        return Ok(with_players(vec![marker])?.remove(0));
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the markers of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Restituisce un segnaposto del video dato il suo ID.
+pub fn find_time_marker(video_id: i64, marker_id: i64) -> Result<TimeMarker, ApiError> {
+    use domain::schema::time_marker;
+
+    let connection = &mut establish_connection();
+
+    let marker = time_marker::table
+        .filter(time_marker::id.eq(marker_id))
+        .filter(time_marker::video_id.eq(video_id))
+        .select(TimeMarker::as_select())
+        .get_result(connection)?;
+
+    return Ok(marker);
+}
+
+pub fn authorize_list_time_markers(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<Vec<TimeMarkerDetails>, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return with_players(list_time_markers(video_id)?);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to read the markers of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Restituisce i segnaposti del video, ordinati per istante.
+pub fn list_time_markers(video_id: i64) -> Result<Vec<TimeMarker>, ApiError> {
+    use domain::schema::time_marker;
+
+    let connection = &mut establish_connection();
+
+    // Il video deve esistere, altrimenti la lista vuota non sarebbe distinguibile da un video senza segnaposti
+    find_video(video_id)?;
+
+    let markers = time_marker::table
+        .filter(time_marker::video_id.eq(video_id))
+        .order((time_marker::instant.asc(), time_marker::id.asc()))
+        .select(TimeMarker::as_select())
+        .load(connection)?;
+
+    return Ok(markers);
+}
+
+pub fn authorize_update_time_marker(
+    requesting_user: Claims,
+    video_id: i64,
+    marker_id: i64,
+    marker: TimeMarkerData,
+) -> Result<TimeMarkerDetails, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return update_time_marker(video_id, marker_id, marker);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to edit the markers of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Modifica un segnaposto del video, sostituendo i giocatori ai quali si riferisce.
+pub fn update_time_marker(
+    video_id: i64,
+    marker_id: i64,
+    marker: TimeMarkerData,
+) -> Result<TimeMarkerDetails, ApiError> {
+    use domain::schema::time_marker_player;
+
+    marker.validate()?;
+
+    // Il segnaposto non può essere spostato in un altro video
+    find_time_marker(video_id, marker_id)?;
+
+    let video = find_video(video_id)?;
+    let (players, team_id) = check_marker_links(&video, &marker)?;
+
+    let connection = &mut establish_connection();
+
+    let new_marker = NewTimeMarker {
+        instant: marker.instant,
+        video_id,
+        name: marker.name,
+        notes: marker.notes,
+        team_id,
+        formation_id: marker.formation_id,
+    };
+
+    let updated_marker = connection.transaction::<_, Error, _>(|connection| {
+        let updated_marker = new_marker
+            .to_identified(marker_id)
+            .save_changes::<TimeMarker>(connection)?;
+
+        diesel::delete(
+            time_marker_player::table.filter(time_marker_player::time_marker_id.eq(marker_id)),
+        )
+        .execute(connection)?;
+
+        let marker_players: Vec<TimeMarkerPlayer> = players
+            .iter()
+            .map(|player_id| TimeMarkerPlayer {
+                time_marker_id: marker_id,
+                player_id: *player_id,
+            })
+            .collect();
+
+        diesel::insert_into(time_marker_player::table)
+            .values(&marker_players)
+            .execute(connection)?;
+
+        Ok(updated_marker)
+    })?;
+
+    discard_rendered(&chapters_prefix(video_id));
+    discard_annotated_images(video_id, None, Some(marker_id));
+
+    return Ok(TimeMarkerDetails {
+        marker: updated_marker,
+        players,
+    });
+}
+
+pub fn authorize_delete_time_marker(
+    requesting_user: Claims,
+    video_id: i64,
+    marker_id: i64,
+) -> Result<TimeMarkerDetails, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return delete_time_marker(video_id, marker_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to delete the markers of video {}",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+pub fn delete_time_marker(video_id: i64, marker_id: i64) -> Result<TimeMarkerDetails, ApiError> {
+    use domain::schema::time_marker;
+
+    let connection = &mut establish_connection();
+
+    let marker_to_delete = with_players(vec![find_time_marker(video_id, marker_id)?])?.remove(0);
+
+    diesel::delete(time_marker::table.filter(time_marker::id.eq(marker_id))).execute(connection)?;
+
+    discard_rendered(&chapters_prefix(video_id));
+    discard_annotated_images(video_id, None, Some(marker_id));
+
+    return Ok(marker_to_delete);
+}
+
+pub fn authorize_list_player_moments(
+    requesting_user: Claims,
+    player_id: i64,
+    filter: PlayerMomentsFilter,
+) -> Result<Vec<PlayerMoment>, ApiError> {
+    let moments = list_player_moments(player_id, filter)?;
+
+    // Vengono restituiti solo i momenti dei video che l'utente può vedere
+    let mut readable_videos: HashMap<i64, bool> = HashMap::new();
+    let mut authorized_moments = Vec::new();
+    for moment in moments {
+        let video_id = moment.marker.marker.video_id;
+        let readable = match readable_videos.get(&video_id) {
+            Some(readable) => *readable,
+            None => {
+                let readable = can_read_video(requesting_user.subject_id, video_id)?;
+                readable_videos.insert(video_id, readable);
+                readable
+            }
+        };
+
+        if readable {
+            authorized_moments.push(moment);
+        }
+    }
+
+    return Ok(authorized_moments);
+}
+
+/// Restituisce i momenti di tutti i video nei quali è stato indicato il giocatore, ordinati per data di
+/// registrazione.
+///
+/// I momenti possono essere filtrati per nome del segnaposto, per squadra e per data del video.
+pub fn list_player_moments(
+    player_id: i64,
+    filter: PlayerMomentsFilter,
+) -> Result<Vec<PlayerMoment>, ApiError> {
+    use domain::schema::{player, time_marker, time_marker_player, video};
+
+    let connection = &mut establish_connection();
+
+    // Il giocatore deve esistere, altrimenti la lista vuota non sarebbe distinguibile da un giocatore senza momenti
+    player::table
+        .filter(player::person_id.eq(player_id))
+        .select(player::person_id)
+        .first::<i64>(connection)?;
+
+    let mut query = time_marker_player::table
+        .inner_join(time_marker::table.inner_join(video::table))
+        .filter(time_marker_player::player_id.eq(player_id))
+        .into_boxed();
+
+    if let Some(name) = filter.name {
+        query = query.filter(time_marker::name.eq(name));
+    }
+
+    if let Some(team_id) = filter.team_id {
+        query = query.filter(time_marker::team_id.eq(team_id));
+    }
+
+    if let Some(from_date) = filter.from_date {
+        query = query.filter(video::date.ge(from_date));
+    }
+
+    if let Some(to_date) = filter.to_date {
+        query = query.filter(video::date.le(to_date));
+    }
+
+    let rows: Vec<(TimeMarker, String, NaiveDateTime)> = query
+        .order((
+            video::date.asc(),
+            time_marker::instant.asc(),
+            time_marker::id.asc(),
+        ))
+        .select((TimeMarker::as_select(), video::name, video::date))
+        .load(connection)?;
+
+    let mut videos: HashMap<i64, (String, NaiveDateTime)> = HashMap::new();
+    let mut markers = Vec::new();
+    for (marker, video_name, video_date) in rows {
+        videos.insert(marker.video_id, (video_name, video_date));
+        markers.push(marker);
+    }
+
+    let moments = with_players(markers)?
+        .into_iter()
+        .map(|marker| {
+            let (video_name, video_date) = videos[&marker.marker.video_id].clone();
+            let offset = marker.marker.instant - NaiveTime::MIN;
+
+            PlayerMoment {
+                video_name,
+                offset_ms: offset.num_milliseconds(),
+                recorded_at: video_date + offset,
+                marker,
+            }
+        })
+        .collect();
+
+    return Ok(moments);
+}
+
+// Controlla i giocatori, la squadra e la formazione indicati nel segnaposto e restituisce i giocatori senza
+// ripetizioni e la squadra del segnaposto, che se non indicata è quella della formazione.
+// La formazione deve essere una delle due della partita alla quale appartiene il video e i giocatori, se è
+// indicata una formazione, devono farne parte; altrimenti devono far parte della squadra. La squadra deve
+// essere una di quelle dell'allenamento o della partita del video.
+fn check_marker_links(
+    video: &Video,
+    marker: &TimeMarkerData,
+) -> Result<(Vec<i64>, Option<i64>), ApiError> {
+    use domain::schema::{
+        formation, formation_player, game, player, player_team, recording_session,
+    };
+
+    let connection = &mut establish_connection();
+
+    let mut players: Vec<i64> = Vec::new();
+    for player_id in &marker.players {
+        if !players.contains(player_id) {
+            players.push(*player_id);
+        }
+    }
+
+    let existing_players: i64 = player::table
+        .filter(player::person_id.eq_any(&players))
+        .count()
+        .get_result(connection)?;
+
+    if existing_players != players.len() as i64 {
+        return Err(ApiError {
+            http_status: Status::BadRequest,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: "Error - Some of the players of the marker don't exist".to_string(),
+        });
+    }
+
+    let mut team_id = marker.team_id;
+
+    if let Some(formation_id) = marker.formation_id {
+        let booking_id: i64 = recording_session::table
+            .filter(recording_session::id.eq(video.session_id))
+            .select(recording_session::booking_id)
+            .first(connection)?;
+
+        let game = game::table
+            .filter(game::booking_id.eq(booking_id))
+            .select(Game::as_select())
+            .first(connection)
+            .optional()?;
+
+        let is_game_formation = match game {
+            Some(game) => {
+                game.home_formation_id == formation_id
+                    || game.visiting_formation_id == Some(formation_id)
+            }
+            None => false,
+        };
+
+        if !is_game_formation {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Formation {} doesn't belong to the game of video {}",
+                    formation_id, video.id
+                ),
+            });
+        }
+
+        let formation = formation::table
+            .filter(formation::id.eq(formation_id))
+            .select(Formation::as_select())
+            .first(connection)?;
+
+        if team_id.is_some_and(|team_id| team_id != formation.team_id) {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Formation {} doesn't belong to team {}",
+                    formation_id,
+                    team_id.unwrap_or_default()
+                ),
+            });
+        }
+        team_id = Some(formation.team_id);
+
+        let formation_players: i64 = formation_player::table
+            .filter(formation_player::formation_id.eq(formation_id))
+            .filter(formation_player::player_id.eq_any(&players))
+            .count()
+            .get_result(connection)?;
+
+        if formation_players != players.len() as i64 {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Some of the players of the marker aren't part of formation {}",
+                    formation_id
+                ),
+            });
+        }
+    } else if let Some(team_id) = team_id {
+        // Senza una formazione, i giocatori devono far parte della squadra quando il video è stato registrato
+        let roster_players: i64 = player_team::table
+            .filter(player_team::team_id.eq(team_id))
+            .filter(player_team::player_id.eq_any(&players))
+            .filter(player_team::since_date.le(video.date))
+            .filter(
+                player_team::until_date
+                    .is_null()
+                    .or(player_team::until_date.gt(video.date)),
+            )
+            .select(diesel::dsl::count_distinct(player_team::player_id))
+            .get_result(connection)?;
+
+        if roster_players != players.len() as i64 {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Some of the players of the marker weren't part of team {} when video {} was recorded",
+                    team_id, video.id
+                ),
+            });
+        }
+    }
+
+    // La squadra deve aver partecipato all'allenamento o alla partita del video
+    if let Some(team_id) = team_id {
+        let video_teams = teams_of_video(connection, video.id)?;
+
+        if !video_teams.iter().any(|team| team.id == team_id) {
+            return Err(ApiError {
+                http_status: Status::BadRequest,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error - Team {} didn't take part in the training or game of video {}",
+                    team_id, video.id
+                ),
+            });
+        }
+    }
+
+    return Ok((players, team_id));
+}
+
+// Aggiunge ai segnaposti i giocatori ai quali si riferiscono
+fn with_players(markers: Vec<TimeMarker>) -> Result<Vec<TimeMarkerDetails>, ApiError> {
+    use domain::schema::time_marker_player;
+
+    let connection = &mut establish_connection();
+
+    let marker_players = time_marker_player::table
+        .filter(
+            time_marker_player::time_marker_id
+                .eq_any(markers.iter().map(|m| m.id).collect::<Vec<_>>()),
+        )
+        .select(TimeMarkerPlayer::as_select())
+        .load(connection)?;
+
+    let mut players_by_marker: HashMap<i64, Vec<i64>> = HashMap::new();
+    for marker_player in marker_players {
+        players_by_marker
+            .entry(marker_player.time_marker_id)
+            .or_default()
+            .push(marker_player.player_id);
+    }
+
+    let details = markers
+        .into_iter()
+        .map(|marker| TimeMarkerDetails {
+            players: players_by_marker.remove(&marker.id).unwrap_or_default(),
+            marker,
+        })
+        .collect();
+
+    return Ok(details);
+}
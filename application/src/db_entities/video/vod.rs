@@ -0,0 +1,148 @@
+use diesel::prelude::*;
+use domain::models::{full_tables::Video, others::SignedUrl};
+use infrastructure::{
+    establish_connection,
+    storage::{delete_dir, put_dir, read_location, working_path},
+};
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::{media::create_media_token, Claims},
+    authorization::video_checks::{can_edit_video, can_read_video},
+    db_entities::video::{
+        file::{not_authorized_to_read, video_scope},
+        read::find_video,
+    },
+    player::{
+        archive::{package_vod, vod_prefix},
+        blocking::run_blocking,
+        player::SIGNED_DASH_URL_PREFIX,
+    },
+};
+
+pub async fn authorize_create_vod(
+    requesting_user: Claims,
+    video_id: i64,
+) -> Result<Video, ApiError> {
+    if can_edit_video(requesting_user.subject_id, video_id)? {
+        return create_vod(video_id).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to publish video {} on demand",
+                requesting_user.subject_id, video_id
+            ),
+        });
+    }
+}
+
+/// Pubblica il video in formato DASH on demand nell'archivio dei file multimediali, salva la chiave
+/// del manifest nel video e lo restituisce.
+///
+/// Viene eseguita al termine di ogni sessione di registrazione, così che la diretta di ciascuna telecamera
+/// possa essere sostituita dal video registrato. Può essere ripetuta per i video che ne sono privi:
+/// la versione precedente viene sostituita.
+pub async fn create_vod(video_id: i64) -> Result<Video, ApiError> {
+    use domain::schema::video;
+
+    let original_video = find_video(video_id)?;
+
+    let input = read_location(&original_video.file_location)
+        .map_err(|e| vod_error(video_id, e.to_string()))?;
+
+    let prefix = vod_prefix(video_id);
+    let published = run_blocking(move || {
+        // I segmenti della versione precedente non sarebbero sostituiti tutti da quelli nuovi
+        delete_dir(&prefix).map_err(|e| e.to_string())?;
+
+        let dir = working_path(&prefix);
+        let manifest = package_vod(&input, &dir)?;
+        put_dir(&prefix, &dir).map_err(|e| e.to_string())?;
+
+        match manifest.file_name().and_then(|name| name.to_str()) {
+            Some(name) => Ok(format!("{}/{}", prefix, name)),
+            None => Err(format!("Invalid manifest {}", manifest.display())),
+        }
+    })
+    .await;
+
+    let manifest_key = match published {
+        Ok(manifest_key) => manifest_key,
+        Err(e) => return Err(vod_error(video_id, e)),
+    };
+
+    let connection = &mut establish_connection();
+
+    diesel::update(video::table.filter(video::id.eq(video_id)))
+        .set(video::vod_location.eq(Some(manifest_key)))
+        .execute(connection)?;
+
+    return find_video(video_id);
+}
+
+pub fn authorize_sign_vod(requesting_user: Claims, video_id: i64) -> Result<SignedUrl, ApiError> {
+    if can_read_video(requesting_user.subject_id, video_id)? {
+        return sign_vod(requesting_user.subject_id, video_id);
+    } else {
+        return Err(not_authorized_to_read(requesting_user.subject_id, video_id));
+    }
+}
+
+/// Restituisce un URL firmato del manifest DASH on demand del video, utilizzabile senza l'header
+/// di autenticazione fino alla sua scadenza.
+pub fn sign_vod(subject_id: i64, video_id: i64) -> Result<SignedUrl, ApiError> {
+    let video = find_video(video_id)?;
+
+    if video.vod_location.is_none() {
+        return Err(ApiError {
+            http_status: Status::NotFound,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: format!(
+                "Error - Video {} has not been published on demand",
+                video_id
+            ),
+        });
+    }
+
+    let scope = video_scope(video_id);
+
+    let media_token = match create_media_token(subject_id, scope.clone()) {
+        Ok(media_token) => media_token,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error while signing the on demand URL of video {} - {}",
+                    video_id, err
+                ),
+            });
+        }
+    };
+
+    return Ok(SignedUrl {
+        url: format!(
+            "{}/{}/{}/manifest.mpd",
+            SIGNED_DASH_URL_PREFIX, media_token.token, scope
+        ),
+        expires_at: media_token.expires_at,
+    });
+}
+
+fn vod_error(video_id: i64, message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!(
+            "Error while publishing video {} on demand - {}",
+            video_id, message
+        ),
+    }
+}
@@ -2,3 +2,4 @@ pub mod authentication;
 pub mod authorization;
 pub mod db_entities;
 pub mod player;
+pub mod secrets;
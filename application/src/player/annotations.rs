@@ -0,0 +1,568 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use domain::models::full_tables::{Point, Shape, ShapeKind};
+
+use super::metadata::probe_metadata;
+
+/// Default width of the strokes, relative to the width of the frame
+const DEFAULT_STROKE_WIDTH: f64 = 0.005;
+/// Length of the arrowheads, relative to the width of the frame, at least a few times the stroke width
+const ARROWHEAD_LENGTH: f64 = 0.02;
+
+/// Annotation to draw on a frame
+pub struct AnnotationLayer {
+    /// Description of the annotation, listed in the top left corner
+    pub label: Option<String>,
+    pub shapes: Vec<Shape>,
+}
+
+// Folder of the media storage with the annotated images of the screenshots and of the markers of a video
+pub fn annotations_prefix(video_id: i64) -> String {
+    format!("annotations/video_{}", video_id)
+}
+
+// Draws `layers` on `image` (a path or a URL, as returned by `read_location`) and saves the result
+// as a JPEG image in `output`. The intermediate files are written in `dir`, that is removed at the end.
+//
+// The shapes are rasterized here into a transparent overlay of the size of the image, that FFmpeg
+// puts on the image together with the labels.
+pub fn render_annotations(
+    image: &str,
+    layers: &[AnnotationLayer],
+    dir: &Path,
+    output: &Path,
+) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let result = compose(image, layers, dir, output);
+
+    let _ = fs::remove_dir_all(dir);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+
+    result
+}
+
+fn compose(
+    image: &str,
+    layers: &[AnnotationLayer],
+    dir: &Path,
+    output: &Path,
+) -> Result<(), String> {
+    let metadata = probe_metadata(image)?;
+    let (width, height) = match (metadata.width, metadata.height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => (width as usize, height as usize),
+        _ => return Err("Failed to read the size of the image".to_string()),
+    };
+
+    let mut canvas = Canvas::new(width, height);
+    for layer in layers {
+        for shape in &layer.shapes {
+            canvas.draw(shape);
+        }
+    }
+
+    // FFmpeg runs in `dir`, so that the overlay and the label files can be referenced without escaping
+    // their paths in the filter graph: every other path has to be absolute
+    let dir =
+        fs::canonicalize(dir).map_err(|e| format!("Failed to open {}: {}", dir.display(), e))?;
+    let output = absolute(output)?;
+    let image = match Path::new(image).exists() {
+        true => absolute(Path::new(image))?.to_string_lossy().to_string(),
+        false => image.to_string(),
+    };
+
+    fs::write(dir.join("overlay.rgba"), &canvas.pixels)
+        .map_err(|e| format!("Failed to write the overlay: {}", e))?;
+
+    let mut filters = vec!["[0:v][1:v]overlay=0:0:format=auto".to_string()];
+    let font_size = (height / 36).max(12);
+    let mut label_count = 0;
+    let mut add_label = |text: &str, x: String, y: String| -> Result<(), String> {
+        let file = format!("label_{}.txt", label_count);
+        fs::write(dir.join(&file), text).map_err(|e| format!("Failed to write a label: {}", e))?;
+        filters.push(format!(
+            "drawtext=textfile={}:fontcolor=white:fontsize={}:box=1:boxcolor=black@0.6:boxborderw={}:x={}:y={}",
+            file,
+            font_size,
+            font_size / 4,
+            x,
+            y
+        ));
+        label_count += 1;
+        Ok(())
+    };
+
+    // The labels of the annotations are listed in the top left corner, the ones of the shapes
+    // are drawn next to their first point
+    let mut line = 0;
+    for layer in layers {
+        if let Some(label) = &layer.label {
+            add_label(
+                label,
+                font_size.to_string(),
+                (font_size + line * font_size * 3 / 2).to_string(),
+            )?;
+            line += 1;
+        }
+        for shape in &layer.shapes {
+            if let (Some(label), Some(point)) = (&shape.label, shape.points.first()) {
+                add_label(
+                    label,
+                    format!(
+                        "min({},w-text_w)",
+                        (point.x * width as f64) as usize + font_size / 2
+                    ),
+                    format!(
+                        "min({},h-text_h)",
+                        (point.y * height as f64) as usize + font_size / 2
+                    ),
+                )?;
+            }
+        }
+    }
+
+    let result = Command::new("ffmpeg")
+        .current_dir(&dir)
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-i")
+        .arg(&image)
+        .arg("-f")
+        .arg("rawvideo")
+        .arg("-pix_fmt")
+        .arg("rgba")
+        .arg("-s")
+        .arg(format!("{}x{}", width, height))
+        .arg("-i")
+        .arg("overlay.rgba")
+        .arg("-filter_complex")
+        .arg(filters.join(","))
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-q:v") // JPEG quality, from 2 (best) to 31
+        .arg("2")
+        .arg(&output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        return Err(format!(
+            "FFmpeg failed to draw the annotations: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+// Absolute path of a file that may not exist yet, in an existing folder
+fn absolute(path: &Path) -> Result<PathBuf, String> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+
+    let parent = fs::canonicalize(parent)
+        .map_err(|e| format!("Failed to open {}: {}", parent.display(), e))?;
+
+    match path.file_name() {
+        Some(name) => Ok(parent.join(name)),
+        None => Err(format!("Invalid path {}", path.display())),
+    }
+}
+
+// Transparent RGBA image on which the shapes are drawn
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    fn draw(&mut self, shape: &Shape) {
+        let points: Vec<(f64, f64)> = shape.points.iter().map(|p| self.pixel(p)).collect();
+        let half_width =
+            (shape.width.unwrap_or(DEFAULT_STROKE_WIDTH) * self.width as f64 / 2.0).max(0.5);
+        let color = parse_color(&shape.color);
+        let fill = shape.fill.as_deref().map(parse_color);
+
+        let head = match (&shape.kind, points.as_slice()) {
+            (ShapeKind::Arrow | ShapeKind::Path, [.., from, to]) => {
+                let length = (ARROWHEAD_LENGTH * self.width as f64).max(half_width * 6.0);
+                Some(arrowhead(*from, *to, length))
+            }
+            _ => None,
+        };
+
+        // Every shape is drawn on a mask first, so that overlapping parts of the same shape
+        // are not blended twice with semi-transparent colors. The masks only cover the bounding box
+        // of the shape, that is usually a small part of the frame
+        let (min, max) = match shape.kind {
+            ShapeKind::Circle => {
+                bounding_box(&points[..1], distance(points[0], points[1]) + half_width)
+            }
+            _ => {
+                let outline: Vec<(f64, f64)> = points
+                    .iter()
+                    .chain(head.iter().flatten())
+                    .copied()
+                    .collect();
+                bounding_box(&outline, half_width)
+            }
+        };
+        let mut fill_mask = Mask::new(min, max, self.width, self.height);
+        let mut stroke_mask = Mask::new(min, max, self.width, self.height);
+
+        match shape.kind {
+            ShapeKind::Arrow | ShapeKind::Path => {
+                for segment in points.windows(2) {
+                    stroke_mask.segment(segment[0], segment[1], half_width);
+                }
+                if let Some(head) = head {
+                    stroke_mask.polygon(&head);
+                }
+            }
+            ShapeKind::Circle => {
+                let (center, edge) = (points[0], points[1]);
+                let radius = distance(center, edge);
+                if fill.is_some() {
+                    fill_mask.ring(center, 0.0, radius);
+                }
+                stroke_mask.ring(center, radius - half_width, radius + half_width);
+            }
+            ShapeKind::Zone => {
+                if fill.is_some() {
+                    fill_mask.polygon(&points);
+                }
+                for index in 0..points.len() {
+                    let next = (index + 1) % points.len();
+                    stroke_mask.segment(points[index], points[next], half_width);
+                }
+            }
+        }
+
+        if let Some(fill) = fill {
+            self.blend(&fill_mask, fill);
+        }
+        self.blend(&stroke_mask, color);
+    }
+
+    fn pixel(&self, point: &Point) -> (f64, f64) {
+        (point.x * self.width as f64, point.y * self.height as f64)
+    }
+
+    // Puts `color` over the pixels of the mask
+    fn blend(&mut self, mask: &Mask, color: [u8; 4]) {
+        let alpha = color[3] as f64 / 255.0;
+
+        for (x, y) in mask.set_pixels() {
+            let index = y * self.width + x;
+            let pixel = &mut self.pixels[index * 4..index * 4 + 4];
+            let below = pixel[3] as f64 / 255.0;
+            let out = alpha + below * (1.0 - alpha);
+
+            for channel in 0..3 {
+                pixel[channel] = ((color[channel] as f64 * alpha
+                    + pixel[channel] as f64 * below * (1.0 - alpha))
+                    / out)
+                    .round() as u8;
+            }
+            pixel[3] = (out * 255.0).round() as u8;
+        }
+    }
+}
+
+// Pixels covered by a shape, in a rectangle of the canvas
+struct Mask {
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Mask {
+    // Mask of the pixels of a canvas of `canvas_width` x `canvas_height` pixels in the rectangle
+    // between the corners
+    fn new(min: (f64, f64), max: (f64, f64), canvas_width: usize, canvas_height: usize) -> Mask {
+        let (left, right) = pixel_range(min.0, max.0, canvas_width);
+        let (top, bottom) = pixel_range(min.1, max.1, canvas_height);
+
+        Mask {
+            left,
+            top,
+            width: right - left,
+            height: bottom - top,
+            pixels: vec![false; (right - left) * (bottom - top)],
+        }
+    }
+
+    // Coordinates in the canvas of the pixels that are set
+    fn set_pixels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.pixels
+            .iter()
+            .enumerate()
+            .filter(|(_, set)| **set)
+            .map(|(index, _)| {
+                (
+                    self.left + index % self.width,
+                    self.top + index / self.width,
+                )
+            })
+    }
+
+    // Sets the pixels whose center is in the rectangle between the corners, and for which `inside` is true
+    fn fill(&mut self, min: (f64, f64), max: (f64, f64), inside: impl Fn(f64, f64) -> bool) {
+        let (left, right) = pixel_range(min.0, max.0, self.left + self.width);
+        let (top, bottom) = pixel_range(min.1, max.1, self.top + self.height);
+
+        for y in top.max(self.top)..bottom {
+            for x in left.max(self.left)..right {
+                if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                    self.pixels[(y - self.top) * self.width + (x - self.left)] = true;
+                }
+            }
+        }
+    }
+
+    // Line from `from` to `to` with round ends
+    fn segment(&mut self, from: (f64, f64), to: (f64, f64), half_width: f64) {
+        self.fill(
+            (from.0.min(to.0) - half_width, from.1.min(to.1) - half_width),
+            (from.0.max(to.0) + half_width, from.1.max(to.1) + half_width),
+            |x, y| distance_to_segment((x, y), from, to) <= half_width,
+        );
+    }
+
+    // Pixels between the two circles around `center`
+    fn ring(&mut self, center: (f64, f64), inner: f64, outer: f64) {
+        self.fill(
+            (center.0 - outer, center.1 - outer),
+            (center.0 + outer, center.1 + outer),
+            |x, y| {
+                let d = distance((x, y), center);
+                d >= inner && d <= outer
+            },
+        );
+    }
+
+    // Polygon with the even-odd rule
+    fn polygon(&mut self, vertices: &[(f64, f64)]) {
+        let min = vertices
+            .iter()
+            .fold((f64::MAX, f64::MAX), |m, v| (m.0.min(v.0), m.1.min(v.1)));
+        let max = vertices
+            .iter()
+            .fold((f64::MIN, f64::MIN), |m, v| (m.0.max(v.0), m.1.max(v.1)));
+
+        self.fill(min, max, |x, y| {
+            let mut inside = false;
+            for index in 0..vertices.len() {
+                let (a, b) = (vertices[index], vertices[(index + 1) % vertices.len()]);
+                if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1) {
+                    inside = !inside;
+                }
+            }
+            inside
+        });
+    }
+}
+
+// Rectangle around `points`, with a `margin` on every side
+fn bounding_box(points: &[(f64, f64)], margin: f64) -> ((f64, f64), (f64, f64)) {
+    let min = points
+        .iter()
+        .fold((f64::MAX, f64::MAX), |m, p| (m.0.min(p.0), m.1.min(p.1)));
+    let max = points
+        .iter()
+        .fold((f64::MIN, f64::MIN), |m, p| (m.0.max(p.0), m.1.max(p.1)));
+
+    (
+        (min.0 - margin, min.1 - margin),
+        (max.0 + margin, max.1 + margin),
+    )
+}
+
+// Pixels (as a start and an end) from `min` to `max` on an axis of `size` pixels
+fn pixel_range(min: f64, max: f64, size: usize) -> (usize, usize) {
+    let start = (min.floor().max(0.0) as usize).min(size);
+    let end = (max.ceil().max(0.0) as usize).min(size).max(start);
+
+    (start, end)
+}
+
+// Triangle of the head of an arrow that goes from `from` to `to`
+fn arrowhead(from: (f64, f64), to: (f64, f64), length: f64) -> [(f64, f64); 3] {
+    let d = distance(from, to).max(f64::EPSILON);
+    let (ux, uy) = ((to.0 - from.0) / d, (to.1 - from.1) / d);
+    let base = (to.0 - ux * length, to.1 - uy * length);
+    let half = length / 2.0;
+
+    [
+        to,
+        (base.0 - uy * half, base.1 + ux * half),
+        (base.0 + uy * half, base.1 - ux * half),
+    ]
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+fn distance_to_segment(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let length = (b.0 - a.0).powi(2) + (b.1 - a.1).powi(2);
+    if length == 0.0 {
+        return distance(p, a);
+    }
+
+    let t = (((p.0 - a.0) * (b.0 - a.0) + (p.1 - a.1) * (b.1 - a.1)) / length).clamp(0.0, 1.0);
+    distance(p, (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)))
+}
+
+// Color in the format #RRGGBB or #RRGGBBAA, already validated; opaque black if it can't be read
+fn parse_color(color: &str) -> [u8; 4] {
+    let hex = color.trim_start_matches('#');
+    let channel = |index: usize| {
+        hex.get(index * 2..index * 2 + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+    };
+
+    [
+        channel(0).unwrap_or(0),
+        channel(1).unwrap_or(0),
+        channel(2).unwrap_or(0),
+        channel(3).unwrap_or(255),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Masks covering a whole canvas of `size` x `size` pixels
+    fn full_mask(size: usize) -> Mask {
+        Mask::new((0.0, 0.0), (size as f64, size as f64), size, size)
+    }
+
+    fn is_set(mask: &Mask, x: usize, y: usize) -> bool {
+        mask.set_pixels().any(|pixel| pixel == (x, y))
+    }
+
+    #[test]
+    fn polygon_fills_its_inside_only() {
+        let mut mask = full_mask(10);
+        mask.polygon(&[(2.0, 2.0), (8.0, 2.0), (8.0, 8.0), (2.0, 8.0)]);
+
+        assert_eq!(mask.set_pixels().count(), 36);
+        assert!(is_set(&mask, 2, 2));
+        assert!(is_set(&mask, 7, 7));
+        assert!(!is_set(&mask, 1, 5));
+        assert!(!is_set(&mask, 8, 5));
+    }
+
+    #[test]
+    fn polygon_follows_the_even_odd_rule() {
+        // Pentagram: the center is crossed by two edges on each side, so it stays empty
+        let star: Vec<(f64, f64)> = (0..5)
+            .map(|index| {
+                let angle = std::f64::consts::PI * (0.8 * index as f64 - 0.5);
+                (50.0 + 40.0 * angle.cos(), 50.0 + 40.0 * angle.sin())
+            })
+            .collect();
+
+        let mut mask = full_mask(100);
+        mask.polygon(&star);
+
+        assert!(!is_set(&mask, 50, 50));
+        // Inside the upper point of the star
+        assert!(is_set(&mask, 50, 20));
+    }
+
+    #[test]
+    fn ring_covers_the_pixels_between_the_circles() {
+        let mut mask = full_mask(20);
+        mask.ring((10.0, 10.0), 4.0, 6.0);
+
+        assert!(!is_set(&mask, 10, 10));
+        assert!(is_set(&mask, 14, 10));
+        assert!(is_set(&mask, 10, 5));
+        assert!(!is_set(&mask, 17, 10));
+        assert!(mask.set_pixels().all(|(x, y)| (3.5..=6.5)
+            .contains(&distance((x as f64 + 0.5, y as f64 + 0.5), (10.0, 10.0)))));
+    }
+
+    #[test]
+    fn arrowhead_points_at_the_end_of_the_arrow() {
+        let [tip, left, right] = arrowhead((0.0, 0.0), (10.0, 0.0), 4.0);
+
+        assert_eq!(tip, (10.0, 0.0));
+        assert_eq!(left, (6.0, 2.0));
+        assert_eq!(right, (6.0, -2.0));
+    }
+
+    #[test]
+    fn arrowhead_of_a_zero_length_arrow_is_finite() {
+        let head = arrowhead((5.0, 5.0), (5.0, 5.0), 4.0);
+
+        assert!(head.iter().all(|(x, y)| x.is_finite() && y.is_finite()));
+    }
+
+    #[test]
+    fn masks_only_cover_the_bounding_box_of_the_shape() {
+        let mut canvas = Canvas::new(1000, 1000);
+        let (min, max) = bounding_box(&[(100.0, 100.0), (110.0, 120.0)], 2.0);
+        let mask = Mask::new(min, max, canvas.width, canvas.height);
+
+        assert_eq!(
+            (mask.left, mask.top, mask.width, mask.height),
+            (98, 98, 14, 24)
+        );
+
+        // Shapes partly outside the frame are cut at its edges
+        let mask = Mask::new((-5.0, 990.0), (5.0, 1010.0), canvas.width, canvas.height);
+        assert_eq!(
+            (mask.left, mask.top, mask.width, mask.height),
+            (0, 990, 5, 10)
+        );
+
+        canvas.draw(&Shape {
+            kind: ShapeKind::Circle,
+            points: vec![Point { x: 0.5, y: 0.5 }, Point { x: 0.51, y: 0.5 }],
+            color: "#FF0000".to_string(),
+            fill: None,
+            width: None,
+            label: None,
+        });
+
+        let drawn: Vec<usize> = canvas
+            .pixels
+            .chunks(4)
+            .enumerate()
+            .filter(|(_, pixel)| pixel[3] > 0)
+            .map(|(index, _)| index)
+            .collect();
+        assert!(!drawn.is_empty());
+        assert!(drawn.iter().all(|index| {
+            let (x, y) = ((index % 1000) as f64, (index / 1000) as f64);
+            distance((x + 0.5, y + 0.5), (500.0, 500.0)) <= 12.5
+        }));
+        assert_eq!(
+            canvas.pixels[(500 * 1000 + 510) * 4..][..4],
+            [255, 0, 0, 255]
+        );
+    }
+}
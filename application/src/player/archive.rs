@@ -0,0 +1,259 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, Local, NaiveDateTime, SubsecRound};
+use infrastructure::storage::{storage, working_path};
+
+use crate::player::player::LIVE_SEGMENT_SECONDS;
+
+/// Duration in seconds of every archive segment written during the capture
+pub const SEGMENT_SECONDS: u32 = 300;
+
+// Folder of the media storage with the recordings of the sessions
+const RECORDINGS_PREFIX: &str = "recordings";
+// Name of the archive segments, the wall clock time at which they were started
+const SEGMENT_NAME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// Key of the media storage of the recording of a camera for a recording session, without extension
+fn recording_key(session_id: i64, camera_id: i64) -> String {
+    format!(
+        "{}/session_{}/camera_{}",
+        RECORDINGS_PREFIX, session_id, camera_id
+    )
+}
+
+// Local folder with the archive of a camera for a recording session
+fn camera_dir(session_id: i64, camera_id: i64) -> PathBuf {
+    working_path(&recording_key(session_id, camera_id))
+}
+
+// Folder where FFmpeg writes the archive segments during the capture
+pub fn segments_dir(session_id: i64, camera_id: i64) -> PathBuf {
+    camera_dir(session_id, camera_id).join("segments")
+}
+
+// FFmpeg output options to write the archive segments in `dir`.
+//
+// The stream is copied without re-encoding into fragmented MP4 segments, so the data written
+// up to the last fragment stays readable even if the process is killed. Segment names contain
+// the wall clock time, so a restarted capture never overwrites the previous segments.
+pub fn segment_output_args(dir: &Path) -> Vec<String> {
+    vec![
+        "-map".to_string(),
+        "0".to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        "-f".to_string(),
+        "segment".to_string(),
+        "-segment_time".to_string(),
+        SEGMENT_SECONDS.to_string(),
+        "-segment_format".to_string(),
+        "mp4".to_string(),
+        "-segment_format_options".to_string(),
+        "movflags=+frag_keyframe+empty_moov+default_base_moof".to_string(),
+        "-reset_timestamps".to_string(),
+        "1".to_string(),
+        "-strftime".to_string(),
+        "1".to_string(),
+        dir.join(format!("{}.mp4", SEGMENT_NAME_FORMAT))
+            .to_string_lossy()
+            .to_string(),
+    ]
+}
+
+// Wall clock time at which the capture of the first archive segment of a camera started,
+// if the segments haven't been joined yet.
+//
+// The name of the segment has the precision of the second, so the creation time of its file is used
+// when the file system records it: FFmpeg creates the file as soon as it starts writing the segment.
+pub fn capture_start(session_id: i64, camera_id: i64) -> Option<NaiveDateTime> {
+    let segments = list_segments(&segments_dir(session_id, camera_id)).ok()?;
+    let first_segment = segments.first()?;
+
+    let named =
+        NaiveDateTime::parse_from_str(first_segment.file_stem()?.to_str()?, SEGMENT_NAME_FORMAT)
+            .ok()?;
+
+    // A creation time in a different second belongs to a copy of the segment, not to the capture
+    let created = fs::metadata(first_segment)
+        .and_then(|metadata| metadata.created())
+        .ok()
+        .map(|created| DateTime::<Local>::from(created).naive_local())
+        .filter(|created| created.trunc_subsecs(0) == named);
+
+    Some(created.unwrap_or(named))
+}
+
+// Recording sessions and cameras (as `(session_id, camera_id)`) with archive segments that haven't
+// been joined yet, for example because the server was stopped during the capture
+pub fn unfinished_captures() -> Vec<(i64, i64)> {
+    let mut captures = Vec::new();
+
+    for session_dir in subdirs(&working_path(RECORDINGS_PREFIX)) {
+        let Some(session_id) = parse_id(&session_dir, "session_") else {
+            continue;
+        };
+
+        for camera_dir in subdirs(&session_dir) {
+            let Some(camera_id) = parse_id(&camera_dir, "camera_") else {
+                continue;
+            };
+
+            if list_segments(&camera_dir.join("segments")).is_ok_and(|s| !s.is_empty()) {
+                captures.push((session_id, camera_id));
+            }
+        }
+    }
+
+    captures
+}
+
+// Archive segments in `dir`, in chronological order
+fn list_segments(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mp4"))
+        .collect();
+
+    // Segment names start with the capture time, so the alphabetical order is the chronological one
+    segments.sort();
+
+    Ok(segments)
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+// Id in the name of a folder like `session_1` or `camera_1`
+fn parse_id(dir: &Path, prefix: &str) -> Option<i64> {
+    dir.file_name()?
+        .to_str()?
+        .strip_prefix(prefix)?
+        .parse::<i64>()
+        .ok()
+}
+
+// Joins the archive segments of a camera into a single MP4 file, saves it in the media storage
+// and returns its key.
+//
+// The joined segments are removed once the file has been written, if anything goes wrong they are
+// kept so that the recording can be recovered by hand. The same goes for the joined file,
+// if it can't be saved in the media storage. Segments written in the meantime by a new capture
+// of the camera are left in place.
+pub fn finalize(session_id: i64, camera_id: i64) -> Result<String, String> {
+    let segments_dir = segments_dir(session_id, camera_id);
+    let output = camera_dir(session_id, camera_id).with_extension("mp4");
+
+    let segments = list_segments(&segments_dir)?;
+
+    if segments.is_empty() {
+        return Err(format!("No segments found in {}", segments_dir.display()));
+    }
+
+    let list_path = segments_dir.join("segments.txt");
+    let list = segments
+        .iter()
+        .map(|segment| format!("file '{}'\n", segment.display()))
+        .collect::<String>();
+
+    fs::write(&list_path, list)
+        .map_err(|e| format!("Failed to write {}: {}", list_path.display(), e))?;
+
+    let result = Command::new("ffmpeg")
+        .arg("-y") // Overwrite a file left by a previous attempt
+        .arg("-f")
+        .arg("concat")
+        .arg("-safe")
+        .arg("0")
+        .arg("-i")
+        .arg(&list_path)
+        .arg("-c")
+        .arg("copy")
+        .arg("-movflags") // Index at the beginning of the file, to start the playback without downloading it all
+        .arg("+faststart")
+        .arg(&output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        return Err(format!(
+            "FFmpeg failed to join the segments of {}: {}",
+            segments_dir.display(),
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    for path in segments.iter().chain([&list_path]) {
+        if let Err(e) = fs::remove_file(path) {
+            log::warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+    // Only succeeds if no new capture is writing in the folder
+    let _ = fs::remove_dir(&segments_dir);
+
+    let key = format!("{}.mp4", recording_key(session_id, camera_id));
+    storage()
+        .put(&key, &output)
+        .map_err(|e| format!("Failed to store {}: {}", output.display(), e))?;
+
+    Ok(key)
+}
+
+// Folder of the media storage with the videos published on demand
+pub const VOD_PREFIX: &str = "vod";
+
+// Folder of the media storage with the on demand DASH stream of a video,
+// named like the scope of its media tokens
+pub fn vod_prefix(video_id: i64) -> String {
+    format!("{}/video_{}", VOD_PREFIX, video_id)
+}
+
+// Publishes `video` (a path or a URL, as returned by `read_location`) as an on demand DASH stream
+// in the local folder `dir` and returns the location of its manifest.
+//
+// The streams are copied without re-encoding, so the live stream of a recording can be handed over
+// to its on demand version as soon as the archive is finalized. A previous version in `dir` is replaced.
+pub fn package_vod(video: &str, dir: &Path) -> Result<PathBuf, String> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)
+            .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let manifest = dir.join("manifest.mpd");
+
+    let result = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-i")
+        .arg(video)
+        .arg("-map")
+        .arg("0")
+        .arg("-c")
+        .arg("copy")
+        .arg("-f")
+        .arg("dash")
+        .arg("-seg_duration")
+        .arg(LIVE_SEGMENT_SECONDS.to_string())
+        .arg(&manifest)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        let _ = fs::remove_dir_all(dir);
+        return Err(format!(
+            "FFmpeg failed to publish the video on demand: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(manifest)
+}
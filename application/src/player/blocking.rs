@@ -0,0 +1,16 @@
+use rocket::tokio::task;
+
+// Runs `f` on the threads reserved to blocking work and returns its result.
+//
+// FFmpeg, FFprobe and the transfers to and from the media storage keep a thread busy for a long time,
+// so they are never run on the threads of the async runtime. A panic of `f` is returned as an error.
+pub async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    match task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) => Err(e.to_string()),
+    }
+}
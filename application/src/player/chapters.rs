@@ -0,0 +1,148 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::NaiveTime;
+use domain::models::full_tables::TimeMarker;
+
+use super::editing::webvtt_timestamp;
+
+/// Chapter of a video, that starts at a time marker and ends at the next one
+pub struct Chapter {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub title: String,
+}
+
+// Folder of the media storage with the copies of a video with embedded chapters
+pub fn chapters_prefix(video_id: i64) -> String {
+    format!("chapters/video_{}", video_id)
+}
+
+// Turns the markers of a video, ordered by instant, into chapters: every chapter starts at a marker
+// and ends at the next one, the last one at the end of the video.
+//
+// Markers at the same instant of the previous one or past the end of the video are skipped.
+pub fn chapters_from_markers(markers: &[TimeMarker], duration_ms: i64) -> Vec<Chapter> {
+    let starts: Vec<(i64, &str)> = markers
+        .iter()
+        .map(|marker| {
+            (
+                (marker.instant - NaiveTime::MIN).num_milliseconds(),
+                marker.name.as_str(),
+            )
+        })
+        .filter(|(start_ms, _)| *start_ms < duration_ms)
+        .fold(Vec::new(), |mut starts, (start_ms, title)| {
+            if starts.last().is_none_or(|(last, _)| *last < start_ms) {
+                starts.push((start_ms, title));
+            }
+            starts
+        });
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, (start_ms, title))| Chapter {
+            start_ms: *start_ms,
+            end_ms: starts
+                .get(index + 1)
+                .map(|(next, _)| *next)
+                .unwrap_or(duration_ms),
+            title: title.to_string(),
+        })
+        .collect()
+}
+
+// WebVTT chapters track, with a cue for every chapter
+pub fn webvtt_chapters(chapters: &[Chapter]) -> String {
+    let mut track = String::from("WEBVTT\n");
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        track.push_str(&format!(
+            "\n{}\n{} --> {}\n{}\n",
+            index + 1,
+            webvtt_timestamp(chapter.start_ms),
+            webvtt_timestamp(chapter.end_ms),
+            // A line with the arrow or an empty line would end the cue text
+            chapter.title.replace("-->", "->").replace('\n', " ")
+        ));
+    }
+
+    track
+}
+
+// FFmpeg metadata file with the title of the video and its chapters, in milliseconds
+pub fn ffmetadata_chapters(title: &str, chapters: &[Chapter]) -> String {
+    let mut metadata = format!(";FFMETADATA1\ntitle={}\n", ffmetadata_escape(title));
+
+    for chapter in chapters {
+        metadata.push_str(&format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.start_ms,
+            chapter.end_ms,
+            ffmetadata_escape(&chapter.title)
+        ));
+    }
+
+    metadata
+}
+
+// Copies `video` (a path or a URL, as returned by `read_location`) as an MP4 file in `output`,
+// replacing its global metadata and chapters with the ones of `metadata`, in the FFmpeg metadata format.
+// The streams are copied as they are, so the copy is fast and has the same quality.
+pub fn embed_chapters(video: &str, metadata: &str, output: &Path) -> Result<(), String> {
+    if let Some(dir) = output.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let metadata_path = output.with_extension("ffmetadata");
+    fs::write(&metadata_path, metadata)
+        .map_err(|e| format!("Failed to write {}: {}", metadata_path.display(), e))?;
+
+    let result = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-i")
+        .arg(video)
+        .arg("-i")
+        .arg(&metadata_path)
+        .arg("-map")
+        .arg("0")
+        .arg("-map_metadata")
+        .arg("1")
+        .arg("-map_chapters")
+        .arg("1")
+        .arg("-c")
+        .arg("copy")
+        .arg("-movflags") // Index at the beginning of the file, to start the playback without downloading it all
+        .arg("+faststart")
+        .arg(output)
+        .output();
+
+    let _ = fs::remove_file(&metadata_path);
+    let result = result.map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        let _ = fs::remove_file(output);
+        return Err(format!(
+            "FFmpeg failed to embed the chapters: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+// Escapes the characters with a special meaning in FFmpeg metadata files
+fn ffmetadata_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
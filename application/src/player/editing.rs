@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::NaiveTime;
+use domain::models::others::ClipMode;
+
+// Folder of the media storage with the screenshots extracted from a video
+pub fn screenshots_prefix(video_id: i64) -> String {
+    format!("screenshots/video_{}", video_id)
+}
+
+// Folder of the media storage with the clips cut from a video
+pub fn clips_prefix(video_id: i64) -> String {
+    format!("clips/video_{}", video_id)
+}
+
+// Position in a video in the format understood by FFmpeg, like 00:12:34.500
+pub fn ffmpeg_position(instant: NaiveTime) -> String {
+    instant.format("%H:%M:%S%.3f").to_string()
+}
+
+// Timestamp of a WebVTT cue at `ms` milliseconds from the start of a video, like 01:02:03.456
+pub fn webvtt_timestamp(ms: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+// Saves the frame shown at `instant` in `video` as a JPEG image in `output`, returns false if
+// the video has no frame at that instant. `video` is a path or a URL, as returned by `read_location`.
+//
+// FFmpeg ends without errors and without writing anything when the instant is past the end
+// of the video, so the result is checked on the written file.
+pub fn extract_frame(video: &str, instant: NaiveTime, output: &Path) -> Result<bool, String> {
+    if let Some(dir) = output.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let result = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-ss") // Before the input, to seek to the nearest keyframe instead of decoding everything before it
+        .arg(ffmpeg_position(instant))
+        .arg("-i")
+        .arg(video)
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-q:v") // JPEG quality, from 2 (best) to 31
+        .arg("2")
+        .arg(output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        let _ = fs::remove_file(output);
+        return Err(format!(
+            "FFmpeg failed to extract a frame: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    match fs::metadata(output) {
+        Ok(metadata) if metadata.len() > 0 => Ok(true),
+        _ => {
+            let _ = fs::remove_file(output);
+            Ok(false)
+        }
+    }
+}
+
+// Saves the part of `video` between `start` and `end` as an MP4 file in `output`, returns false if
+// the video ends before `start`. `video` is a path or a URL, as returned by `read_location`.
+//
+// With `ClipMode::Copy` the streams are copied as they are, so the clip starts from the keyframe
+// before `start`; with `ClipMode::Reencode` the video is encoded again and the clip starts exactly
+// at `start`.
+pub fn cut_clip(
+    video: &str,
+    start: NaiveTime,
+    end: NaiveTime,
+    mode: ClipMode,
+    output: &Path,
+) -> Result<bool, String> {
+    if let Some(dir) = output.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let duration = (end - start).num_milliseconds() as f64 / 1000.0;
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-ss")
+        .arg(ffmpeg_position(start))
+        .arg("-i")
+        .arg(video)
+        .arg("-t")
+        .arg(format!("{:.3}", duration))
+        .arg("-map")
+        .arg("0");
+
+    match mode {
+        ClipMode::Copy => {
+            command
+                .arg("-c")
+                .arg("copy")
+                .arg("-avoid_negative_ts") // The clip starts from 0 even if the first keyframe is before `start`
+                .arg("make_zero");
+        }
+        ClipMode::Reencode => {
+            command
+                .arg("-c:v")
+                .arg("libx264")
+                .arg("-preset")
+                .arg("veryfast")
+                .arg("-crf")
+                .arg("20")
+                .arg("-c:a")
+                .arg("aac");
+        }
+    }
+
+    let result = command
+        .arg("-movflags") // Index at the beginning of the file, to start the playback without downloading it all
+        .arg("+faststart")
+        .arg(output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        let _ = fs::remove_file(output);
+        return Err(format!(
+            "FFmpeg failed to cut a clip: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    // Past the end of the video FFmpeg writes a file without frames, that is little more than the header
+    let has_frames = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-count_packets")
+        .arg("-show_entries")
+        .arg("stream=nb_read_packets")
+        .arg("-of")
+        .arg("csv=p=0")
+        .arg(output)
+        .output()
+        .map(|probe| {
+            String::from_utf8_lossy(&probe.stdout)
+                .lines()
+                .any(|packets| packets.trim().parse::<u64>().is_ok_and(|p| p > 0))
+        })
+        .map_err(|e| format!("Failed to start FFprobe: {}", e))?;
+
+    if !has_frames {
+        let _ = fs::remove_file(output);
+    }
+
+    Ok(has_frames)
+}
@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::NaiveTime;
+
+use super::editing::ffmpeg_position;
+
+/// Size and frame rate of every highlight video, whatever the videos it is compiled from
+const HIGHLIGHT_WIDTH: u32 = 1280;
+const HIGHLIGHT_HEIGHT: u32 = 720;
+const HIGHLIGHT_FRAME_RATE: u32 = 30;
+/// Seconds a title card is shown before its segment
+const TITLE_CARD_SECONDS: f64 = 3.0;
+
+/// Part of a video to include in a highlight video
+pub struct HighlightPart {
+    /// Path or URL of the video, as returned by `read_location`
+    pub video: String,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Whether the video has an audio track, otherwise a silent one is added
+    pub has_audio: bool,
+    /// Title shown on a card before the part
+    pub title: Option<String>,
+}
+
+// Key of the highlight video compiled by a job in the media storage
+pub fn highlight_key(job_id: i64) -> String {
+    format!("highlights/highlight_{}.mp4", job_id)
+}
+
+// Compiles `parts` one after the other, each one preceded by its title card, in a single MP4 file in `output`.
+// The intermediate files are written in `dir`, that is removed at the end.
+//
+// Every part is encoded again with the same size, frame rate and codecs, so that the parts of videos
+// recorded by different cameras can be joined by the concat demuxer without encoding them once more.
+pub fn compile_highlight(parts: &[HighlightPart], dir: &Path, output: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let result = compile_parts(parts, dir, output);
+
+    let _ = fs::remove_dir_all(dir);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+
+    result
+}
+
+fn compile_parts(parts: &[HighlightPart], dir: &Path, output: &Path) -> Result<(), String> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    // The files of the list are relative to the folder of the list
+    let mut list = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        if let Some(title) = &part.title {
+            let card = format!("title_{}", index);
+            render_title_card(title, dir, &card)?;
+            list.push_str(&format!("file '{}.mp4'\n", card));
+        }
+
+        let segment = format!("part_{}.mp4", index);
+        render_part(part, &dir.join(&segment))?;
+        list.push_str(&format!("file '{}'\n", segment));
+    }
+
+    let list_path = dir.join("parts.txt");
+    fs::write(&list_path, list)
+        .map_err(|e| format!("Failed to write {}: {}", list_path.display(), e))?;
+
+    let result = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-f")
+        .arg("concat")
+        .arg("-i")
+        .arg(&list_path)
+        .arg("-c")
+        .arg("copy")
+        .arg("-movflags") // Index at the beginning of the file, to start the playback without downloading it all
+        .arg("+faststart")
+        .arg(output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        return Err(format!(
+            "FFmpeg failed to join the highlight segments: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+// Encodes the part of the video between `start` and `end` in `output`, with the common size and codecs
+fn render_part(part: &HighlightPart, output: &Path) -> Result<(), String> {
+    let duration = (part.end - part.start).num_milliseconds() as f64 / 1000.0;
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-ss")
+        .arg(ffmpeg_position(part.start))
+        .arg("-i")
+        .arg(&part.video);
+
+    if part.has_audio {
+        command.arg("-map").arg("0:v:0").arg("-map").arg("0:a:0");
+    } else {
+        command
+            .arg("-f")
+            .arg("lavfi")
+            .arg("-i")
+            .arg("anullsrc=channel_layout=stereo:sample_rate=48000")
+            .arg("-map")
+            .arg("0:v:0")
+            .arg("-map")
+            .arg("1:a:0");
+    }
+
+    command
+        .arg("-t")
+        .arg(format!("{:.3}", duration))
+        .arg("-vf")
+        .arg(format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps={fps}",
+            w = HIGHLIGHT_WIDTH,
+            h = HIGHLIGHT_HEIGHT,
+            fps = HIGHLIGHT_FRAME_RATE
+        ));
+
+    run_encoder(command, output, "encode a highlight segment")
+}
+
+// Encodes a black card with `title` in the middle in the file `name`.mp4 of `dir`, with the common size and codecs.
+// The title is read from a file, so that it doesn't need to be escaped for the filter graph.
+fn render_title_card(title: &str, dir: &Path, name: &str) -> Result<(), String> {
+    let text = format!("{}.txt", name);
+    fs::write(dir.join(&text), title)
+        .map_err(|e| format!("Failed to write the title {}: {}", name, e))?;
+
+    // FFmpeg runs in `dir`, so that the title file can be referenced without escaping its path
+    let mut command = Command::new("ffmpeg");
+    command
+        .current_dir(dir)
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-f")
+        .arg("lavfi")
+        .arg("-i")
+        .arg(format!(
+            "color=c=black:s={}x{}:r={}:d={}",
+            HIGHLIGHT_WIDTH, HIGHLIGHT_HEIGHT, HIGHLIGHT_FRAME_RATE, TITLE_CARD_SECONDS
+        ))
+        .arg("-f")
+        .arg("lavfi")
+        .arg("-i")
+        .arg("anullsrc=channel_layout=stereo:sample_rate=48000")
+        .arg("-t")
+        .arg(format!("{:.3}", TITLE_CARD_SECONDS))
+        .arg("-vf")
+        .arg(format!(
+            "drawtext=textfile={}:fontcolor=white:fontsize=64:x=(w-text_w)/2:y=(h-text_h)/2,setsar=1",
+            text
+        ));
+
+    run_encoder(
+        command,
+        Path::new(&format!("{}.mp4", name)),
+        "render a title card",
+    )
+}
+
+// Adds the common codecs to `command` and runs it, writing `output`
+fn run_encoder(mut command: Command, output: &Path, action: &str) -> Result<(), String> {
+    let result = command
+        .arg("-c:v")
+        .arg("libx264")
+        .arg("-preset")
+        .arg("veryfast")
+        .arg("-crf")
+        .arg("20")
+        .arg("-pix_fmt")
+        .arg("yuv420p")
+        .arg("-c:a")
+        .arg("aac")
+        .arg("-ar")
+        .arg("48000")
+        .arg("-ac")
+        .arg("2")
+        .arg(output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        return Err(format!(
+            "FFmpeg failed to {}: {}",
+            action,
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(())
+}
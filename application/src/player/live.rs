@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use domain::models::{
+    full_tables::Camera,
+    others::{ActiveStream, StreamState},
+};
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims,
+    authorization::{
+        booking_checks::can_edit_delete_booking, camera_checks::can_control_camera,
+        person_checks::is_administrator,
+    },
+    db_entities::{camera::read::find_camera, recording_session::read::find_recording_session},
+    player::player::{self, StreamInfo, StreamMap},
+};
+
+pub async fn authorize_start_camera_stream(
+    requesting_user: Claims,
+    camera_id: i64,
+    state: &StreamMap,
+) -> Result<ActiveStream, ApiError> {
+    if can_control_camera(requesting_user.subject_id, camera_id)? {
+        return start_camera_stream(camera_id, state).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to start the stream of camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
+
+pub async fn start_camera_stream(
+    camera_id: i64,
+    state: &StreamMap,
+) -> Result<ActiveStream, ApiError> {
+    let camera = find_camera(camera_id)?;
+
+    return start_stream(&camera, state).await;
+}
+
+pub async fn authorize_stop_camera_stream(
+    requesting_user: Claims,
+    camera_id: i64,
+    state: &StreamMap,
+) -> Result<ActiveStream, ApiError> {
+    if can_control_camera(requesting_user.subject_id, camera_id)? {
+        return stop_camera_stream(camera_id, state).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to stop the stream of camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
+
+pub async fn stop_camera_stream(
+    camera_id: i64,
+    state: &StreamMap,
+) -> Result<ActiveStream, ApiError> {
+    match stop_stream(camera_id, state).await {
+        Some(stopped_stream) => return Ok(stopped_stream),
+        None => {
+            return Err(ApiError {
+                http_status: Status::NotFound,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error - The stream of camera {} is not active", camera_id),
+            });
+        }
+    }
+}
+
+pub async fn authorize_start_session_streams(
+    requesting_user: Claims,
+    session_id: i64,
+    state: &StreamMap,
+) -> Result<Vec<ActiveStream>, ApiError> {
+    let session = find_recording_session(session_id)?;
+
+    if can_edit_delete_booking(
+        requesting_user.subject_id,
+        session.recording_session.booking_id,
+    )? {
+        return start_session_streams(session_id, state).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to start the streams of recording session {}",
+                requesting_user.subject_id, session_id
+            ),
+        });
+    }
+}
+
+pub async fn start_session_streams(
+    session_id: i64,
+    state: &StreamMap,
+) -> Result<Vec<ActiveStream>, ApiError> {
+    let session = find_recording_session(session_id)?;
+
+    // Disabled cameras are skipped
+    let mut started_streams = Vec::new();
+    for camera in session.cameras.iter().filter(|c| c.enabled) {
+        started_streams.push(start_stream(camera, state).await?);
+    }
+
+    return Ok(started_streams);
+}
+
+pub async fn authorize_stop_session_streams(
+    requesting_user: Claims,
+    session_id: i64,
+    state: &StreamMap,
+) -> Result<Vec<ActiveStream>, ApiError> {
+    let session = find_recording_session(session_id)?;
+
+    if can_edit_delete_booking(
+        requesting_user.subject_id,
+        session.recording_session.booking_id,
+    )? {
+        return stop_session_streams(session_id, state).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to stop the streams of recording session {}",
+                requesting_user.subject_id, session_id
+            ),
+        });
+    }
+}
+
+pub async fn stop_session_streams(
+    session_id: i64,
+    state: &StreamMap,
+) -> Result<Vec<ActiveStream>, ApiError> {
+    let session = find_recording_session(session_id)?;
+
+    // Cameras without an active stream are skipped
+    let mut stopped_streams = Vec::new();
+    for camera in &session.cameras {
+        if let Some(stopped_stream) = stop_stream(camera.id, state).await {
+            stopped_streams.push(stopped_stream);
+        }
+    }
+
+    return Ok(stopped_streams);
+}
+
+pub async fn authorize_list_active_streams(
+    requesting_user: Claims,
+    state: &StreamMap,
+) -> Result<Vec<ActiveStream>, ApiError> {
+    let active_streams = list_active_streams(state).await;
+
+    if is_administrator(requesting_user.subject_id)? {
+        return Ok(active_streams);
+    }
+
+    // Only the streams of the cameras that the user can control are shown
+    let mut authorized_cameras: HashMap<i64, bool> = HashMap::new();
+    let mut visible_streams = Vec::new();
+
+    for active_stream in active_streams {
+        let is_authorized = match authorized_cameras.get(&active_stream.camera_id) {
+            Some(is_authorized) => *is_authorized,
+            None => {
+                let is_authorized =
+                    can_control_camera(requesting_user.subject_id, active_stream.camera_id)?;
+                authorized_cameras.insert(active_stream.camera_id, is_authorized);
+                is_authorized
+            }
+        };
+
+        if is_authorized {
+            visible_streams.push(active_stream);
+        }
+    }
+
+    return Ok(visible_streams);
+}
+
+pub async fn list_active_streams(state: &StreamMap) -> Vec<ActiveStream> {
+    let mut active_streams: Vec<ActiveStream> = player::list_streams(state)
+        .await
+        .into_iter()
+        .map(to_active_stream)
+        .collect();
+
+    active_streams.sort_by_key(|s| s.camera_id);
+
+    return active_streams;
+}
+
+// Starts the live stream of the camera, a stream that is already active (or recording) is left as it is
+async fn start_stream(camera: &Camera, state: &StreamMap) -> Result<ActiveStream, ApiError> {
+    if !camera.enabled {
+        return Err(ApiError {
+            http_status: Status::BadRequest,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::ApplicationError,
+            message: format!("Error - Camera {} is disabled", camera.id),
+        });
+    }
+
+    let start_result = player::stream(camera, None, state).await;
+
+    match find_stream(camera.id, state).await {
+        Some(active_stream) if active_stream.state != StreamState::Failed => {
+            return Ok(active_stream)
+        }
+        _ => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error while starting the stream of camera {} - {}",
+                    camera.id,
+                    start_result.err().unwrap_or_default()
+                ),
+            });
+        }
+    }
+}
+
+// Stops a stream, returning its last state
+async fn stop_stream(camera_id: i64, state: &StreamMap) -> Option<ActiveStream> {
+    let stopped_stream = find_stream(camera_id, state).await?;
+
+    match player::stop(camera_id, state).await {
+        Ok(_) => Some(stopped_stream),
+        Err(_) => None, // Stopped in the meantime
+    }
+}
+
+async fn find_stream(camera_id: i64, state: &StreamMap) -> Option<ActiveStream> {
+    return player::list_streams(state)
+        .await
+        .into_iter()
+        .find(|stream_info| stream_info.camera_id == camera_id)
+        .map(to_active_stream);
+}
+
+fn to_active_stream(stream_info: StreamInfo) -> ActiveStream {
+    let status = stream_info.status;
+
+    // The uptime refers to the FFmpeg process that is currently running
+    let uptime_seconds = match (status.pid, status.process_started_at) {
+        (Some(_), Some(process_started_at)) => (Local::now().naive_local() - process_started_at)
+            .num_seconds()
+            .max(0),
+        _ => 0,
+    };
+
+    return ActiveStream {
+        camera_id: stream_info.camera_id,
+        session_id: stream_info.session_id,
+        manifest_url: player::manifest_url(stream_info.camera_id),
+        state: status.state,
+        pid: status.pid,
+        uptime_seconds,
+        restarts: status.restarts,
+        last_error: status.last_error,
+        stderr_tail: status.stderr_tail.into(),
+    };
+}
@@ -0,0 +1,186 @@
+use std::path::{Component, Path, PathBuf};
+
+use domain::models::others::SignedUrl;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::{
+        media::{authorize_media_token, create_media_token},
+        Claims,
+    },
+    authorization::{camera_checks::can_watch_camera, video_checks::can_read_video},
+    db_entities::camera::read::find_camera,
+    db_entities::video::file::video_scope,
+    player::{
+        archive::VOD_PREFIX,
+        player::{camera_stream_name, DASH_ROOT, SIGNED_DASH_URL_PREFIX},
+    },
+};
+
+/// Location of a DASH file
+pub enum DashFile {
+    /// File of a live stream, written by FFmpeg on the disk of the server
+    Live(PathBuf),
+    /// File of an on demand video, identified by its key in the media storage
+    OnDemand(String),
+}
+
+// Access to the published DASH streams, live (`CAM<camera id>/...`) and on demand (`video_<video id>/...`)
+//
+// Manifests and segments can be requested either with the usual Authorization header, or through
+// a signed URL for clients that can't set headers (like `<video>` elements and dash.js).
+// The token of a signed URL is part of the path, so the relative URLs of the segments listed in
+// the manifest carry it as well.
+
+pub fn authorize_dash_file(requesting_user: Claims, path: PathBuf) -> Result<DashFile, ApiError> {
+    let authorized = match dash_source(&path) {
+        Some(DashSource::Live(camera_id)) => {
+            can_watch_camera(requesting_user.subject_id, camera_id)?
+        }
+        Some(DashSource::OnDemand(video_id)) => {
+            can_read_video(requesting_user.subject_id, video_id)?
+        }
+        None => return Err(dash_file_not_found(&path)),
+    };
+
+    if authorized {
+        return dash_file_location(path);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to watch {}",
+                requesting_user.subject_id,
+                path.display()
+            ),
+        });
+    }
+}
+
+/// Returns the location of a DASH file requested through a signed URL.
+///
+/// The permissions are checked when the URL is signed: the token only gives access to the files
+/// of the stream it was signed for, until it expires.
+pub fn signed_dash_file(token: &str, path: PathBuf) -> Result<DashFile, ApiError> {
+    authorize_media_token(token, &path)?;
+
+    return dash_file_location(path);
+}
+
+pub fn authorize_sign_camera_stream(
+    requesting_user: Claims,
+    camera_id: i64,
+) -> Result<SignedUrl, ApiError> {
+    // The camera must exist, otherwise a 403 would hide the 404
+    find_camera(camera_id)?;
+
+    if can_watch_camera(requesting_user.subject_id, camera_id)? {
+        return sign_camera_stream(requesting_user.subject_id, camera_id);
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to watch the stream of camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
+
+/// Returns a signed URL of the DASH manifest of a camera, usable without the Authorization header
+/// until it expires.
+pub fn sign_camera_stream(subject_id: i64, camera_id: i64) -> Result<SignedUrl, ApiError> {
+    let stream_name = camera_stream_name(camera_id);
+
+    let media_token = match create_media_token(subject_id, stream_name.clone()) {
+        Ok(media_token) => media_token,
+        Err(err) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!(
+                    "Error while signing the stream URL of camera {} - {}",
+                    camera_id, err
+                ),
+            });
+        }
+    };
+
+    return Ok(SignedUrl {
+        url: format!(
+            "{}/{}/{}/manifest.mpd",
+            SIGNED_DASH_URL_PREFIX, media_token.token, stream_name
+        ),
+        expires_at: media_token.expires_at,
+    });
+}
+
+// Stream a DASH file belongs to
+enum DashSource {
+    /// Live stream of a camera, in a folder like `CAM1`
+    Live(i64),
+    /// On demand stream of a recorded video, in a folder like `video_1`
+    OnDemand(i64),
+}
+
+// Stream that contains the file, from the name of the first folder of the path
+fn dash_source(path: &Path) -> Option<DashSource> {
+    let name = match path.components().next() {
+        Some(Component::Normal(name)) => name.to_str()?,
+        _ => return None,
+    };
+
+    if let Some(camera_id) = name
+        .strip_prefix("CAM")
+        .and_then(|id| id.parse::<i64>().ok())
+    {
+        // Names like "CAM01" don't belong to any camera
+        if camera_stream_name(camera_id) == name {
+            return Some(DashSource::Live(camera_id));
+        }
+    }
+
+    if let Some(video_id) = name
+        .strip_prefix("video_")
+        .and_then(|id| id.parse::<i64>().ok())
+    {
+        if video_scope(video_id) == name {
+            return Some(DashSource::OnDemand(video_id));
+        }
+    }
+
+    return None;
+}
+
+// Location of a DASH file: live streams are published on the disk of the server,
+// on demand videos in the media storage
+fn dash_file_location(path: PathBuf) -> Result<DashFile, ApiError> {
+    match dash_source(&path) {
+        Some(DashSource::Live(_)) => return Ok(DashFile::Live(Path::new(DASH_ROOT).join(path))),
+        Some(DashSource::OnDemand(_)) => {
+            // Rocket only accepts normal components in the path, so it can be joined as a key
+            let key = path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            return Ok(DashFile::OnDemand(format!("{}/{}", VOD_PREFIX, key)));
+        }
+        None => return Err(dash_file_not_found(&path)),
+    }
+}
+
+pub fn dash_file_not_found(path: &Path) -> ApiError {
+    ApiError {
+        http_status: Status::NotFound,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!("Error - DASH file {} not found", path.display()),
+    }
+}
@@ -0,0 +1,98 @@
+use std::process::Command;
+
+use domain::models::full_tables::VideoMetadata;
+use serde::Deserialize;
+
+// Output of `ffprobe -of json`, limited to the entries requested by `probe_metadata`.
+// FFprobe writes the numbers of the format section as strings.
+#[derive(Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<i32>,
+    height: Option<i32>,
+    avg_frame_rate: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    bit_rate: Option<String>,
+    size: Option<String>,
+}
+
+// Reads the technical metadata of `video` (a path or a URL, as returned by `read_location`) with FFprobe.
+//
+// The metadata of the first video and audio tracks is returned, entries that FFprobe doesn't report are None.
+pub fn probe_metadata(video: &str) -> Result<VideoMetadata, String> {
+    let probe = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-show_entries")
+        .arg("format=duration,bit_rate,size:stream=codec_type,codec_name,width,height,avg_frame_rate")
+        .arg("-of")
+        .arg("json")
+        .arg(video)
+        .output()
+        .map_err(|e| format!("Failed to start FFprobe: {}", e))?;
+
+    if !probe.status.success() {
+        return Err(format!(
+            "FFprobe failed to read the video: {}",
+            String::from_utf8_lossy(&probe.stderr)
+        ));
+    }
+
+    let output: ProbeOutput = serde_json::from_slice(&probe.stdout)
+        .map_err(|e| format!("Invalid FFprobe output: {}", e))?;
+
+    let track = |codec_type: &str| {
+        output
+            .streams
+            .iter()
+            .find(|stream| stream.codec_type.as_deref() == Some(codec_type))
+    };
+    let video_track = track("video");
+    let audio_track = track("audio");
+    let format = output.format.as_ref();
+
+    Ok(VideoMetadata {
+        duration_ms: format
+            .and_then(|format| format.duration.as_deref())
+            .and_then(|duration| duration.parse::<f64>().ok())
+            .map(|seconds| (seconds * 1000.0).round() as i64),
+        width: video_track.and_then(|stream| stream.width),
+        height: video_track.and_then(|stream| stream.height),
+        frame_rate: video_track
+            .and_then(|stream| stream.avg_frame_rate.as_deref())
+            .and_then(parse_frame_rate),
+        video_codec: video_track.and_then(|stream| stream.codec_name.clone()),
+        audio_codec: audio_track.and_then(|stream| stream.codec_name.clone()),
+        bit_rate: format
+            .and_then(|format| format.bit_rate.as_deref())
+            .and_then(|bit_rate| bit_rate.parse::<i64>().ok()),
+        file_size: format
+            .and_then(|format| format.size.as_deref())
+            .and_then(|size| size.parse::<i64>().ok()),
+    })
+}
+
+// Frame rate written by FFprobe as a fraction, like 30000/1001; 0/0 when it's unknown
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (numerator, denominator) = rate.split_once('/')?;
+    let numerator = numerator.parse::<f64>().ok()?;
+    let denominator = denominator.parse::<f64>().ok()?;
+
+    if numerator > 0.0 && denominator > 0.0 {
+        Some(numerator / denominator)
+    } else {
+        None
+    }
+}
@@ -1 +1,14 @@
-pub mod player;
\ No newline at end of file
+pub mod annotations;
+pub mod archive;
+pub mod blocking;
+pub mod chapters;
+pub mod editing;
+pub mod highlight;
+pub mod live;
+pub mod media;
+pub mod metadata;
+pub mod player;
+pub mod probe;
+pub mod scheduler;
+pub mod supervisor;
+pub mod thumbnails;
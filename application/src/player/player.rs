@@ -1,82 +1,236 @@
-use crate::db_entities;
+use crate::player::archive;
+use crate::player::supervisor::{StreamStatus, SupervisedStream};
+use crate::secrets;
+use domain::models::{full_tables::Camera, others::StreamState};
 use rocket::tokio::sync::Mutex;
 use std::collections::HashMap;
 use std::env;
-use std::process::{Child, Command};
 use std::string::String;
 use std::sync::Arc;
 
-type StreamMap = Arc<Mutex<HashMap<String, Child>>>;
-type Cams = Arc<Mutex<Vec<(String, String)>>>;
+/// Supervised FFmpeg processes, keyed by camera id
+pub type StreamMap = Arc<Mutex<HashMap<i64, CameraStream>>>;
+
+/// Folder where the DASH streams are published
+pub const DASH_ROOT: &str = "./infrastructure/tmp/dash";
+/// Path where the DASH streams are served to authenticated requests
+pub const DASH_URL_PREFIX: &str = "/media/dash";
+/// Path where the DASH streams are served to requests with a signed URL
+pub const SIGNED_DASH_URL_PREFIX: &str = "/media/signed";
+
+/// Duration in seconds of every segment of the live streams
+pub const LIVE_SEGMENT_SECONDS: u64 = 4;
+/// Segments kept on disk after they leave the live manifest
+const LIVE_EXTRA_WINDOW_SIZE: u64 = 5;
+/// Default seconds of a live stream that viewers can rewind to
+const DEFAULT_DVR_WINDOW_SECONDS: u64 = 1800;
+
+/// FFmpeg process receiving the stream of a camera
+pub struct CameraStream {
+    /// Recording session being archived, if any
+    pub session_id: Option<i64>,
+    pub stream: SupervisedStream,
+}
+
+/// Snapshot of a stream in the shared state
+pub struct StreamInfo {
+    pub camera_id: i64,
+    pub session_id: Option<i64>,
+    pub status: StreamStatus,
+}
 
 // Creation of the pipelines to the stream
+//
+// Every camera has a single stream, published at the same URL whoever started it (see `manifest_url`).
+// The live manifest lists the last `LIVE_DVR_WINDOW_SECONDS` of the stream, so viewers can rewind
+// during a live game. If `session_id` is provided, the stream is also saved as a durable archive of the
+// recording session (see `archive::segment_output_args`), that is published on demand when the session
+// ends (see `archive::package_vod`). Until then the live DASH stream of the recording stays published,
+// and it's removed by `remove_live_stream`; the live DASH stream of a preview is removed when the process ends.
 pub async fn stream(
-    url: String,
-    name: String,
-    state: &rocket::State<StreamMap>,
+    camera: &Camera,
+    session_id: Option<i64>,
+    state: &StreamMap,
 ) -> Result<&'static str, String> {
-    let dash_path = format!("./infrastructure/tmp/dash/{}", &name);
+    let name = camera_stream_name(camera.id);
+    let dash_path = format!("{}/{}", DASH_ROOT, &name);
+    let remove_at_exit = if session_id.is_some() { "0" } else { "1" };
 
-    // Create DASH output directory
+    let mut args: Vec<String> = vec![
+        "-i".to_string(),            // Input
+        rtsp_url(camera)?,           // Input RTSP URL
+        "-f".to_string(),            // Output
+        "dash".to_string(),          // DASH output format
+        "-seg_duration".to_string(), // Duration of every live segment
+        LIVE_SEGMENT_SECONDS.to_string(),
+        "-window_size".to_string(), // Segments listed in the manifest, that viewers can rewind to
+        live_window_size()?.to_string(),
+        "-extra_window_size".to_string(), // Segments kept on disk after leaving the manifest, for slow viewers
+        LIVE_EXTRA_WINDOW_SIZE.to_string(),
+        "-remove_at_exit".to_string(), // Remove files when process ends
+        remove_at_exit.to_string(),
+        format!("{}/manifest.mpd", dash_path), // DASH output location
+    ];
+
+    if let Some(session_id) = session_id {
+        let archive_dir = archive::segments_dir(session_id, camera.id);
+
+        // Create archive output directory
+        if let Err(e) = std::fs::create_dir_all(&archive_dir) {
+            return Err(format!(
+                "Failed to create directory {}: {}",
+                archive_dir.display(),
+                e
+            ));
+        }
+
+        args.extend(archive::segment_output_args(&archive_dir)); // Archive output
+    }
+
+    let mut streams = state.lock().await;
+
+    if let Some(camera_stream) = streams.get(&camera.id) {
+        let failed = camera_stream.stream.status().state == StreamState::Failed;
+        // A recording takes the place of the live preview of the camera
+        let replaced_preview = camera_stream.session_id.is_none() && session_id.is_some();
+
+        if !failed && !replaced_preview {
+            return Err(format!("Stream {} already started", name));
+        }
+        if let Some(camera_stream) = streams.remove(&camera.id) {
+            camera_stream.stream.stop().await;
+        }
+    }
+
+    // Replace the files left by a previous recording of the camera and create DASH output directory
+    let _ = std::fs::remove_dir_all(&dash_path);
     if let Err(e) = std::fs::create_dir_all(&dash_path) {
         return Err(format!("Failed to create directory {}: {}", dash_path, e));
     }
 
-    // Start the FFmpeg process
-    let ffmpeg_command = Command::new("ffmpeg")
-        .arg("-i") // Input
-        .arg(&url) // Input RTSP URL
-        .arg("-f") // Output
-        .arg("dash") // DASH output format
-        .arg("-remove_at_exit") // Remove files when process ends
-        .arg("1")
-        .arg(format!("{}/manifest.mpd", dash_path)) // DASH output location
-        .spawn();
-
-    // Save the FFmpeg process in the shared state + error management
-    match ffmpeg_command {
-        Ok(child) => {
-            let mut streams = state.lock().await;
-            streams.insert(url.clone(), child);
-            Ok("Stream started")
+    // Start the FFmpeg process and save it in the shared state
+    let stream = SupervisedStream::start(name, args)?;
+    streams.insert(camera.id, CameraStream { session_id, stream });
+
+    Ok("Stream started")
+}
+
+// Termination of the stream of a camera started with `stream`
+pub async fn stop(camera_id: i64, state: &StreamMap) -> Result<&'static str, String> {
+    let camera_stream = state.lock().await.remove(&camera_id);
+
+    match camera_stream {
+        Some(camera_stream) => {
+            // Wait for the process to be reaped
+            camera_stream.stream.stop().await;
+            Ok("Stream stopped")
+        }
+        None => Err(format!(
+            "Stream {} not found",
+            camera_stream_name(camera_id)
+        )),
+    }
+}
+
+// Termination of the stream of a camera, only if it's archiving the given recording session
+pub async fn stop_recording(
+    camera_id: i64,
+    session_id: i64,
+    state: &StreamMap,
+) -> Result<&'static str, String> {
+    let mut streams = state.lock().await;
+
+    match streams.get(&camera_id) {
+        Some(camera_stream) if camera_stream.session_id == Some(session_id) => {
+            if let Some(camera_stream) = streams.remove(&camera_id) {
+                // Wait for the process to be reaped
+                camera_stream.stream.stop().await;
+            }
+            Ok("Stream stopped")
         }
-        Err(e) => Err(format!("Failed to start FFmpeg: {}", e)),
+        _ => Err(format!(
+            "Stream {} is not recording session {}",
+            camera_stream_name(camera_id),
+            session_id
+        )),
     }
 }
 
-// Init for the cameras info
-pub async fn list_cameras() -> Result<Cams, &'static str> {
-    let cams: Cams = Arc::new(Mutex::new(Vec::new()));
-    let db_list = db_entities::camera::read::list_cameras().unwrap();
+// Removal of the live DASH stream left by a recording of the camera, once it has been published
+// on demand. Nothing is removed if the camera has started streaming again.
+pub async fn remove_live_stream(camera_id: i64, state: &StreamMap) -> Result<(), String> {
+    // The lock prevents a new stream from starting while the files are being removed
+    let streams = state.lock().await;
+
+    if streams.contains_key(&camera_id) {
+        return Ok(());
+    }
 
-    if db_list.is_empty() {
-        return Err("Couldn't load from database");
+    let dash_path = format!("{}/{}", DASH_ROOT, camera_stream_name(camera_id));
+    match std::fs::remove_dir_all(&dash_path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {}", dash_path, e)),
     }
+}
+
+// Streams in the shared state with their health
+pub async fn list_streams(state: &StreamMap) -> Vec<StreamInfo> {
+    let streams = state.lock().await;
 
+    streams
+        .iter()
+        .map(|(camera_id, camera_stream)| StreamInfo {
+            camera_id: *camera_id,
+            session_id: camera_stream.session_id,
+            status: camera_stream.stream.status(),
+        })
+        .collect()
+}
+
+// Name of the stream of a camera, used as the folder where it's published
+pub fn camera_stream_name(camera_id: i64) -> String {
+    format!("CAM{}", camera_id)
+}
+
+// URL of the DASH manifest of a camera, it doesn't change as long as the camera exists
+pub fn manifest_url(camera_id: i64) -> String {
+    format!(
+        "{}/{}/manifest.mpd",
+        DASH_URL_PREFIX,
+        camera_stream_name(camera_id)
+    )
+}
+
+// RTSP URL used by FFmpeg to connect to the camera
+pub fn rtsp_url(cam: &Camera) -> Result<String, String> {
     let rtsp_authentication = env::var("RTSP_AUTHENTICATION")
-        .expect("RTSP_AUTHENTICATION must be set.")
+        .map_err(|_| "RTSP_AUTHENTICATION must be set.".to_string())?
         .parse::<bool>()
-        .expect("RTSP_AUTHENTICATION must be true or false.");
-
-    let mut url;
-
-    for (index, cam) in db_list.iter().enumerate() {
-        if rtsp_authentication {
-            // With authentication
-            url = format!(
-                "rtsp://{}:{}@{}:{}/",
-                cam.username, cam.password, cam.ipv4_address, cam.port
-            );
-        } else {
-            // Without authentication (to be used then emulating the camera with VLC)
-            url = format!("rtsp://{}:{}/", cam.ipv4_address, cam.port);
-        }
-
-        let name = format!("CAM{}", index);
+        .map_err(|_| "RTSP_AUTHENTICATION must be true or false.".to_string())?;
 
-        let mut cams_lock = cams.lock().await;
-        cams_lock.push((name, url));
+    if rtsp_authentication {
+        // With authentication, the password is stored encrypted
+        let password = secrets::decrypt(&cam.password)?;
+        Ok(format!(
+            "rtsp://{}:{}@{}:{}/",
+            cam.username, password, cam.ipv4_address, cam.port
+        ))
+    } else {
+        // Without authentication (to be used then emulating the camera with VLC)
+        Ok(format!("rtsp://{}:{}/", cam.ipv4_address, cam.port))
     }
+}
+
+// Number of segments of the live manifest, to cover the DVR window set in the environment.
+// With a window of 0 seconds the manifest lists the whole stream.
+fn live_window_size() -> Result<u64, String> {
+    let window_seconds = match env::var("LIVE_DVR_WINDOW_SECONDS") {
+        Ok(value) => value
+            .parse::<u64>()
+            .map_err(|_| "LIVE_DVR_WINDOW_SECONDS must be a number.".to_string())?,
+        Err(_) => DEFAULT_DVR_WINDOW_SECONDS,
+    };
 
-    Ok(cams)
+    Ok(window_seconds.div_ceil(LIVE_SEGMENT_SECONDS))
 }
@@ -0,0 +1,648 @@
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use domain::models::{
+    full_tables::Camera,
+    others::{CameraProbe, CameraTrack},
+};
+use md5::{Digest, Md5};
+use rocket::{http::Status, tokio::task};
+use shared::response_models::{ApiError, ApiErrorType};
+
+use crate::{
+    authentication::Claims, authorization::person_checks::is_administrator,
+    db_entities::camera::read::find_camera, secrets,
+};
+
+/// Maximum wait for the connection and for every response of the camera
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const USER_AGENT: &str = "Sanbapolis";
+
+pub async fn authorize_probe_camera(
+    requesting_user: Claims,
+    camera_id: i64,
+) -> Result<CameraProbe, ApiError> {
+    if is_administrator(requesting_user.subject_id)? {
+        return probe_camera(camera_id).await;
+    } else {
+        return Err(ApiError {
+            http_status: Status::Forbidden,
+            error_code: 123, // TODO organizzare i codici di errore
+            error_type: ApiErrorType::AuthorizationError,
+            message: format!(
+                "Error - User {} is not authorized to probe camera {}",
+                requesting_user.subject_id, camera_id
+            ),
+        });
+    }
+}
+
+// Checks that the camera answers to an RTSP OPTIONS/DESCRIBE handshake.
+//
+// An unreachable camera is not an error: the outcome of the check is reported in the result.
+pub async fn probe_camera(camera_id: i64) -> Result<CameraProbe, ApiError> {
+    let camera = find_camera(camera_id)?;
+    let authenticate = rtsp_authentication();
+
+    // The handshake uses blocking sockets, so it's done outside of the async runtime
+    match task::spawn_blocking(move || probe(&camera, authenticate)).await {
+        Ok(camera_probe) => return Ok(camera_probe),
+        Err(e) => {
+            return Err(ApiError {
+                http_status: Status::InternalServerError,
+                error_code: 123, // TODO organizzare i codici di errore
+                error_type: ApiErrorType::ApplicationError,
+                message: format!("Error while probing camera {} - {}", camera_id, e),
+            });
+        }
+    }
+}
+
+// Response of the camera to an RTSP request
+struct RtspResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl RtspResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// With `authenticate` the credentials of the camera are sent if it asks for them
+fn probe(camera: &Camera, authenticate: bool) -> CameraProbe {
+    let url = format!("rtsp://{}:{}/", camera.ipv4_address, camera.port);
+
+    let mut camera_probe = CameraProbe {
+        camera_id: camera.id,
+        url: url.clone(),
+        reachable: false,
+        latency_ms: None,
+        methods: Vec::new(),
+        describe_status: None,
+        tracks: Vec::new(),
+        error: None,
+    };
+
+    if let Err(e) = handshake(camera, &url, authenticate, &mut camera_probe) {
+        camera_probe.error = Some(e);
+    }
+
+    camera_probe
+}
+
+// Sends OPTIONS and DESCRIBE, filling the result as the answers arrive
+fn handshake(
+    camera: &Camera,
+    url: &str,
+    authenticate: bool,
+    camera_probe: &mut CameraProbe,
+) -> Result<(), String> {
+    let address: SocketAddr = format!("{}:{}", camera.ipv4_address, camera.port)
+        .parse()
+        .map_err(|e| format!("Invalid camera address: {}", e))?;
+
+    let stream = TcpStream::connect_timeout(&address, PROBE_TIMEOUT)
+        .map_err(|e| format!("Failed to connect to {}: {}", address, e))?;
+    stream
+        .set_read_timeout(Some(PROBE_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(PROBE_TIMEOUT)))
+        .map_err(|e| format!("Failed to configure the connection: {}", e))?;
+
+    let mut reader = BufReader::new(
+        stream
+            .try_clone()
+            .map_err(|e| format!("Failed to configure the connection: {}", e))?,
+    );
+    let mut writer = stream;
+
+    // OPTIONS
+    let sent_at = Instant::now();
+    let options = request(&mut writer, &mut reader, "OPTIONS", url, 1, &[])?;
+    camera_probe.latency_ms = Some(sent_at.elapsed().as_millis() as u64);
+    camera_probe.reachable = true;
+
+    if let Some(public) = options.header("Public") {
+        camera_probe.methods = public
+            .split(',')
+            .map(|method| method.trim().to_string())
+            .filter(|method| !method.is_empty())
+            .collect();
+    }
+
+    // DESCRIBE, repeated with the credentials if the camera asks for them
+    let accept = [("Accept", "application/sdp".to_string())];
+    let mut describe = request(&mut writer, &mut reader, "DESCRIBE", url, 2, &accept)?;
+
+    if describe.status == 401 && authenticate {
+        if let Some(challenge) = describe.header("WWW-Authenticate") {
+            let authorization = authorization_header(camera, "DESCRIBE", url, challenge)?;
+            let headers = [accept[0].clone(), ("Authorization", authorization)];
+            describe = request(&mut writer, &mut reader, "DESCRIBE", url, 3, &headers)?;
+        }
+    }
+
+    camera_probe.describe_status = Some(describe.status);
+
+    if describe.status == 200 {
+        camera_probe.tracks = parse_tracks(&describe.body);
+    } else {
+        return Err(format!("DESCRIBE answered with status {}", describe.status));
+    }
+
+    Ok(())
+}
+
+fn request(
+    writer: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    method: &str,
+    url: &str,
+    cseq: u32,
+    headers: &[(&str, String)],
+) -> Result<RtspResponse, String> {
+    let mut message = format!(
+        "{} {} RTSP/1.0\r\nCSeq: {}\r\nUser-Agent: {}\r\n",
+        method, url, cseq, USER_AGENT
+    );
+    for (name, value) in headers {
+        message.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    message.push_str("\r\n");
+
+    writer
+        .write_all(message.as_bytes())
+        .map_err(|e| format!("Failed to send {}: {}", method, e))?;
+
+    read_response(reader).map_err(|e| format!("Invalid answer to {}: {}", method, e))
+}
+
+fn read_response(reader: &mut BufReader<TcpStream>) -> Result<RtspResponse, String> {
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|e| e.to_string())?;
+
+    // RTSP/1.0 200 OK
+    let status = status_line
+        .strip_prefix("RTSP/")
+        .and_then(|rest| rest.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("not an RTSP response: {}", status_line.trim()))?;
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    Ok(RtspResponse {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+// Value of the Authorization header for the Basic and Digest challenges
+fn authorization_header(
+    camera: &Camera,
+    method: &str,
+    url: &str,
+    challenge: &str,
+) -> Result<String, String> {
+    // The password is stored encrypted
+    let password = secrets::decrypt(&camera.password)?;
+
+    if challenge.starts_with("Basic") {
+        let credentials = format!("{}:{}", camera.username, password);
+        return Ok(format!("Basic {}", STANDARD.encode(credentials)));
+    }
+
+    if let Some(params) = challenge.strip_prefix("Digest") {
+        return digest_authorization(&camera.username, &password, method, url, params, &cnonce());
+    }
+
+    Err(format!("Unsupported authentication: {}", challenge))
+}
+
+// Value of the Authorization header for a Digest challenge (RFC 2617).
+//
+// When the camera offers the `auth` quality of protection, the response also covers the
+// client nonce `cnonce` and the request counter, which is always 1 since every request
+// of the probe answers a new challenge.
+fn digest_authorization(
+    username: &str,
+    password: &str,
+    method: &str,
+    url: &str,
+    params: &str,
+    cnonce: &str,
+) -> Result<String, String> {
+    let realm = digest_param(params, "realm").unwrap_or_default();
+    let nonce = digest_param(params, "nonce").ok_or("Digest challenge without nonce")?;
+
+    if let Some(algorithm) = digest_param(params, "algorithm") {
+        if !algorithm.eq_ignore_ascii_case("MD5") {
+            return Err(format!("Unsupported Digest algorithm: {}", algorithm));
+        }
+    }
+
+    let ha1 = md5_hex(&format!("{}:{}:{}", username, realm, password));
+    let ha2 = md5_hex(&format!("{}:{}", method, url));
+
+    let mut authorization = format!(
+        "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\"",
+        username, realm, nonce, url
+    );
+
+    match digest_param(params, "qop") {
+        Some(qop) => {
+            if !qop.split(',').any(|option| option.trim() == "auth") {
+                return Err(format!("Unsupported Digest quality of protection: {}", qop));
+            }
+
+            let nc = "00000001";
+            let response = md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, nonce, nc, cnonce, ha2));
+            authorization.push_str(&format!(
+                ", response=\"{}\", qop=auth, nc={}, cnonce=\"{}\"",
+                response, nc, cnonce
+            ));
+        }
+        None => {
+            let response = md5_hex(&format!("{}:{}:{}", ha1, nonce, ha2));
+            authorization.push_str(&format!(", response=\"{}\"", response));
+        }
+    }
+
+    // The opaque value must be sent back as it is
+    if let Some(opaque) = digest_param(params, "opaque") {
+        authorization.push_str(&format!(", opaque=\"{}\"", opaque));
+    }
+
+    Ok(authorization)
+}
+
+// Value of a parameter of a Digest challenge, like realm="camera".
+//
+// Quoted values can contain commas, like qop="auth,auth-int".
+fn digest_param(params: &str, name: &str) -> Option<String> {
+    let mut rest = params.trim();
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim();
+        let value = value.trim_start();
+
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, remaining)) => (value, remaining),
+                None => (quoted, ""),
+            },
+            None => match value.split_once(',') {
+                Some((value, remaining)) => (value.trim(), remaining),
+                None => (value.trim(), ""),
+            },
+        };
+
+        if key.eq_ignore_ascii_case(name) {
+            return Some(value.to_string());
+        }
+
+        rest = remaining.trim_start().trim_start_matches(',');
+    }
+
+    None
+}
+
+// Random client nonce of the Digest authentication
+fn cnonce() -> String {
+    format!("{:016x}", OsRng.next_u64())
+}
+
+fn md5_hex(value: &str) -> String {
+    Md5::digest(value.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Tracks described by the SDP returned by DESCRIBE
+fn parse_tracks(sdp: &str) -> Vec<CameraTrack> {
+    let mut tracks: Vec<CameraTrack> = Vec::new();
+
+    for line in sdp.lines().map(str::trim) {
+        if let Some(media) = line.strip_prefix("m=") {
+            // m=video 0 RTP/AVP 96
+            tracks.push(CameraTrack {
+                media: media
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                codec: None,
+                control: None,
+            });
+        } else if let Some(track) = tracks.last_mut() {
+            if let Some(rtpmap) = line.strip_prefix("a=rtpmap:") {
+                // a=rtpmap:96 H264/90000
+                track.codec = rtpmap
+                    .split_whitespace()
+                    .nth(1)
+                    .and_then(|encoding| encoding.split('/').next())
+                    .map(|codec| codec.to_string());
+            } else if let Some(control) = line.strip_prefix("a=control:") {
+                track.control = Some(control.to_string());
+            }
+        }
+    }
+
+    tracks
+}
+
+fn rtsp_authentication() -> bool {
+    env::var("RTSP_AUTHENTICATION")
+        .expect("RTSP_AUTHENTICATION must be set.")
+        .parse::<bool>()
+        .expect("RTSP_AUTHENTICATION must be true or false.")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::thread;
+
+    use super::*;
+
+    const USERNAME: &str = "admin";
+    const PASSWORD: &str = "secret";
+    const REALM: &str = "camera";
+    const NONCE: &str = "3d4f8a9b";
+
+    const SDP: &str = "v=0\r\n\
+        o=- 0 0 IN IP4 127.0.0.1\r\n\
+        s=Camera\r\n\
+        m=video 0 RTP/AVP 96\r\n\
+        a=rtpmap:96 H264/90000\r\n\
+        a=control:trackID=1\r\n\
+        m=audio 0 RTP/AVP 97\r\n\
+        a=rtpmap:97 MPEG4-GENERIC/48000/2\r\n\
+        a=control:trackID=2\r\n";
+
+    // Local RTSP server that answers like a camera asking for Digest authentication with `qop`,
+    // for a single connection
+    fn start_camera() -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+
+            loop {
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    return;
+                }
+
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_once(':').unwrap();
+                    headers.push((name.trim().to_string(), value.trim().to_string()));
+                }
+
+                let header = |name: &str| {
+                    headers
+                        .iter()
+                        .find(|(header, _)| header == name)
+                        .map(|(_, value)| value.clone())
+                };
+                let cseq = header("CSeq").unwrap();
+                let mut parts = request_line.split_whitespace();
+                let (method, url) = (parts.next().unwrap(), parts.next().unwrap());
+
+                let response = match (method, header("Authorization")) {
+                    ("OPTIONS", _) => format!(
+                        "RTSP/1.0 200 OK\r\nCSeq: {}\r\nPublic: OPTIONS, DESCRIBE, SETUP, PLAY, TEARDOWN\r\n\r\n",
+                        cseq
+                    ),
+                    ("DESCRIBE", Some(authorization)) if valid_authorization(&authorization, method, url) => format!(
+                        "RTSP/1.0 200 OK\r\nCSeq: {}\r\nContent-Type: application/sdp\r\nContent-Length: {}\r\n\r\n{}",
+                        cseq,
+                        SDP.len(),
+                        SDP
+                    ),
+                    ("DESCRIBE", _) => format!(
+                        "RTSP/1.0 401 Unauthorized\r\nCSeq: {}\r\nWWW-Authenticate: Digest realm=\"{}\", nonce=\"{}\", qop=\"auth,auth-int\", opaque=\"5ccc\"\r\n\r\n",
+                        cseq, REALM, NONCE
+                    ),
+                    _ => format!("RTSP/1.0 501 Not Implemented\r\nCSeq: {}\r\n\r\n", cseq),
+                };
+
+                writer.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        port
+    }
+
+    // Checks the Digest response like the camera would
+    fn valid_authorization(authorization: &str, method: &str, url: &str) -> bool {
+        let Some(params) = authorization.strip_prefix("Digest") else {
+            return false;
+        };
+        let param = |name| digest_param(params, name).unwrap_or_default();
+
+        let ha1 = md5_hex(&format!("{}:{}:{}", USERNAME, REALM, PASSWORD));
+        let ha2 = md5_hex(&format!("{}:{}", method, url));
+        let expected = md5_hex(&format!(
+            "{}:{}:{}:{}:{}:{}",
+            ha1,
+            NONCE,
+            param("nc"),
+            param("cnonce"),
+            param("qop"),
+            ha2
+        ));
+
+        param("username") == USERNAME
+            && param("qop") == "auth"
+            && param("opaque") == "5ccc"
+            && param("response") == expected
+    }
+
+    fn camera(port: u16) -> Camera {
+        Camera {
+            id: 1,
+            ipv4_address: Ipv4Addr::LOCALHOST.into(),
+            ipv6_address: None,
+            port,
+            username: USERNAME.to_string(),
+            // Values stored before the encryption are used as they are
+            password: PASSWORD.to_string(),
+            name: "Test camera".to_string(),
+            location_description: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn probe_authenticates_with_digest_qop() {
+        let port = start_camera();
+
+        let camera_probe = probe(&camera(port), true);
+
+        assert_eq!(camera_probe.error, None);
+        assert!(camera_probe.reachable);
+        assert!(camera_probe.latency_ms.is_some());
+        assert_eq!(
+            camera_probe.methods,
+            vec!["OPTIONS", "DESCRIBE", "SETUP", "PLAY", "TEARDOWN"]
+        );
+        assert_eq!(camera_probe.describe_status, Some(200));
+        assert_eq!(camera_probe.tracks.len(), 2);
+    }
+
+    #[test]
+    fn probe_reports_missing_credentials() {
+        let port = start_camera();
+
+        let camera_probe = probe(&camera(port), false);
+
+        assert!(camera_probe.reachable);
+        assert_eq!(camera_probe.describe_status, Some(401));
+        assert!(camera_probe.tracks.is_empty());
+        assert!(camera_probe.error.is_some());
+    }
+
+    #[test]
+    fn probe_reports_unreachable_camera() {
+        // The port is free once the listener is dropped
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let camera_probe = probe(&camera(port), true);
+
+        assert!(!camera_probe.reachable);
+        assert_eq!(camera_probe.latency_ms, None);
+        assert!(camera_probe.error.is_some());
+    }
+
+    #[test]
+    fn digest_authorization_matches_rfc_2617() {
+        // Example of section 3.5 of RFC 2617
+        let params = " realm=\"testrealm@host.com\", qop=\"auth,auth-int\", \
+            nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"";
+
+        let authorization = digest_authorization(
+            "Mufasa",
+            "Circle Of Life",
+            "GET",
+            "/dir/index.html",
+            params,
+            "0a4f113b",
+        )
+        .unwrap();
+
+        assert_eq!(
+            digest_param(&authorization["Digest".len()..], "response").as_deref(),
+            Some("6629fae49393a05397450978507c4ef1")
+        );
+        assert!(authorization.contains("qop=auth, nc=00000001, cnonce=\"0a4f113b\""));
+        assert!(authorization.contains("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""));
+    }
+
+    #[test]
+    fn digest_authorization_without_qop() {
+        let params = "realm=\"camera\", nonce=\"abc\"";
+
+        let authorization =
+            digest_authorization("admin", "secret", "DESCRIBE", "rtsp://camera/", params, "x")
+                .unwrap();
+
+        let ha1 = md5_hex("admin:camera:secret");
+        let ha2 = md5_hex("DESCRIBE:rtsp://camera/");
+        let expected = md5_hex(&format!("{}:abc:{}", ha1, ha2));
+
+        assert_eq!(
+            digest_param(&authorization["Digest".len()..], "response"),
+            Some(expected)
+        );
+        assert!(!authorization.contains("qop"));
+    }
+
+    #[test]
+    fn digest_authorization_rejects_unsupported_qop() {
+        let params = "realm=\"camera\", nonce=\"abc\", qop=\"auth-int\"";
+
+        assert!(digest_authorization("a", "b", "DESCRIBE", "rtsp://c/", params, "x").is_err());
+    }
+
+    #[test]
+    fn digest_param_handles_quotes_and_commas() {
+        let params = " realm=\"my, camera\", nonce=abc123, qop=\"auth,auth-int\",stale=FALSE";
+
+        assert_eq!(digest_param(params, "realm").as_deref(), Some("my, camera"));
+        assert_eq!(digest_param(params, "nonce").as_deref(), Some("abc123"));
+        assert_eq!(
+            digest_param(params, "QOP").as_deref(),
+            Some("auth,auth-int")
+        );
+        assert_eq!(digest_param(params, "stale").as_deref(), Some("FALSE"));
+        assert_eq!(digest_param(params, "opaque"), None);
+    }
+
+    #[test]
+    fn parse_tracks_reads_codecs_and_controls() {
+        let tracks = parse_tracks(SDP);
+
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].media, "video");
+        assert_eq!(tracks[0].codec.as_deref(), Some("H264"));
+        assert_eq!(tracks[0].control.as_deref(), Some("trackID=1"));
+        assert_eq!(tracks[1].media, "audio");
+        assert_eq!(tracks[1].codec.as_deref(), Some("MPEG4-GENERIC"));
+        assert_eq!(tracks[1].control.as_deref(), Some("trackID=2"));
+    }
+
+    #[test]
+    fn parse_tracks_ignores_attributes_before_the_first_track() {
+        let tracks = parse_tracks("v=0\na=control:*\na=rtpmap:96 H264/90000\n");
+
+        assert!(tracks.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDateTime};
+use domain::models::{
+    full_tables::Camera, insertions::NewVideo, others::RecordingSessionWithCameras,
+};
+use log::{error, info, warn};
+use rocket::tokio::{task, time};
+
+use crate::db_entities::{
+    recording_session::read::{
+        find_recording_session, list_recording_sessions_in_progress, next_recording_session_start,
+    },
+    video::{
+        create::create_video, metadata::probe_new_video_file, thumbnails::create_thumbnails,
+        vod::create_vod,
+    },
+};
+use crate::player::{
+    archive,
+    blocking::run_blocking,
+    player::{self, StreamMap},
+};
+
+/// Seconds between two checks of the recording sessions, if not set in the environment
+const DEFAULT_INTERVAL_SECONDS: u64 = 10;
+
+// Recording session whose cameras are being captured
+struct ActiveSession {
+    end_datetime: NaiveDateTime,
+    camera_ids: HashSet<i64>,
+}
+
+// Background task that starts and stops the capture of the cameras linked to each recording session.
+//
+// The recording sessions in the database are checked periodically, and also at the start and end
+// time of each session, so that sessions shorter than the interval between two checks are recorded
+// too. When a session starts the capture of its cameras is started, when it ends (or is deleted)
+// the capture is stopped and a video is registered for every camera. Changes to the cameras of a
+// session in progress are applied at the next check.
+pub async fn run(state: StreamMap) {
+    let interval = match interval_seconds() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(e) => {
+            error!("Recording scheduler: not started - {}", e);
+            return;
+        }
+    };
+
+    let mut active_sessions = recover_sessions(&state).await;
+
+    loop {
+        let now = Local::now().naive_local();
+
+        match list_recording_sessions_in_progress(now) {
+            Ok(sessions) => update_sessions(&sessions, &mut active_sessions, &state).await,
+            Err(e) => error!(
+                "Recording scheduler: error loading sessions - {}",
+                e.message
+            ),
+        }
+
+        time::sleep(next_check(now, interval, &active_sessions)).await;
+    }
+}
+
+// Captures left by a previous run of the scheduler.
+//
+// The recordings still running in the shared state are finished like the ones started by the
+// scheduler. The archive segments of the captures interrupted by a restart of the server are joined
+// and registered if their session has ended, while if the session is still in progress the capture
+// is started again at the first check and the new segments are added to the previous ones.
+async fn recover_sessions(state: &StreamMap) -> HashMap<i64, ActiveSession> {
+    let now = Local::now().naive_local();
+    let mut active_sessions: HashMap<i64, ActiveSession> = HashMap::new();
+
+    // The end of the session is updated at the first check, or the capture is finished if it has ended
+    for stream in player::list_streams(state).await {
+        if let Some(session_id) = stream.session_id {
+            active_sessions
+                .entry(session_id)
+                .or_insert_with(|| ActiveSession {
+                    end_datetime: now,
+                    camera_ids: HashSet::new(),
+                })
+                .camera_ids
+                .insert(stream.camera_id);
+        }
+    }
+
+    // Without the sessions in progress the segments could be joined while they are still being written
+    let sessions = match list_recording_sessions_in_progress(now) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            error!(
+                "Recording scheduler: interrupted captures not recovered, error loading sessions - {}",
+                e.message
+            );
+            return active_sessions;
+        }
+    };
+
+    let unfinished_captures = task::spawn_blocking(archive::unfinished_captures)
+        .await
+        .unwrap_or_default();
+
+    for (session_id, camera_id) in unfinished_captures {
+        let running = active_sessions
+            .get(&session_id)
+            .is_some_and(|s| s.camera_ids.contains(&camera_id));
+        let in_progress = sessions
+            .iter()
+            .any(|s| s.recording_session.id == session_id);
+
+        if running || in_progress {
+            continue;
+        }
+
+        // A video can't be registered for a deleted session, the segments are kept to be recovered by hand
+        if let Err(e) = find_recording_session(session_id) {
+            warn!(
+                "Recording scheduler: interrupted capture of camera {} for session {} not recovered - {}",
+                camera_id, session_id, e.message
+            );
+            continue;
+        }
+
+        info!(
+            "Recording scheduler: recovering the interrupted capture of camera {} for session {}",
+            camera_id, session_id
+        );
+        task::spawn(register_recording(session_id, camera_id, state.clone()));
+    }
+
+    active_sessions
+}
+
+// Starts the sessions in progress, applies the changes to their cameras and finishes the ones that ended
+async fn update_sessions(
+    sessions: &[RecordingSessionWithCameras],
+    active_sessions: &mut HashMap<i64, ActiveSession>,
+    state: &StreamMap,
+) {
+    for session in sessions {
+        let active_session = active_sessions
+            .entry(session.recording_session.id)
+            .or_insert_with(|| ActiveSession {
+                end_datetime: session.recording_session.end_datetime,
+                camera_ids: HashSet::new(),
+            });
+        active_session.end_datetime = session.recording_session.end_datetime;
+
+        sync_session(session, active_session, state).await;
+    }
+
+    // Sessions that are no longer in progress are finished
+    let ended_sessions: Vec<i64> = active_sessions
+        .keys()
+        .filter(|id| !sessions.iter().any(|s| s.recording_session.id == **id))
+        .cloned()
+        .collect();
+
+    for session_id in ended_sessions {
+        if let Some(active_session) = active_sessions.remove(&session_id) {
+            info!("Recording scheduler: session {} ended", session_id);
+
+            for camera_id in active_session.camera_ids {
+                finish_capture(session_id, camera_id, state).await;
+            }
+        }
+    }
+}
+
+// Time to wait before the next check: the interval, unless a session starts or ends before it
+fn next_check(
+    now: NaiveDateTime,
+    interval: Duration,
+    active_sessions: &HashMap<i64, ActiveSession>,
+) -> Duration {
+    let next_start = match next_recording_session_start(now) {
+        Ok(next_start) => next_start,
+        Err(e) => {
+            error!(
+                "Recording scheduler: error loading the next session - {}",
+                e.message
+            );
+            None
+        }
+    };
+
+    active_sessions
+        .values()
+        .map(|s| s.end_datetime)
+        .chain(next_start)
+        .filter_map(|instant| (instant - now).to_std().ok())
+        .fold(interval, Duration::min)
+}
+
+// Starts the capture of the cameras added to the session and stops the ones removed from it
+async fn sync_session(
+    session: &RecordingSessionWithCameras,
+    active_session: &mut ActiveSession,
+    state: &StreamMap,
+) {
+    let session_id = session.recording_session.id;
+
+    // Disabled cameras are not recorded
+    for camera in session.cameras.iter().filter(|c| c.enabled) {
+        if !active_session.camera_ids.contains(&camera.id)
+            && start_capture(session_id, camera, state).await
+        {
+            active_session.camera_ids.insert(camera.id);
+        }
+    }
+
+    let removed_cameras: Vec<i64> = active_session
+        .camera_ids
+        .iter()
+        .filter(|id| !session.cameras.iter().any(|c| c.id == **id && c.enabled))
+        .cloned()
+        .collect();
+
+    for camera_id in removed_cameras {
+        active_session.camera_ids.remove(&camera_id);
+        finish_capture(session_id, camera_id, state).await;
+    }
+}
+
+// Starts the capture of the camera and returns whether it was started
+async fn start_capture(session_id: i64, camera: &Camera, state: &StreamMap) -> bool {
+    match player::stream(camera, Some(session_id), state).await {
+        Ok(_) => {
+            info!(
+                "Recording scheduler: started camera {} for session {}",
+                camera.id, session_id
+            );
+            true
+        }
+        Err(e) => {
+            error!(
+                "Recording scheduler: error starting camera {} for session {} - {}",
+                camera.id, session_id, e
+            );
+            false
+        }
+    }
+}
+
+// Stops the capture and registers the recorded video.
+//
+// Joining the segments and publishing the video can take minutes, so they are run in their own task
+// and the scheduler goes on starting and stopping the other captures on time.
+async fn finish_capture(session_id: i64, camera_id: i64, state: &StreamMap) {
+    if let Err(e) = player::stop_recording(camera_id, session_id, state).await {
+        error!(
+            "Recording scheduler: error stopping camera {} for session {} - {}",
+            camera_id, session_id, e
+        );
+    }
+
+    task::spawn(register_recording(session_id, camera_id, state.clone()));
+}
+
+// Joins the archive segments of a capture, registers the recorded video and publishes it on demand
+async fn register_recording(session_id: i64, camera_id: i64, state: StreamMap) {
+    // The video starts with the first segment, even if the capture was restarted
+    let capture_start = archive::capture_start(session_id, camera_id);
+    let started_at = capture_start.unwrap_or_else(|| Local::now().naive_local());
+
+    let finalized = run_blocking(move || archive::finalize(session_id, camera_id)).await;
+
+    let file_location = match finalized {
+        Ok(key) => key,
+        Err(e) => {
+            // The segments or the joined file are kept, so the recording can be recovered by hand
+            error!(
+                "Recording scheduler: error archiving camera {} for session {} - {}",
+                camera_id, session_id, e
+            );
+            return;
+        }
+    };
+
+    let metadata = probe_new_video_file(&file_location).await;
+
+    let new_video = NewVideo {
+        file_location,
+        name: format!("Sessione {} - Telecamera {}", session_id, camera_id),
+        date: started_at,
+        capture_start,
+        notes: None,
+        session_id,
+        camera_id,
+        metadata,
+    };
+
+    let video = match create_video(new_video) {
+        Ok(video) => {
+            info!(
+                "Recording scheduler: registered video {} for camera {} of session {}",
+                video.id, camera_id, session_id
+            );
+            video
+        }
+        Err(e) => {
+            error!(
+                "Recording scheduler: error registering video for camera {} of session {} - {}",
+                camera_id, session_id, e.message
+            );
+            return;
+        }
+    };
+
+    // The live stream has ended, the recording takes its place as an on demand stream
+    match create_vod(video.id).await {
+        Ok(_) => {
+            if let Err(e) = player::remove_live_stream(camera_id, &state).await {
+                warn!(
+                    "Recording scheduler: error removing the live stream of camera {} - {}",
+                    camera_id, e
+                );
+            }
+        }
+        Err(e) => warn!(
+            "Recording scheduler: error publishing video {} on demand - {}",
+            video.id, e.message
+        ),
+    }
+
+    // The video is usable without thumbnails, they can be generated again later
+    if let Err(e) = create_thumbnails(video.id).await {
+        warn!(
+            "Recording scheduler: error generating the thumbnails of video {} - {}",
+            video.id, e.message
+        );
+    }
+}
+
+fn interval_seconds() -> Result<u64, String> {
+    match env::var("RECORDING_SCHEDULER_INTERVAL_SECONDS") {
+        Ok(value) => value
+            .parse::<u64>()
+            .map_err(|_| "RECORDING_SCHEDULER_INTERVAL_SECONDS must be a number.".to_string()),
+        Err(_) => Ok(DEFAULT_INTERVAL_SECONDS),
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDateTime};
+use domain::models::others::StreamState;
+use log::{error, info, warn};
+use rocket::tokio::{task::JoinHandle, time};
+
+/// Seconds a process has to stay alive before being considered running
+const STARTUP_SECONDS: u64 = 5;
+/// Seconds a process has to stay alive before its previous failures are forgotten
+const STABLE_SECONDS: u64 = 60;
+/// Maximum wait in seconds between two restarts
+const MAX_BACKOFF_SECONDS: u64 = 60;
+/// Consecutive restarts allowed before giving up, if not set in the environment
+const DEFAULT_MAX_RETRIES: u32 = 20;
+/// Number of stderr lines kept for every stream
+const STDERR_TAIL_LINES: usize = 20;
+/// Interval between two checks of the process
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Health of a supervised FFmpeg process
+#[derive(Clone, Debug)]
+pub struct StreamStatus {
+    pub state: StreamState,
+    /// PID of the current process, if any
+    pub pid: Option<u32>,
+    /// When the stream was requested
+    pub started_at: NaiveDateTime,
+    /// When the current process has been started
+    pub process_started_at: Option<NaiveDateTime>,
+    /// Number of times the process has been restarted
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// Last lines written by FFmpeg on stderr
+    pub stderr_tail: VecDeque<String>,
+}
+
+// FFmpeg process that is restarted with an exponential backoff when it ends unexpectedly
+//
+// The process is owned by a background task that reaps it when it exits, so that a crashed
+// FFmpeg or a camera that drops the connection never leaves a stale process behind.
+pub struct SupervisedStream {
+    status: Arc<Mutex<StreamStatus>>,
+    stop: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+}
+
+impl SupervisedStream {
+    // Starts FFmpeg with the given arguments: only the first start is reported to the caller,
+    // the following ones are handled by the supervisor
+    pub fn start(name: String, args: Vec<String>) -> Result<SupervisedStream, String> {
+        let now = Local::now().naive_local();
+        let status = Arc::new(Mutex::new(StreamStatus {
+            state: StreamState::Starting,
+            pid: None,
+            started_at: now,
+            process_started_at: None,
+            restarts: 0,
+            last_error: None,
+            stderr_tail: VecDeque::new(),
+        }));
+
+        // Checked before starting FFmpeg, so that a bad value never leaves a process behind
+        let max_retries = max_retries()?;
+
+        let child = spawn(&args, &status)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let task = rocket::tokio::spawn(supervise(
+            name,
+            args,
+            max_retries,
+            child,
+            status.clone(),
+            stop.clone(),
+        ));
+
+        Ok(SupervisedStream { status, stop, task })
+    }
+
+    pub fn status(&self) -> StreamStatus {
+        return lock(&self.status).clone();
+    }
+
+    // Kills the process and waits until it has been reaped
+    pub async fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+
+        if let Err(e) = self.task.await {
+            error!("Stream supervisor: task ended abnormally - {}", e);
+        }
+    }
+}
+
+// Watches the process, restarting it until it's stopped or it fails too many times in a row
+async fn supervise(
+    name: String,
+    args: Vec<String>,
+    max_retries: u32,
+    mut child: Child,
+    status: Arc<Mutex<StreamStatus>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut failures: u32 = 0;
+
+    loop {
+        let spawned_at = Instant::now();
+
+        // Wait for the process to end
+        let exit_error = loop {
+            if stop.load(Ordering::SeqCst) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return;
+            }
+
+            match child.try_wait() {
+                Ok(Some(exit_status)) => break format!("FFmpeg exited with {}", exit_status),
+                Ok(None) => {
+                    if spawned_at.elapsed() >= Duration::from_secs(STARTUP_SECONDS) {
+                        let mut status = lock(&status);
+                        if status.state == StreamState::Starting {
+                            status.state = StreamState::Running;
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break format!("Failed to check FFmpeg process: {}", e);
+                }
+            }
+
+            time::sleep(POLL_INTERVAL).await;
+        };
+
+        // A process that ran for a while counts as a new glitch, not as one more failure
+        if spawned_at.elapsed() >= Duration::from_secs(STABLE_SECONDS) {
+            failures = 0;
+        }
+        failures += 1;
+
+        if failures > max_retries {
+            error!(
+                "Stream supervisor: {} failed after {} restarts - {}",
+                name, max_retries, exit_error
+            );
+            log_stderr_tail(&name, &status);
+            set_failed(&status, exit_error);
+            return;
+        }
+
+        let delay = backoff(failures);
+        warn!(
+            "Stream supervisor: {} ended ({}), restarting in {} seconds",
+            name,
+            exit_error,
+            delay.as_secs()
+        );
+        log_stderr_tail(&name, &status);
+
+        {
+            let mut status = lock(&status);
+            status.state = StreamState::Retrying;
+            status.pid = None;
+            status.last_error = Some(exit_error);
+        }
+
+        // Wait before restarting, unless the stream is stopped in the meantime
+        let restart_at = Instant::now() + delay;
+        while Instant::now() < restart_at {
+            if stop.load(Ordering::SeqCst) {
+                return;
+            }
+            time::sleep(POLL_INTERVAL).await;
+        }
+
+        child = match spawn(&args, &status) {
+            Ok(child) => child,
+            Err(e) => {
+                error!("Stream supervisor: {} can't be restarted - {}", name, e);
+                log_stderr_tail(&name, &status);
+                set_failed(&status, e);
+                return;
+            }
+        };
+
+        lock(&status).restarts += 1;
+        info!("Stream supervisor: {} restarted", name);
+    }
+}
+
+// Starts FFmpeg, collecting the last lines written on stderr in the status
+fn spawn(args: &[String], status: &Arc<Mutex<StreamStatus>>) -> Result<Child, String> {
+    let mut child = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-nostats") // No progress report, only the messages are kept
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if let Some(stderr) = child.stderr.take() {
+        let status = status.clone();
+
+        // The reader ends by itself when the process exits.
+        // FFmpeg repeats the input URL in its messages, so the camera credentials are removed from every line.
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                let mut status = lock(&status);
+                if status.stderr_tail.len() == STDERR_TAIL_LINES {
+                    status.stderr_tail.pop_front();
+                }
+                status.stderr_tail.push_back(remove_credentials(&line));
+            }
+        });
+    }
+
+    let mut status = lock(status);
+    status.state = StreamState::Starting;
+    status.pid = Some(child.id());
+    status.process_started_at = Some(Local::now().naive_local());
+
+    Ok(child)
+}
+
+// Logs the last lines written by FFmpeg, which usually explain why the process ended
+fn log_stderr_tail(name: &str, status: &Arc<Mutex<StreamStatus>>) {
+    let status = lock(status);
+
+    if !status.stderr_tail.is_empty() {
+        warn!(
+            "Stream supervisor: last FFmpeg output of {}:\n{}",
+            name,
+            status
+                .stderr_tail
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<&str>>()
+                .join("\n")
+        );
+    }
+}
+
+// Removes the `user:password@` part from the URLs in a line
+fn remove_credentials(line: &str) -> String {
+    let mut cleaned = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(scheme_end) = rest.find("://") {
+        let authority_start = scheme_end + 3;
+        cleaned.push_str(&rest[..authority_start]);
+        rest = &rest[authority_start..];
+
+        // The credentials end with the last '@' before the path of the URL
+        let authority_end = rest
+            .find(|c: char| c == '/' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if let Some(at) = rest[..authority_end].rfind('@') {
+            rest = &rest[at + 1..];
+        }
+    }
+    cleaned.push_str(rest);
+
+    return cleaned;
+}
+
+fn set_failed(status: &Arc<Mutex<StreamStatus>>, error: String) {
+    let mut status = lock(status);
+    status.state = StreamState::Failed;
+    status.pid = None;
+    status.last_error = Some(error);
+}
+
+// 1, 2, 4, ... seconds, up to `MAX_BACKOFF_SECONDS`
+fn backoff(failures: u32) -> Duration {
+    let seconds = 2u64.saturating_pow(failures - 1).min(MAX_BACKOFF_SECONDS);
+    return Duration::from_secs(seconds);
+}
+
+// The status is only read and updated, so it's still valid if a thread panicked while holding it
+fn lock(status: &Mutex<StreamStatus>) -> std::sync::MutexGuard<'_, StreamStatus> {
+    return status.lock().unwrap_or_else(|e| e.into_inner());
+}
+
+fn max_retries() -> Result<u32, String> {
+    match env::var("STREAM_MAX_RETRIES") {
+        Ok(value) => value
+            .parse::<u32>()
+            .map_err(|_| "STREAM_MAX_RETRIES must be a number.".to_string()),
+        Err(_) => Ok(DEFAULT_MAX_RETRIES),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_are_removed_from_urls() {
+        assert_eq!(
+            remove_credentials("Input #0, rtsp, from 'rtsp://admin:p@ss@10.0.0.2:554/':"),
+            "Input #0, rtsp, from 'rtsp://10.0.0.2:554/':"
+        );
+        assert_eq!(
+            remove_credentials("rtsp://10.0.0.2:554/: Connection refused"),
+            "rtsp://10.0.0.2:554/: Connection refused"
+        );
+        assert_eq!(
+            remove_credentials("a rtsp://u:p@host b rtsp://v:q@other"),
+            "a rtsp://host b rtsp://other"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::editing::webvtt_timestamp;
+
+/// Default seconds between two thumbnails of the sprite sheet
+const DEFAULT_INTERVAL_SECONDS: u64 = 10;
+/// Size of every thumbnail of the sprite sheet
+const THUMBNAIL_WIDTH: u64 = 160;
+const THUMBNAIL_HEIGHT: u64 = 90;
+/// Thumbnails in every row of the sprite sheet
+const SPRITE_COLUMNS: u64 = 10;
+
+/// Name of the sprite sheet, as referenced by the WebVTT track
+pub const SPRITE_NAME: &str = "sprite";
+
+/// Files generated by `generate_thumbnails`
+pub struct Thumbnails {
+    pub poster: PathBuf,
+    pub sprite: PathBuf,
+    pub track: PathBuf,
+}
+
+// Folder of the media storage with the poster and the thumbnails of a video
+pub fn thumbnails_prefix(video_id: i64) -> String {
+    format!("thumbnails/video_{}", video_id)
+}
+
+// Generates in `dir` the poster of `video` (a path or a URL, as returned by `read_location`), a sprite sheet with a thumbnail every few seconds
+// and the WebVTT track that maps every interval of the video to its thumbnail in the sprite sheet.
+//
+// The cues of the track point to `SPRITE_NAME`, relative to the URL the track is served from.
+pub fn generate_thumbnails(video: &str, dir: &Path) -> Result<Thumbnails, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let interval = interval_seconds()?;
+    let duration = probe_duration(video)?;
+
+    let count = ((duration / interval as f64).ceil() as u64).max(1);
+    let columns = count.min(SPRITE_COLUMNS);
+    let rows = count.div_ceil(columns);
+
+    let thumbnails = Thumbnails {
+        poster: dir.join("poster.jpg"),
+        sprite: dir.join("sprite.jpg"),
+        track: dir.join("thumbnails.vtt"),
+    };
+
+    // The thumbnail filter picks the most representative frame among the first ones,
+    // to avoid a black poster when the recording starts
+    run_ffmpeg(
+        video,
+        "thumbnail,scale=640:-2",
+        &thumbnails.poster,
+        "generate the poster",
+    )?;
+
+    // Every thumbnail is padded to the same size, so that its position in the sprite sheet
+    // depends only on its index
+    run_ffmpeg(
+        video,
+        &format!(
+            "fps=1/{interval},scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,tile={columns}x{rows}",
+            interval = interval,
+            w = THUMBNAIL_WIDTH,
+            h = THUMBNAIL_HEIGHT,
+            columns = columns,
+            rows = rows
+        ),
+        &thumbnails.sprite,
+        "generate the sprite sheet",
+    )?;
+
+    let duration_ms = (duration * 1000.0).round() as i64;
+    let mut track = String::from("WEBVTT\n");
+    for index in 0..count {
+        let start_ms = (index * interval * 1000) as i64;
+        let end_ms = (((index + 1) * interval * 1000) as i64).min(duration_ms.max(start_ms));
+
+        track.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            webvtt_timestamp(start_ms),
+            webvtt_timestamp(end_ms),
+            SPRITE_NAME,
+            (index % columns) * THUMBNAIL_WIDTH,
+            (index / columns) * THUMBNAIL_HEIGHT,
+            THUMBNAIL_WIDTH,
+            THUMBNAIL_HEIGHT
+        ));
+    }
+
+    fs::write(&thumbnails.track, track)
+        .map_err(|e| format!("Failed to write {}: {}", thumbnails.track.display(), e))?;
+
+    Ok(thumbnails)
+}
+
+// Duration of the video in seconds, read with FFprobe
+fn probe_duration(video: &str) -> Result<f64, String> {
+    let probe = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-show_entries")
+        .arg("format=duration")
+        .arg("-of")
+        .arg("csv=p=0")
+        .arg(video)
+        .output()
+        .map_err(|e| format!("Failed to start FFprobe: {}", e))?;
+
+    String::from_utf8_lossy(&probe.stdout)
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| {
+            format!(
+                "FFprobe failed to read the duration of the video: {}",
+                String::from_utf8_lossy(&probe.stderr)
+            )
+        })
+}
+
+// Saves a single image of `video` processed by the `filter` graph in `output`
+fn run_ffmpeg(video: &str, filter: &str, output: &Path, action: &str) -> Result<(), String> {
+    let result = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-y")
+        .arg("-i")
+        .arg(video)
+        .arg("-vf")
+        .arg(filter)
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-q:v") // JPEG quality, from 2 (best) to 31
+        .arg("4")
+        .arg(output)
+        .output()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    if !result.status.success() {
+        let _ = fs::remove_file(output);
+        return Err(format!(
+            "FFmpeg failed to {}: {}",
+            action,
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+fn interval_seconds() -> Result<u64, String> {
+    match env::var("THUMBNAIL_INTERVAL_SECONDS") {
+        Ok(value) => value
+            .parse::<u64>()
+            .ok()
+            .filter(|interval| *interval > 0)
+            .ok_or_else(|| "THUMBNAIL_INTERVAL_SECONDS must be a positive number".to_string()),
+        Err(_) => Ok(DEFAULT_INTERVAL_SECONDS),
+    }
+}
@@ -0,0 +1,222 @@
+use std::env;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng},
+    AeadCore, Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use diesel::prelude::*;
+use infrastructure::establish_connection;
+use log::info;
+use rocket::http::Status;
+use shared::response_models::{ApiError, ApiErrorType};
+
+// Cifratura a busta delle credenziali salvate nel database (password delle telecamere e token di InfluxDB).
+//
+// Ogni credenziale viene cifrata con una chiave generata casualmente, che a sua volta viene cifrata con
+// una delle chiavi principali indicate nella configurazione. Il valore salvato ha la forma
+// `enc:v1:<id chiave principale>:<chiave cifrata>:<credenziale cifrata>`, dove le parti cifrate sono
+// in base64 e iniziano con il nonce usato per cifrarle.
+//
+// Per la rotazione è sufficiente aggiungere una nuova chiave principale e renderla attiva: all'avvio
+// le chiavi delle credenziali vengono cifrate di nuovo con la chiave attiva (vedi `rotate_secrets`).
+
+const PREFIX: &str = "enc:v1";
+const NONCE_LENGTH: usize = 12;
+
+// Chiave principale indicata nella configurazione
+struct MasterKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+/// Cifra una credenziale con la chiave principale attiva.
+pub fn encrypt(plaintext: &str) -> Result<String, String> {
+    let master_key = active_key()?;
+
+    let data_key = Aes256Gcm::generate_key(OsRng);
+    let data_cipher = Aes256Gcm::new(&data_key);
+
+    let wrapped_key = seal(&master_key.cipher, data_key.as_slice())?;
+    let ciphertext = seal(&data_cipher, plaintext.as_bytes())?;
+
+    Ok(format!(
+        "{}:{}:{}:{}",
+        PREFIX, master_key.id, wrapped_key, ciphertext
+    ))
+}
+
+/// Decifra una credenziale salvata con `encrypt`.
+///
+/// I valori non cifrati, inseriti prima dell'introduzione della cifratura, vengono restituiti così come sono.
+pub fn decrypt(value: &str) -> Result<String, String> {
+    let Some((key_id, wrapped_key, ciphertext)) = parse(value)? else {
+        return Ok(value.to_string());
+    };
+
+    let master_key = find_key(key_id)?;
+    let data_key = open(&master_key.cipher, wrapped_key)?;
+    if data_key.len() != 32 {
+        return Err("Invalid data key".to_string());
+    }
+    let data_cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key));
+
+    let plaintext = open(&data_cipher, ciphertext)?;
+
+    String::from_utf8(plaintext).map_err(|e| format!("Invalid decrypted secret: {}", e))
+}
+
+/// Restituisce il valore cifrato con la chiave principale attiva, o `None` se lo è già.
+///
+/// Solo la chiave della credenziale viene cifrata di nuovo, la credenziale resta invariata.
+/// I valori non ancora cifrati vengono cifrati.
+pub fn rewrap(value: &str) -> Result<Option<String>, String> {
+    let Some((key_id, wrapped_key, ciphertext)) = parse(value)? else {
+        return encrypt(value).map(Some);
+    };
+
+    let active_key = active_key()?;
+    if key_id == active_key.id {
+        return Ok(None);
+    }
+
+    let data_key = open(&find_key(key_id)?.cipher, wrapped_key)?;
+    let wrapped_key = seal(&active_key.cipher, &data_key)?;
+
+    Ok(Some(format!(
+        "{}:{}:{}:{}",
+        PREFIX, active_key.id, wrapped_key, ciphertext
+    )))
+}
+
+/// Cifra con la chiave principale attiva tutte le credenziali salvate nel database e restituisce il numero di quelle aggiornate.
+///
+/// Vengono cifrate le credenziali inserite prima dell'introduzione della cifratura e quelle cifrate con una chiave
+/// principale diversa da quella attiva, che al termine può essere rimossa dalla configurazione.
+pub fn rotate_secrets() -> Result<usize, ApiError> {
+    use domain::schema::{camera, influxdb_bucket};
+
+    let connection = &mut establish_connection();
+
+    let mut updated = 0;
+
+    let passwords = camera::table
+        .select((camera::id, camera::password))
+        .load::<(i64, String)>(connection)?;
+
+    for (camera_id, password) in passwords {
+        if let Some(password) = rewrap(&password).map_err(secret_error)? {
+            diesel::update(camera::table.filter(camera::id.eq(camera_id)))
+                .set(camera::password.eq(password))
+                .execute(connection)?;
+            updated += 1;
+        }
+    }
+
+    let tokens = influxdb_bucket::table
+        .select((influxdb_bucket::location, influxdb_bucket::token))
+        .load::<(String, String)>(connection)?;
+
+    for (location, token) in tokens {
+        if let Some(token) = rewrap(&token).map_err(secret_error)? {
+            diesel::update(influxdb_bucket::table.filter(influxdb_bucket::location.eq(location)))
+                .set(influxdb_bucket::token.eq(token))
+                .execute(connection)?;
+            updated += 1;
+        }
+    }
+
+    info!("Secrets: {} secrets encrypted with the active key", updated);
+
+    return Ok(updated);
+}
+
+/// Errore da restituire quando una credenziale non può essere cifrata o decifrata.
+pub fn secret_error(message: String) -> ApiError {
+    ApiError {
+        http_status: Status::InternalServerError,
+        error_code: 123, // TODO organizzare i codici di errore
+        error_type: ApiErrorType::ApplicationError,
+        message: format!("Error while processing a secret - {}", message),
+    }
+}
+
+// Parti di un valore cifrato, o `None` se il valore non è cifrato
+fn parse(value: &str) -> Result<Option<(&str, &str, &str)>, String> {
+    let Some(rest) = value
+        .strip_prefix(PREFIX)
+        .and_then(|rest| rest.strip_prefix(':'))
+    else {
+        return Ok(None);
+    };
+
+    let mut parts = rest.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(key_id), Some(wrapped_key), Some(ciphertext)) => {
+            Ok(Some((key_id, wrapped_key, ciphertext)))
+        }
+        _ => Err("Malformed encrypted secret".to_string()),
+    }
+}
+
+// Cifra i dati con un nonce casuale e restituisce nonce e dati cifrati in base64
+fn seal(cipher: &Aes256Gcm, data: &[u8]) -> Result<String, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+
+    Ok(STANDARD.encode(sealed))
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &str) -> Result<Vec<u8>, String> {
+    let sealed = STANDARD
+        .decode(sealed)
+        .map_err(|e| format!("Malformed encrypted secret: {}", e))?;
+
+    if sealed.len() < NONCE_LENGTH {
+        return Err("Malformed encrypted secret".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed, wrong key or corrupted secret".to_string())
+}
+
+fn active_key() -> Result<MasterKey, String> {
+    let key_id = env::var("SECRETS_ACTIVE_KEY_ID")
+        .map_err(|_| "SECRETS_ACTIVE_KEY_ID must be set".to_string())?;
+
+    find_key(&key_id)
+}
+
+// Le chiavi principali sono indicate come `<id>:<chiave di 32 byte in base64>`, separate da virgole
+fn find_key(key_id: &str) -> Result<MasterKey, String> {
+    let keys = env::var("SECRETS_KEYS").map_err(|_| "SECRETS_KEYS must be set".to_string())?;
+
+    for entry in keys.split(',').map(str::trim) {
+        let Some((id, key)) = entry.split_once(':') else {
+            return Err(format!("Malformed entry in SECRETS_KEYS: {}", entry));
+        };
+
+        if id == key_id {
+            let key = STANDARD
+                .decode(key)
+                .map_err(|e| format!("Key {} is not valid base64: {}", id, e))?;
+            if key.len() != 32 {
+                return Err(format!("Key {} must be 32 bytes long", id));
+            }
+
+            return Ok(MasterKey {
+                id: id.to_string(),
+                cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+            });
+        }
+    }
+
+    Err(format!("Key {} not found in SECRETS_KEYS", key_id))
+}
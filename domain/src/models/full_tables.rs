@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fmt;
 use std::net::{Ipv4Addr, Ipv6Addr};
 
@@ -11,9 +12,9 @@ use diesel::serialize::{self, Output, ToSql};
 use diesel::sql_types::Text;
 use diesel::AsExpression;
 use serde::{Deserialize, Serialize};
-use shared::validation::is_valid_phone;
+use shared::validation::{is_valid_color, is_valid_phone};
 use utoipa::ToSchema;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 /// Amministratore
 #[derive(
@@ -35,6 +36,137 @@ pub struct Administrator {
     pub person_id: i64,
 }
 
+/// Annotazione tracciata su uno screenshot o sul fotogramma di un segnaposto
+#[derive(
+    Debug,
+    Clone,
+    Identifiable,
+    Queryable,
+    Selectable,
+    AsChangeset,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::annotation)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(Screenshot, foreign_key = screenshot_id))]
+#[diesel(belongs_to(TimeMarker, foreign_key = time_marker_id))]
+#[diesel(belongs_to(User, foreign_key = author_id))]
+#[diesel(treat_none_as_null = true)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct Annotation {
+    pub id: i64,
+    /// Screenshot sul quale è tracciata l'annotazione
+    pub screenshot_id: Option<i64>,
+    /// Segnaposto sul cui fotogramma è tracciata l'annotazione
+    pub time_marker_id: Option<i64>,
+    /// Utente che ha tracciato l'annotazione
+    pub author_id: i64,
+    pub label: Option<String>,
+    pub shapes: AnnotationShapes,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: NaiveDateTime,
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: NaiveDateTime,
+}
+
+/// Forme di un'annotazione, salvate nel database in formato JSON
+#[derive(Debug, Clone, PartialEq, AsExpression, FromSqlRow, Serialize, Deserialize, ToSchema)]
+#[diesel(sql_type = Text)]
+#[serde(transparent)]
+pub struct AnnotationShapes(pub Vec<Shape>);
+
+impl ToSql<Text, Mysql> for AnnotationShapes
+where
+    String: serialize::ToSql<Text, Mysql>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Mysql>) -> serialize::Result {
+        let v = serde_json::to_string(&self.0)?;
+        <String as serialize::ToSql<Text, Mysql>>::to_sql(&v, &mut out.reborrow())
+    }
+}
+
+impl FromSql<Text, Mysql> for AnnotationShapes
+where
+    String: deserialize::FromSql<Text, Mysql>,
+{
+    fn from_sql(bytes: <Mysql as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        let v = <String as deserialize::FromSql<Text, Mysql>>::from_sql(bytes)?;
+        Ok(AnnotationShapes(serde_json::from_str(&v)?))
+    }
+}
+
+/// Forma di un'annotazione
+///
+/// I punti richiesti dipendono dal tipo di forma:
+/// - `arrow`: l'inizio e la punta della freccia
+/// - `circle`: il centro e un punto della circonferenza
+/// - `zone`: i vertici della zona, almeno 3
+/// - `path`: i punti del percorso di un giocatore, almeno 2, con una freccia sull'ultimo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_shape"))]
+pub struct Shape {
+    #[serde(rename = "type")]
+    pub kind: ShapeKind,
+    #[validate(length(min = 2, max = 500), nested)]
+    pub points: Vec<Point>,
+    /// Colore del tratto, in formato #RRGGBB o #RRGGBBAA
+    #[validate(custom(function = "is_valid_color"))]
+    pub color: String,
+    /// Colore di riempimento di cerchi e zone, in formato #RRGGBB o #RRGGBBAA
+    #[validate(custom(function = "is_valid_color"))]
+    pub fill: Option<String>,
+    /// Spessore del tratto, in proporzione alla larghezza del fotogramma
+    #[validate(range(min = 0.001, max = 0.1))]
+    pub width: Option<f64>,
+    /// Testo mostrato accanto alla forma
+    #[validate(length(min = 1, max = 64))]
+    pub label: Option<String>,
+}
+
+fn validate_shape(data: &Shape) -> Result<(), ValidationError> {
+    let valid = match data.kind {
+        ShapeKind::Arrow | ShapeKind::Circle => data.points.len() == 2,
+        ShapeKind::Zone => data.points.len() >= 3,
+        ShapeKind::Path => data.points.len() >= 2,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("invalid_shape_points").with_message(Cow::Borrowed(
+                "Arrows and circles need 2 points, zones at least 3 and paths at least 2",
+            )),
+        )
+    }
+}
+
+/// Tipo di forma di un'annotazione
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ShapeKind {
+    /// Freccia
+    Arrow,
+    /// Cerchio
+    Circle,
+    /// Zona del campo
+    Zone,
+    /// Percorso di un giocatore
+    Path,
+}
+
+/// Punto di un fotogramma, con coordinate da 0 (a sinistra o in alto) a 1 (a destra o in basso)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct Point {
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub x: f64,
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub y: f64,
+}
+
 /// Prenotazione
 #[derive(
     Debug,
@@ -64,9 +196,12 @@ pub struct Booking {
 }
 
 /// Videocamera
-#[derive(Debug, Clone, Identifiable, Queryable, Selectable, Serialize, Deserialize, ToSchema)]
+#[derive(
+    Debug, Clone, Identifiable, Queryable, Selectable, AsChangeset, Serialize, Deserialize, ToSchema,
+)]
 #[diesel(table_name = crate::schema::camera)]
 #[diesel(primary_key(id))]
+#[diesel(treat_none_as_null = true)]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct Camera {
     pub id: i64,
@@ -76,7 +211,13 @@ pub struct Camera {
     pub ipv6_address: Option<CustomIpv6Address>,
     pub port: u16,
     pub username: String,
+    /// Cifrata, non viene mai restituita dalle API
+    #[serde(skip_serializing)]
+    #[schema(write_only)]
     pub password: String,
+    pub name: String,
+    pub location_description: Option<String>,
+    pub enabled: bool,
 }
 
 // Implementazione della serializzazione Diesel per gli indirizzi IP
@@ -110,6 +251,12 @@ impl fmt::Display for CustomIpv4Address {
     }
 }
 
+impl From<Ipv4Addr> for CustomIpv4Address {
+    fn from(address: Ipv4Addr) -> Self {
+        CustomIpv4Address(address)
+    }
+}
+
 #[derive(Debug, Clone, Copy, AsExpression, FromSqlRow, Serialize, Deserialize)]
 #[diesel(sql_type = Text)]
 pub struct CustomIpv6Address(Ipv6Addr);
@@ -165,7 +312,9 @@ pub struct CameraSession {
 /// Relazione tra un Video e le sue clip
 // NB: Non è possibile usare belongs_to (trait Associations) per due volte sulla stessa tabella, è necessario effettuare dei join manualmente
 // (https://github.com/diesel-rs/diesel/issues/2613)
-#[derive(Debug, Identifiable, Queryable, Selectable, Serialize, Deserialize, ToSchema)]
+#[derive(
+    Debug, Identifiable, Queryable, Selectable, Insertable, Serialize, Deserialize, ToSchema,
+)]
 #[diesel(table_name = crate::schema::clip_video)]
 #[diesel(primary_key(original_video_id, clip_id))]
 //#[diesel(belongs_to(Video, foreign_key = original_video_id))]     // In conflitto
@@ -174,6 +323,9 @@ pub struct CameraSession {
 pub struct ClipVideo {
     pub original_video_id: i64,
     pub clip_id: i64,
+    /// Istante del video originale nel quale inizia la clip, null per i video di highlight
+    #[schema(value_type = Option<String>, examples("00:12:34.500"))]
+    pub start_instant: Option<NaiveTime>,
 }
 
 /// Allenatore
@@ -336,6 +488,132 @@ pub struct Game {
     pub booking_id: i64,
 }
 
+/// Richiesta di compilazione di un video di highlight
+#[derive(
+    Debug,
+    Clone,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::highlight_job)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(User, foreign_key = user_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct HighlightJob {
+    pub id: i64,
+    /// Utente che ha richiesto il video di highlight
+    pub user_id: i64,
+    pub name: String,
+    pub notes: Option<String>,
+    pub status: HighlightStatus,
+    /// Video di highlight prodotto, null finché la compilazione non è completata
+    pub video_id: Option<i64>,
+    /// Motivo per il quale la compilazione non è riuscita
+    pub error: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: NaiveDateTime,
+    /// Momento nel quale la compilazione è stata completata o non è riuscita
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+/// Stato della compilazione di un video di highlight
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize, ToSchema,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightStatus {
+    /// In attesa di essere compilato
+    Pending,
+    /// In compilazione
+    Running,
+    /// Compilato, il video di highlight è disponibile
+    Completed,
+    /// La compilazione non è riuscita
+    Failed,
+}
+
+impl ToSql<Text, Mysql> for HighlightStatus
+where
+    String: serialize::ToSql<Text, Mysql>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Mysql>) -> serialize::Result {
+        let v = self.to_string();
+        <String as serialize::ToSql<Text, Mysql>>::to_sql(&v, &mut out.reborrow())
+    }
+}
+
+impl FromSql<Text, Mysql> for HighlightStatus
+where
+    String: deserialize::FromSql<Text, Mysql>,
+{
+    fn from_sql(bytes: <Mysql as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        match <String as deserialize::FromSql<Text, Mysql>>::from_sql(bytes)?.as_str() {
+            "pending" => Ok(HighlightStatus::Pending),
+            "running" => Ok(HighlightStatus::Running),
+            "completed" => Ok(HighlightStatus::Completed),
+            "failed" => Ok(HighlightStatus::Failed),
+            status => Err(format!("Unknown highlight status {}", status).into()),
+        }
+    }
+}
+
+impl fmt::Display for HighlightStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HighlightStatus::Pending => write!(f, "pending"),
+            HighlightStatus::Running => write!(f, "running"),
+            HighlightStatus::Completed => write!(f, "completed"),
+            HighlightStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Parte di un video che compone un video di highlight
+#[derive(
+    Debug,
+    Clone,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::highlight_segment)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(HighlightJob, foreign_key = job_id))]
+//#[diesel(belongs_to(Video, foreign_key = video_id))]     // In conflitto
+//#[diesel(belongs_to(Video, foreign_key = clip_id))]      // In conflitto
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct HighlightSegment {
+    pub id: i64,
+    pub job_id: i64,
+    /// Posizione della parte nel video di highlight, a partire da 0
+    pub position: i32,
+    /// Video dal quale viene estratta la parte
+    pub video_id: i64,
+    /// Istante del video nel quale inizia la parte, compreso il pre-roll
+    #[schema(value_type = String)]
+    pub start_instant: NaiveTime,
+    /// Istante del video nel quale termina la parte, compreso il post-roll
+    #[schema(value_type = String)]
+    pub end_instant: NaiveTime,
+    /// Titolo mostrato prima della parte, null per non mostrare un titolo
+    pub title: Option<String>,
+    /// Segnaposto dal quale è stata ottenuta la parte
+    pub time_marker_id: Option<i64>,
+    /// Clip dalla quale è stata ottenuta la parte
+    pub clip_id: Option<i64>,
+}
+
 /// Bucket di InfluxDB
 #[derive(
     Debug, Identifiable, Queryable, Selectable, Associations, Serialize, Deserialize, ToSchema,
@@ -348,6 +626,9 @@ pub struct InfluxdbBucket {
     pub location: String,
     pub team_id: i64,
     pub name: String,
+    /// Cifrato, non viene mai restituito dalle API
+    #[serde(skip_serializing)]
+    #[schema(write_only)]
     pub token: String,
     pub org: String,
     pub db: String,
@@ -446,6 +727,91 @@ pub struct RecordingSession {
     pub booking_id: i64,
 }
 
+/// Regola di conservazione dei video
+///
+/// Una regola senza società sportiva e senza squadra si applica a tutti i video del tipo indicato.
+#[derive(
+    Debug,
+    Clone,
+    Identifiable,
+    Queryable,
+    Selectable,
+    AsChangeset,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::retention_rule)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(SportsClub, foreign_key = club_id))]
+#[diesel(belongs_to(Team, foreign_key = team_id))]
+#[diesel(treat_none_as_null = true)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct RetentionRule {
+    pub id: i64,
+    pub video_kind: VideoKind,
+    /// Giorni per i quali i video vengono conservati dalla loro data, null per conservarli per sempre
+    pub retention_days: Option<i32>,
+    pub club_id: Option<String>,
+    pub team_id: Option<i64>,
+}
+
+/// Tipo di video al quale si applica una regola di conservazione
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    AsExpression,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoKind {
+    /// Video registrato da una telecamera
+    Recording,
+    /// Clip estratta da un altro video
+    Clip,
+}
+
+impl ToSql<Text, Mysql> for VideoKind
+where
+    String: serialize::ToSql<Text, Mysql>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Mysql>) -> serialize::Result {
+        let v = self.to_string();
+        <String as serialize::ToSql<Text, Mysql>>::to_sql(&v, &mut out.reborrow())
+    }
+}
+
+impl FromSql<Text, Mysql> for VideoKind
+where
+    String: deserialize::FromSql<Text, Mysql>,
+{
+    fn from_sql(bytes: <Mysql as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        match <String as deserialize::FromSql<Text, Mysql>>::from_sql(bytes)?.as_str() {
+            "recording" => Ok(VideoKind::Recording),
+            "clip" => Ok(VideoKind::Clip),
+            kind => Err(format!("Unknown video kind {}", kind).into()),
+        }
+    }
+}
+
+impl fmt::Display for VideoKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VideoKind::Recording => write!(f, "recording"),
+            VideoKind::Clip => write!(f, "clip"),
+        }
+    }
+}
+
 /// Tag RFID
 #[derive(Debug, Identifiable, Queryable, Selectable, Serialize, Deserialize, ToSchema)]
 #[diesel(table_name = crate::schema::rfid_tag)]
@@ -465,6 +831,7 @@ pub struct RfidTag {
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct Screenshot {
     pub id: i64,
+    /// Chiave dell'immagine nell'archivio dei file multimediali
     pub file_location: String,
     pub video_id: i64,
     #[schema(value_type = String)]
@@ -499,6 +866,33 @@ pub struct SportsClub {
     pub phone: Option<String>,
 }
 
+/// Quota di spazio per i video di una società sportiva o di una squadra
+#[derive(
+    Debug,
+    Clone,
+    Identifiable,
+    Queryable,
+    Selectable,
+    AsChangeset,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::storage_quota)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(SportsClub, foreign_key = club_id))]
+#[diesel(belongs_to(Team, foreign_key = team_id))]
+#[diesel(treat_none_as_null = true)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct StorageQuota {
+    pub id: i64,
+    pub club_id: Option<String>,
+    pub team_id: Option<i64>,
+    /// Spazio massimo in byte occupato dai video
+    pub quota_bytes: i64,
+}
+
 /// Squadra
 #[derive(
     Debug,
@@ -527,10 +921,19 @@ pub struct Team {
 
 /// Segnaposto da inserire in un video
 #[derive(
-    Debug, Identifiable, Queryable, Selectable, Associations, Serialize, Deserialize, ToSchema,
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    AsChangeset,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
 )]
 #[diesel(table_name = crate::schema::time_marker)]
 #[diesel(primary_key(id))]
+#[diesel(treat_none_as_null = true)]
 #[diesel(belongs_to(Video, foreign_key = video_id))]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct TimeMarker {
@@ -540,6 +943,32 @@ pub struct TimeMarker {
     pub video_id: i64,
     pub name: String,
     pub notes: Option<String>,
+    /// Squadra alla quale si riferisce il segnaposto
+    pub team_id: Option<i64>,
+    /// Formazione della partita del video alla quale si riferisce il segnaposto
+    pub formation_id: Option<i64>,
+}
+
+/// Giocatore al quale si riferisce un segnaposto di un video
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Insertable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::time_marker_player)]
+#[diesel(primary_key(time_marker_id, player_id))]
+#[diesel(belongs_to(TimeMarker, foreign_key = time_marker_id))]
+#[diesel(belongs_to(Player, foreign_key = player_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct TimeMarkerPlayer {
+    pub time_marker_id: i64,
+    pub player_id: i64,
 }
 
 /// Allenamento
@@ -695,19 +1124,200 @@ pub struct UserInvitation {
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct Video {
     pub id: i64,
+    /// Chiave del file del video nell'archivio dei file multimediali
     pub file_location: String,
     pub name: String,
     #[schema(value_type = String, format = DateTime)]
     pub date: NaiveDateTime,
+    /// Momento, al millisecondo, nel quale è stata registrata la prima immagine del video, se noto
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub capture_start: Option<NaiveDateTime>,
     pub notes: Option<String>,
     pub session_id: i64,
     pub camera_id: i64,
+    /// Immagine di copertina del video, se generata
+    pub poster_location: Option<String>,
+    /// Immagine con le miniature del video a intervalli regolari, se generata
+    pub sprite_location: Option<String>,
+    /// Traccia WebVTT delle miniature, se generata
+    pub thumbnails_location: Option<String>,
+    /// Manifest DASH del video on demand, se pubblicato
+    pub vod_location: Option<String>,
+    /// I video fissati non vengono eliminati dalle regole di conservazione
+    pub pinned: bool,
+    /// Momento dal quale il video può essere eliminato, null se viene conservato per sempre
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub expires_at: Option<NaiveDateTime>,
+    /// Momento nel quale i proprietari sono stati avvisati della prossima eliminazione del video
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub expiration_warned_at: Option<NaiveDateTime>,
+    #[diesel(embed)]
+    #[serde(flatten)]
+    pub metadata: VideoMetadata,
 }
 
-/// Permessi di azione di un utente su un video
+/// Caratteristiche tecniche di un video, lette con FFprobe quando il video viene registrato
+///
+/// Le caratteristiche che non è stato possibile leggere sono null.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Queryable,
+    Selectable,
+    Insertable,
+    AsChangeset,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::video)]
+#[diesel(treat_none_as_null = true)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoMetadata {
+    /// Durata del video in millisecondi
+    pub duration_ms: Option<i64>,
+    /// Larghezza in pixel della traccia video
+    pub width: Option<i32>,
+    /// Altezza in pixel della traccia video
+    pub height: Option<i32>,
+    /// Fotogrammi al secondo della traccia video
+    pub frame_rate: Option<f64>,
+    pub video_codec: Option<String>,
+    /// Codec della traccia audio, null se il video non ha audio
+    pub audio_codec: Option<String>,
+    /// Bitrate complessivo del video in bit al secondo
+    pub bit_rate: Option<i64>,
+    /// Dimensione in byte del file del video
+    pub file_size: Option<i64>,
+}
+
+/// Commento di un video, che inizia una discussione o risponde a una discussione esistente
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    AsChangeset,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::video_comment)]
+#[diesel(primary_key(id))]
+#[diesel(treat_none_as_null = true)]
+#[diesel(belongs_to(Video, foreign_key = video_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoComment {
+    pub id: i64,
+    pub video_id: i64,
+    /// Utente che ha scritto il commento
+    pub author_id: i64,
+    /// Primo commento della discussione alla quale risponde il commento, null per il primo commento di una discussione
+    pub parent_id: Option<i64>,
+    /// Istante del video al quale si riferisce la discussione, null per una discussione sul video intero
+    #[schema(value_type = Option<String>, examples("00:12:34.500"))]
+    pub start_instant: Option<NaiveTime>,
+    /// Fine dell'intervallo del video al quale si riferisce la discussione, null per un singolo istante
+    #[schema(value_type = Option<String>)]
+    pub end_instant: Option<NaiveTime>,
+    pub body: String,
+    /// Se la discussione è stata risolta, solo per il primo commento di una discussione
+    pub resolved: bool,
+    /// Utente che ha risolto la discussione
+    pub resolved_by: Option<i64>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub resolved_at: Option<NaiveDateTime>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: NaiveDateTime,
+    /// Momento dell'ultima modifica del testo, null se non è mai stato modificato
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub edited_at: Option<NaiveDateTime>,
+}
+
+/// Utente menzionato in un commento di un video
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Insertable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::video_comment_mention)]
+#[diesel(primary_key(comment_id, user_id))]
+#[diesel(belongs_to(VideoComment, foreign_key = comment_id))]
+#[diesel(belongs_to(User, foreign_key = user_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoCommentMention {
+    pub comment_id: i64,
+    pub user_id: i64,
+}
+
+/// Testo precedente di un commento modificato
 #[derive(
     Debug, Identifiable, Queryable, Selectable, Associations, Serialize, Deserialize, ToSchema,
 )]
+#[diesel(table_name = crate::schema::video_comment_revision)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(VideoComment, foreign_key = comment_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoCommentRevision {
+    pub id: i64,
+    pub comment_id: i64,
+    /// Testo del commento prima della modifica
+    pub body: String,
+    /// Momento nel quale il testo è stato sostituito
+    #[schema(value_type = String, format = DateTime)]
+    pub replaced_at: NaiveDateTime,
+}
+
+/// Avviso a un utente della prossima eliminazione di un video da parte delle regole di conservazione
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Insertable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::video_expiration_notice)]
+#[diesel(primary_key(video_id, user_id))]
+#[diesel(belongs_to(Video, foreign_key = video_id))]
+#[diesel(belongs_to(User, foreign_key = user_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoExpirationNotice {
+    pub video_id: i64,
+    /// Utente avvisato, proprietario del video o con accesso al video tramite una condivisione
+    pub user_id: i64,
+    /// Scadenza del video al momento dell'avviso
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: NaiveDateTime,
+    /// Momento nel quale è stato registrato l'avviso
+    #[schema(value_type = String, format = DateTime)]
+    pub notified_at: NaiveDateTime,
+}
+
+/// Permessi di azione di un utente su un video
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Insertable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
 #[diesel(table_name = crate::schema::video_user)]
 #[diesel(primary_key(user_id, video_id))]
 #[diesel(belongs_to(User, foreign_key = user_id))]
@@ -722,3 +1332,56 @@ pub struct VideoUser {
     pub delete: bool,
     pub share: bool,
 }
+
+/// Permessi di azione dei membri di una squadra su un video condiviso con la squadra
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Insertable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::video_team)]
+#[diesel(primary_key(video_id, team_id))]
+#[diesel(belongs_to(Team, foreign_key = team_id))]
+#[diesel(belongs_to(Video, foreign_key = video_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoTeam {
+    pub video_id: i64,
+    pub team_id: i64,
+    pub read: bool,
+    pub edit: bool,
+    pub delete: bool,
+    pub share: bool,
+}
+
+/// Permessi di azione dei membri di una società sportiva su un video condiviso con la società
+#[derive(
+    Debug,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Insertable,
+    Associations,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[diesel(table_name = crate::schema::video_club)]
+#[diesel(primary_key(video_id, club_id))]
+#[diesel(belongs_to(SportsClub, foreign_key = club_id))]
+#[diesel(belongs_to(Video, foreign_key = video_id))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct VideoClub {
+    pub video_id: i64,
+    /// Partita IVA della società sportiva
+    pub club_id: String,
+    pub read: bool,
+    pub edit: bool,
+    pub delete: bool,
+    pub share: bool,
+}
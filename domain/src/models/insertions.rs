@@ -7,7 +7,11 @@ use utoipa::ToSchema;
 use validator::{Validate, ValidationError};
 
 use super::{
-    full_tables::{Booking, Person, RecordingSession, SportsClub, Team},
+    full_tables::{
+        AnnotationShapes, Booking, Camera, CustomIpv4Address, CustomIpv6Address, HighlightStatus,
+        Person, RecordingSession, RetentionRule, Screenshot, SportsClub, StorageQuota, Team,
+        TimeMarker, Video, VideoKind, VideoMetadata,
+    },
     WithId,
 };
 use shared::validation::is_future_datetime;
@@ -237,3 +241,301 @@ fn validate_recording_session(data: &NewRecordingSession) -> Result<(), Validati
         Ok(())
     }
 }
+
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::video)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewVideo {
+    pub file_location: String,
+    pub name: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub date: NaiveDateTime,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub capture_start: Option<NaiveDateTime>,
+    pub notes: Option<String>,
+    pub session_id: i64,
+    pub camera_id: i64,
+    #[diesel(embed)]
+    #[serde(flatten)]
+    pub metadata: VideoMetadata,
+}
+
+impl WithId for NewVideo {
+    type IdentifiedType = Video;
+    type IdType = i64;
+    fn to_identified(&self, id: Self::IdType) -> Self::IdentifiedType {
+        Video {
+            id,
+            file_location: self.file_location.clone(),
+            name: self.name.clone(),
+            date: self.date,
+            capture_start: self.capture_start,
+            notes: self.notes.clone(),
+            session_id: self.session_id,
+            camera_id: self.camera_id,
+            poster_location: None,
+            sprite_location: None,
+            thumbnails_location: None,
+            vod_location: None,
+            pinned: false,
+            expires_at: None,
+            expiration_warned_at: None,
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::screenshot)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewScreenshot {
+    pub file_location: String,
+    pub video_id: i64,
+    #[schema(value_type = String)]
+    pub instant: NaiveTime,
+    pub name: String,
+    pub notes: Option<String>,
+}
+
+impl WithId for NewScreenshot {
+    type IdentifiedType = Screenshot;
+    type IdType = i64;
+    fn to_identified(&self, id: Self::IdType) -> Self::IdentifiedType {
+        Screenshot {
+            id,
+            file_location: self.file_location.clone(),
+            video_id: self.video_id,
+            instant: self.instant,
+            name: self.name.clone(),
+            notes: self.notes.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::time_marker)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewTimeMarker {
+    #[schema(value_type = String)]
+    pub instant: NaiveTime,
+    pub video_id: i64,
+    pub name: String,
+    pub notes: Option<String>,
+    pub team_id: Option<i64>,
+    pub formation_id: Option<i64>,
+}
+
+impl WithId for NewTimeMarker {
+    type IdentifiedType = TimeMarker;
+    type IdType = i64;
+    fn to_identified(&self, id: Self::IdType) -> Self::IdentifiedType {
+        TimeMarker {
+            id,
+            instant: self.instant,
+            video_id: self.video_id,
+            name: self.name.clone(),
+            notes: self.notes.clone(),
+            team_id: self.team_id,
+            formation_id: self.formation_id,
+        }
+    }
+}
+
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema, Validate)]
+#[diesel(table_name = crate::schema::camera)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewCamera {
+    #[schema(value_type = String)]
+    pub ipv4_address: CustomIpv4Address,
+    #[schema(value_type = String)]
+    pub ipv6_address: Option<CustomIpv6Address>,
+    #[validate(range(min = 1))]
+    pub port: u16,
+    #[validate(length(max = 255))]
+    pub username: String,
+    #[validate(length(max = 255))]
+    #[serde(skip_serializing)]
+    #[schema(write_only)]
+    pub password: String,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[validate(length(max = 255))]
+    pub location_description: Option<String>,
+    pub enabled: bool,
+}
+
+impl WithId for NewCamera {
+    type IdentifiedType = Camera;
+    type IdType = i64;
+    fn to_identified(&self, id: Self::IdType) -> Self::IdentifiedType {
+        Camera {
+            id,
+            ipv4_address: self.ipv4_address,
+            ipv6_address: self.ipv6_address,
+            port: self.port,
+            username: self.username.clone(),
+            password: self.password.clone(),
+            name: self.name.clone(),
+            location_description: self.location_description.clone(),
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Regola di conservazione dei video, globale o specifica di una società sportiva o di una squadra
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema, Validate)]
+#[diesel(table_name = crate::schema::retention_rule)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+#[validate(schema(function = "validate_retention_rule"))]
+pub struct NewRetentionRule {
+    pub video_kind: VideoKind,
+    /// Giorni per i quali i video vengono conservati dalla loro data, null per conservarli per sempre
+    #[validate(range(min = 1))]
+    pub retention_days: Option<i32>,
+    /// Partita IVA della società sportiva, se la regola è specifica di una società sportiva
+    #[validate(length(equal = 11))]
+    pub club_id: Option<String>,
+    /// ID della squadra, se la regola è specifica di una squadra
+    pub team_id: Option<i64>,
+}
+
+fn validate_retention_rule(data: &NewRetentionRule) -> Result<(), ValidationError> {
+    if data.club_id.is_some() && data.team_id.is_some() {
+        Err(
+            ValidationError::new("invalid_retention_rule_scope").with_message(Cow::Borrowed(
+                "A retention rule can apply to a club or to a team, not both",
+            )),
+        )
+    } else {
+        Ok(())
+    }
+}
+
+impl WithId for NewRetentionRule {
+    type IdentifiedType = RetentionRule;
+    type IdType = i64;
+    fn to_identified(&self, id: Self::IdType) -> Self::IdentifiedType {
+        RetentionRule {
+            id,
+            video_kind: self.video_kind,
+            retention_days: self.retention_days,
+            club_id: self.club_id.clone(),
+            team_id: self.team_id,
+        }
+    }
+}
+
+/// Quota di spazio per i video di una società sportiva o di una squadra
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema, Validate)]
+#[diesel(table_name = crate::schema::storage_quota)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+#[validate(schema(function = "validate_storage_quota"))]
+pub struct NewStorageQuota {
+    /// Partita IVA della società sportiva
+    #[validate(length(equal = 11))]
+    pub club_id: Option<String>,
+    /// ID della squadra
+    pub team_id: Option<i64>,
+    /// Spazio massimo in byte occupato dai video
+    #[validate(range(min = 0))]
+    pub quota_bytes: i64,
+}
+
+fn validate_storage_quota(data: &NewStorageQuota) -> Result<(), ValidationError> {
+    if data.club_id.is_some() == data.team_id.is_some() {
+        Err(
+            ValidationError::new("invalid_storage_quota_scope").with_message(Cow::Borrowed(
+                "A storage quota must apply to either a club or a team",
+            )),
+        )
+    } else {
+        Ok(())
+    }
+}
+
+impl WithId for NewStorageQuota {
+    type IdentifiedType = StorageQuota;
+    type IdType = i64;
+    fn to_identified(&self, id: Self::IdType) -> Self::IdentifiedType {
+        StorageQuota {
+            id,
+            club_id: self.club_id.clone(),
+            team_id: self.team_id,
+            quota_bytes: self.quota_bytes,
+        }
+    }
+}
+
+/// Richiesta di compilazione di un video di highlight
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::highlight_job)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewHighlightJob {
+    pub user_id: i64,
+    pub name: String,
+    pub notes: Option<String>,
+    pub status: HighlightStatus,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: NaiveDateTime,
+}
+
+/// Parte di un video che compone un video di highlight
+#[derive(Debug, Clone, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::highlight_segment)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewHighlightSegment {
+    pub job_id: i64,
+    pub position: i32,
+    pub video_id: i64,
+    #[schema(value_type = String)]
+    pub start_instant: NaiveTime,
+    #[schema(value_type = String)]
+    pub end_instant: NaiveTime,
+    pub title: Option<String>,
+    pub time_marker_id: Option<i64>,
+    pub clip_id: Option<i64>,
+}
+
+/// Annotazione tracciata su uno screenshot o sul fotogramma di un segnaposto
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::annotation)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewAnnotation {
+    pub screenshot_id: Option<i64>,
+    pub time_marker_id: Option<i64>,
+    pub author_id: i64,
+    pub label: Option<String>,
+    pub shapes: AnnotationShapes,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: NaiveDateTime,
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: NaiveDateTime,
+}
+
+/// Commento di un video
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::video_comment)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewVideoComment {
+    pub video_id: i64,
+    pub author_id: i64,
+    pub parent_id: Option<i64>,
+    #[schema(value_type = Option<String>)]
+    pub start_instant: Option<NaiveTime>,
+    #[schema(value_type = Option<String>)]
+    pub end_instant: Option<NaiveTime>,
+    pub body: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: NaiveDateTime,
+}
+
+/// Testo precedente di un commento modificato
+#[derive(Debug, Selectable, Insertable, Serialize, Deserialize, ToSchema)]
+#[diesel(table_name = crate::schema::video_comment_revision)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NewVideoCommentRevision {
+    pub comment_id: i64,
+    pub body: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub replaced_at: NaiveDateTime,
+}
@@ -7,7 +7,11 @@ use utoipa::ToSchema;
 use validator::{Validate, ValidationError, ValidationErrors};
 
 use super::{
-    full_tables::{Booking, Camera, Game, Person, RecordingSession, Team, Training, User},
+    full_tables::{
+        Booking, Camera, Game, HighlightJob, HighlightSegment, Person, RecordingSession, Shape,
+        SportsClub, Team, TimeMarker, Training, User, Video, VideoClub, VideoComment, VideoTeam,
+        VideoUser,
+    },
     insertions::{NewBooking, NewRecordingSession},
 };
 
@@ -354,26 +358,491 @@ pub struct RecordingSessionWithCameras {
     pub cameras: Vec<Camera>,
 }
 
+/// Video con il numero delle sue clip e dei suoi segnaposti
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct NewScreenshot {
-    pub todo: String,
-    // TODO aggiungere i campi necessari
+pub struct VideoSummary {
+    #[serde(flatten)]
+    pub video: Video,
+    /// ID del video dal quale è stata estratta la clip, se il video è una clip
+    pub original_video_id: Option<i64>,
+    /// Numero di clip estratte dal video
+    pub clip_count: i64,
+    /// Numero di segnaposti inseriti nel video
+    pub marker_count: i64,
+}
+
+/// Video registrato da una telecamera durante una sessione di registrazione, con il suo inizio
+/// rispetto all'inizio della sessione
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VideoAngle {
+    #[serde(flatten)]
+    pub video: Video,
+    /// Nome della telecamera che ha registrato il video
+    pub camera_name: String,
+    /// Millisecondi tra l'inizio della sessione e l'inizio del video, negativi se il video è iniziato prima della sessione
+    pub offset_ms: i64,
+}
+
+/// Video delle telecamere di una sessione di registrazione, sincronizzati sull'inizio della sessione
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionAngles {
+    pub recording_session: RecordingSession,
+    pub angles: Vec<VideoAngle>,
+}
+
+/// Richiesta di estrazione di uno screenshot da un video
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ScreenshotRequest {
+    /// Istante del video dal quale estrarre lo screenshot
+    #[schema(value_type = String, examples("00:12:34.500"))]
+    pub instant: NaiveTime,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub notes: Option<String>,
+}
+
+/// Dati di un segnaposto da inserire in un video
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TimeMarkerData {
+    /// Istante del video nel quale si trova il segnaposto
+    #[schema(value_type = String, examples("00:12:34.500"))]
+    pub instant: NaiveTime,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub notes: Option<String>,
+    /// ID dei giocatori ai quali si riferisce il segnaposto
+    #[serde(default)]
+    #[validate(length(max = 30))]
+    pub players: Vec<i64>,
+    /// Squadra alla quale si riferisce il segnaposto; se non indicata, viene usata la squadra della formazione
+    pub team_id: Option<i64>,
+    /// Formazione della partita del video alla quale si riferisce il segnaposto
+    pub formation_id: Option<i64>,
+}
+
+/// Segnaposto di un video con i giocatori ai quali si riferisce
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TimeMarkerDetails {
+    #[serde(flatten)]
+    pub marker: TimeMarker,
+    /// ID dei giocatori ai quali si riferisce il segnaposto
+    pub players: Vec<i64>,
 }
 
+/// Momento di un video nel quale è stato indicato un giocatore, con la posizione per la riproduzione
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct NewTimestamp {
-    pub todo: String,
-    // TODO aggiungere i campi necessari
+pub struct PlayerMoment {
+    #[serde(flatten)]
+    pub marker: TimeMarkerDetails,
+    /// Nome del video nel quale si trova il segnaposto
+    pub video_name: String,
+    /// Millisecondi dall'inizio del video, da usare come posizione di partenza della riproduzione
+    pub offset_ms: i64,
+    /// Data e ora della registrazione nella quale si trova il segnaposto
+    #[schema(value_type = String, format = DateTime)]
+    pub recorded_at: NaiveDateTime,
+}
+
+/// Filtri della ricerca dei momenti nei quali è stato indicato un giocatore
+#[derive(Debug, Default)]
+pub struct PlayerMomentsFilter {
+    /// Nome del segnaposto, ad esempio il tipo di azione
+    pub name: Option<String>,
+    pub team_id: Option<i64>,
+    /// Data minima dei video
+    pub from_date: Option<NaiveDateTime>,
+    /// Data massima dei video
+    pub to_date: Option<NaiveDateTime>,
+}
+
+/// Richiesta di inserimento di un segnaposto in un video
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TimeMarkerRequest {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub marker: TimeMarkerData,
+    /// Se vero, il segnaposto viene inserito anche nei video delle altre telecamere della stessa
+    /// sessione di registrazione, nello stesso momento della registrazione
+    #[serde(default)]
+    pub all_angles: bool,
+}
+
+/// Richiesta di estrazione di una clip da un video
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_clip_request"))]
+pub struct ClipRequest {
+    /// Istante del video nel quale inizia la clip
+    #[schema(value_type = String, examples("00:12:30"))]
+    pub start: NaiveTime,
+    /// Istante del video nel quale termina la clip
+    #[schema(value_type = String, examples("00:13:00"))]
+    pub end: NaiveTime,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub mode: ClipMode,
+}
+
+fn validate_clip_request(data: &ClipRequest) -> Result<(), ValidationError> {
+    if data.start < data.end {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("invalid_clip_period").with_message(Cow::Borrowed(
+                "The start of the clip must be before its end",
+            )),
+        )
+    }
+}
+
+/// Modalità di estrazione di una clip
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipMode {
+    /// Il video viene copiato senza essere ricodificato: l'estrazione è veloce, ma la clip inizia
+    /// dal fotogramma chiave precedente all'istante richiesto
+    #[default]
+    Copy,
+    /// Il video viene ricodificato: l'estrazione è più lenta, ma la clip inizia esattamente all'istante richiesto
+    Reencode,
+}
+
+/// Permessi su un video
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Validate,
+)]
+#[validate(schema(function = "validate_video_permissions"))]
+pub struct VideoPermissions {
+    #[serde(default)]
+    pub read: bool,
+    #[serde(default)]
+    pub edit: bool,
+    #[serde(default)]
+    pub delete: bool,
+    #[serde(default)]
+    pub share: bool,
+}
+
+impl VideoPermissions {
+    pub fn all() -> VideoPermissions {
+        VideoPermissions {
+            read: true,
+            edit: true,
+            delete: true,
+            share: true,
+        }
+    }
+
+    /// Vero se tutti i permessi di `other` sono compresi in questi
+    pub fn contains(&self, other: &VideoPermissions) -> bool {
+        (self.read || !other.read)
+            && (self.edit || !other.edit)
+            && (self.delete || !other.delete)
+            && (self.share || !other.share)
+    }
+
+    /// Unione dei permessi
+    pub fn union(&self, other: &VideoPermissions) -> VideoPermissions {
+        VideoPermissions {
+            read: self.read || other.read,
+            edit: self.edit || other.edit,
+            delete: self.delete || other.delete,
+            share: self.share || other.share,
+        }
+    }
+}
+
+fn validate_video_permissions(data: &VideoPermissions) -> Result<(), ValidationError> {
+    if data.read || data.edit || data.delete || data.share {
+        Ok(())
+    } else {
+        Err(ValidationError::new("empty_video_permissions")
+            .with_message(Cow::Borrowed("At least one permission must be granted")))
+    }
 }
 
+/// Richiesta di condivisione di un video
+///
+/// I permessi vengono concessi a ciascun utente indicato e alle squadre e alle società sportive indicate.
+/// I permessi di una squadra valgono per i suoi giocatori e allenatori attuali, quelli di una società sportiva
+/// per i suoi responsabili attuali e per i giocatori e gli allenatori attuali delle sue squadre, anche se
+/// entrano a farne parte dopo la condivisione.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_video_share_request"))]
+pub struct VideoShareRequest {
+    #[serde(default)]
+    pub user_ids: Vec<i64>,
+    #[serde(default)]
+    pub team_ids: Vec<i64>,
+    /// Partite IVA delle società sportive
+    #[serde(default)]
+    pub club_ids: Vec<String>,
+    #[validate(nested)]
+    pub permissions: VideoPermissions,
+}
+
+/// Condivisioni di un video con gli utenti, le squadre e le società sportive
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct NewClip {
-    pub todo: String,
-    // TODO aggiungere i campi necessari
+pub struct VideoShares {
+    pub users: Vec<VideoUser>,
+    pub teams: Vec<VideoTeam>,
+    pub clubs: Vec<VideoClub>,
+}
+
+fn validate_video_share_request(data: &VideoShareRequest) -> Result<(), ValidationError> {
+    if data.user_ids.is_empty() && data.team_ids.is_empty() && data.club_ids.is_empty() {
+        Err(
+            ValidationError::new("empty_video_share_request").with_message(Cow::Borrowed(
+                "At least one user, team or club must be specified",
+            )),
+        )
+    } else {
+        Ok(())
+    }
+}
+
+/// Stato di un processo FFmpeg che riceve una stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamState {
+    /// Il processo è appena stato avviato
+    Starting,
+    /// Il processo è attivo
+    Running,
+    /// Il processo è terminato inaspettatamente ed è in attesa di essere riavviato
+    Retrying,
+    /// Il processo non può più essere riavviato
+    Failed,
 }
 
+/// Stream attiva di una telecamera
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct UserList {
-    pub todo: String,
-    // TODO aggiungere i campi necessari
+pub struct ActiveStream {
+    pub camera_id: i64,
+    /// Sessione di registrazione che la stream sta archiviando, se presente
+    pub session_id: Option<i64>,
+    /// URL del manifest DASH della stream, che non cambia finché la telecamera esiste
+    pub manifest_url: String,
+    pub state: StreamState,
+    /// PID del processo FFmpeg, se attivo
+    pub pid: Option<u32>,
+    /// Secondi trascorsi dall'avvio del processo FFmpeg attuale
+    pub uptime_seconds: i64,
+    /// Numero di riavvii del processo FFmpeg
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// Ultime righe scritte da FFmpeg sullo stderr
+    pub stderr_tail: Vec<String>,
+}
+
+/// Esito della verifica della raggiungibilità di una telecamera tramite RTSP
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CameraProbe {
+    pub camera_id: i64,
+    /// URL RTSP verificato, senza credenziali
+    pub url: String,
+    /// La telecamera ha risposto alla richiesta OPTIONS
+    pub reachable: bool,
+    /// Tempo in millisecondi impiegato dalla telecamera per rispondere alla richiesta OPTIONS
+    pub latency_ms: Option<u64>,
+    /// Metodi RTSP supportati dalla telecamera
+    pub methods: Vec<String>,
+    /// Codice di stato della risposta alla richiesta DESCRIBE
+    pub describe_status: Option<u16>,
+    /// Tracce descritte dalla telecamera nella risposta alla richiesta DESCRIBE
+    pub tracks: Vec<CameraTrack>,
+    pub error: Option<String>,
+}
+
+/// Traccia di una stream RTSP
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CameraTrack {
+    /// Tipo di traccia (video, audio, ...)
+    pub media: String,
+    /// Codec della traccia, se indicato
+    pub codec: Option<String>,
+    /// Percorso di controllo della traccia, se indicato
+    pub control: Option<String>,
+}
+
+/// URL firmato di un contenuto multimediale, utilizzabile senza l'header di autenticazione fino alla scadenza
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SignedUrl {
+    pub url: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: NaiveDateTime,
+}
+
+/// Spazio occupato dai video di una squadra
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TeamStorageUsage {
+    pub team: Team,
+    /// Byte occupati dai video delle partite e degli allenamenti della squadra
+    pub used_bytes: i64,
+    /// Quota di spazio della squadra in byte, se assegnata
+    pub quota_bytes: Option<i64>,
+    pub video_count: i64,
+}
+
+/// Spazio occupato dai video di una società sportiva, in totale e per ciascuna delle sue squadre
+///
+/// Un video di una partita tra due squadre della stessa società sportiva viene contato una volta sola.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ClubStorageUsage {
+    pub club: SportsClub,
+    pub used_bytes: i64,
+    /// Quota di spazio della società sportiva in byte, se assegnata
+    pub quota_bytes: Option<i64>,
+    pub video_count: i64,
+    pub teams: Vec<TeamStorageUsage>,
+}
+
+/// Richiesta di compilazione di un video di highlight, formato dalle parti dei video indicate una dopo l'altra
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct HighlightRequest {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub notes: Option<String>,
+    /// Millisecondi riprodotti prima di ciascuna parte, se non indicati dalla parte
+    #[serde(default)]
+    #[validate(range(min = 0, max = 600000))]
+    pub pre_roll_ms: i32,
+    /// Millisecondi riprodotti dopo ciascuna parte, se non indicati dalla parte
+    #[serde(default)]
+    #[validate(range(min = 0, max = 600000))]
+    pub post_roll_ms: i32,
+    /// Se vero, prima di ciascuna parte senza un titolo viene mostrato il nome del segnaposto o della clip
+    #[serde(default)]
+    pub title_cards: bool,
+    #[validate(length(min = 1, max = 100), nested)]
+    pub segments: Vec<HighlightSegmentRequest>,
+}
+
+/// Parte di un video di highlight, ottenuta da un segnaposto o da una clip
+///
+/// Una parte ottenuta da un segnaposto va da `pre_roll_ms` prima a `post_roll_ms` dopo il suo istante,
+/// una parte ottenuta da una clip va da `pre_roll_ms` prima del suo inizio a `post_roll_ms` dopo la sua fine
+/// nel video dal quale è stata estratta.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_highlight_segment_request"))]
+pub struct HighlightSegmentRequest {
+    pub time_marker_id: Option<i64>,
+    pub clip_id: Option<i64>,
+    #[validate(range(min = 0, max = 600000))]
+    pub pre_roll_ms: Option<i32>,
+    #[validate(range(min = 0, max = 600000))]
+    pub post_roll_ms: Option<i32>,
+    /// Titolo mostrato prima della parte
+    #[validate(length(min = 1, max = 64))]
+    pub title: Option<String>,
+}
+
+fn validate_highlight_segment_request(
+    data: &HighlightSegmentRequest,
+) -> Result<(), ValidationError> {
+    if data.time_marker_id.is_some() == data.clip_id.is_some() {
+        Err(
+            ValidationError::new("invalid_highlight_segment_source").with_message(Cow::Borrowed(
+                "A highlight segment must come from either a time marker or a clip",
+            )),
+        )
+    } else {
+        Ok(())
+    }
+}
+
+/// Richiesta di compilazione di un video di highlight, con le parti che lo compongono
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HighlightJobDetails {
+    #[serde(flatten)]
+    pub job: HighlightJob,
+    pub segments: Vec<HighlightSegment>,
+}
+
+/// Dati di un'annotazione da tracciare su uno screenshot o sul fotogramma di un segnaposto
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AnnotationData {
+    #[validate(length(min = 1, max = 64))]
+    pub label: Option<String>,
+    #[validate(length(min = 1, max = 100), nested)]
+    pub shapes: Vec<Shape>,
+}
+
+/// Richiesta di inserimento di un'annotazione su uno screenshot o sul fotogramma di un segnaposto del video
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_annotation_request"))]
+pub struct AnnotationRequest {
+    pub screenshot_id: Option<i64>,
+    pub time_marker_id: Option<i64>,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub annotation: AnnotationData,
+}
+
+fn validate_annotation_request(data: &AnnotationRequest) -> Result<(), ValidationError> {
+    if data.screenshot_id.is_some() == data.time_marker_id.is_some() {
+        Err(
+            ValidationError::new("invalid_annotation_target").with_message(Cow::Borrowed(
+                "An annotation must be drawn on either a screenshot or a time marker",
+            )),
+        )
+    } else {
+        Ok(())
+    }
+}
+
+/// Testo di un commento di un video, con gli utenti menzionati
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct VideoCommentData {
+    #[validate(length(min = 1, max = 4000))]
+    pub body: String,
+    /// ID degli utenti menzionati nel commento, che devono avere l'accesso al video in lettura
+    #[serde(default)]
+    #[validate(length(max = 20))]
+    pub mentions: Vec<i64>,
+}
+
+/// Richiesta di inserimento di una nuova discussione in un video
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_video_comment_request"))]
+pub struct VideoCommentRequest {
+    /// Istante del video al quale si riferisce la discussione, null per una discussione sul video intero
+    #[schema(value_type = Option<String>, examples("00:12:34.500"))]
+    pub start_instant: Option<NaiveTime>,
+    /// Fine dell'intervallo del video al quale si riferisce la discussione, null per un singolo istante
+    #[schema(value_type = Option<String>)]
+    pub end_instant: Option<NaiveTime>,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub comment: VideoCommentData,
+}
+
+fn validate_video_comment_request(data: &VideoCommentRequest) -> Result<(), ValidationError> {
+    match (data.start_instant, data.end_instant) {
+        (None, Some(_)) => Err(ValidationError::new("invalid_comment_range")
+            .with_message(Cow::Borrowed("The end of a range needs its start"))),
+        (Some(start), Some(end)) if end <= start => {
+            Err(ValidationError::new("invalid_comment_range")
+                .with_message(Cow::Borrowed("The end of a range must follow its start")))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Commento di un video con gli utenti menzionati
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VideoCommentDetails {
+    #[serde(flatten)]
+    pub comment: VideoComment,
+    /// ID degli utenti menzionati nel commento
+    pub mentions: Vec<i64>,
+}
+
+/// Discussione di un video: il primo commento con le risposte, in ordine di inserimento
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VideoCommentThread {
+    #[serde(flatten)]
+    pub comment: VideoCommentDetails,
+    pub replies: Vec<VideoCommentDetails>,
 }
@@ -14,6 +14,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Annotazioni tracciate sugli screenshot e sui fotogrammi dei segnaposti
+    annotation (id) {
+        /// The `id` column of the `annotation` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// Screenshot sul quale è tracciata l'annotazione
+        screenshot_id -> Nullable<Bigint>,
+        /// Segnaposto sul cui fotogramma è tracciata l'annotazione
+        time_marker_id -> Nullable<Bigint>,
+        /// Utente che ha tracciato l'annotazione
+        author_id -> Bigint,
+        /// Descrizione dell'annotazione
+        #[max_length = 64]
+        label -> Nullable<Varchar>,
+        /// Forme dell'annotazione in formato JSON, con coordinate relative alle dimensioni del fotogramma
+        shapes -> Text,
+        /// The `created_at` column of the `annotation` table.
+        ///
+        /// Its SQL type is `Datetime`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Datetime,
+        /// The `updated_at` column of the `annotation` table.
+        ///
+        /// Its SQL type is `Datetime`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Datetime,
+    }
+}
+
 diesel::table! {
     /// Representation of the `booking` table.
     ///
@@ -77,9 +112,17 @@ diesel::table! {
         /// Credenziali per connettersi alla camera
         #[max_length = 255]
         username -> Varchar,
-        /// Credenziali per connettersi alla camera
-        #[max_length = 255]
+        /// Credenziali per connettersi alla camera, cifrate
+        #[max_length = 1024]
         password -> Varchar,
+        /// Nome della telecamera
+        #[max_length = 64]
+        name -> Varchar,
+        /// Descrizione della posizione della telecamera nella struttura
+        #[max_length = 255]
+        location_description -> Nullable<Varchar>,
+        /// Le telecamere disabilitate non vengono utilizzate per le stream e le registrazioni
+        enabled -> Bool,
     }
 }
 
@@ -108,6 +151,8 @@ diesel::table! {
         original_video_id -> Bigint,
         /// ID della clip estratta
         clip_id -> Bigint,
+        /// Istante del video originale nel quale inizia la clip, NULL per i video di highlight
+        start_instant -> Nullable<Time>,
     }
 }
 
@@ -283,6 +328,69 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Richieste di compilazione di un video di highlight
+    highlight_job (id) {
+        /// The `id` column of the `highlight_job` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// Utente che ha richiesto il video di highlight
+        user_id -> Bigint,
+        /// Nome del video di highlight
+        #[max_length = 64]
+        name -> Varchar,
+        /// Note del video di highlight
+        notes -> Nullable<Text>,
+        /// Stato della compilazione: pending, running, completed o failed
+        #[max_length = 16]
+        status -> Varchar,
+        /// Video di highlight prodotto, NULL finché la compilazione non è completata
+        video_id -> Nullable<Bigint>,
+        /// Motivo per il quale la compilazione non è riuscita
+        error -> Nullable<Text>,
+        /// The `created_at` column of the `highlight_job` table.
+        ///
+        /// Its SQL type is `Datetime`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Datetime,
+        /// Momento nel quale la compilazione è stata completata o non è riuscita
+        completed_at -> Nullable<Datetime>,
+    }
+}
+
+diesel::table! {
+    /// Parti dei video che compongono un video di highlight
+    highlight_segment (id) {
+        /// The `id` column of the `highlight_segment` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// Richiesta di compilazione alla quale appartiene questa parte
+        job_id -> Bigint,
+        /// Posizione della parte nel video di highlight, a partire da 0
+        position -> Integer,
+        /// ID del video dal quale viene estratta questa parte
+        video_id -> Bigint,
+        /// Istante del video nel quale inizia la parte, compreso il pre-roll
+        start_instant -> Time,
+        /// Istante del video nel quale termina la parte, compreso il post-roll
+        end_instant -> Time,
+        /// Titolo mostrato prima della parte, NULL per non mostrare un titolo
+        #[max_length = 64]
+        title -> Nullable<Varchar>,
+        /// Segnaposto dal quale è stata ottenuta la parte
+        time_marker_id -> Nullable<Bigint>,
+        /// Clip dalla quale è stata ottenuta la parte
+        clip_id -> Nullable<Bigint>,
+    }
+}
+
 diesel::table! {
     /// Riferimento a dove vengono memorizzate le informazioni ottenute dai sensori
     influxdb_bucket (location) {
@@ -306,12 +414,8 @@ diesel::table! {
         /// (Automatically generated by Diesel.)
         #[max_length = 64]
         name -> Varchar,
-        /// The `token` column of the `influxdb_bucket` table.
-        ///
-        /// Its SQL type is `Varchar`.
-        ///
-        /// (Automatically generated by Diesel.)
-        #[max_length = 255]
+        /// Token per connettersi al bucket, cifrato
+        #[max_length = 1024]
         token -> Varchar,
         /// The `org` column of the `influxdb_bucket` table.
         ///
@@ -422,6 +526,28 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Per quanto tempo vengono conservati i video, prima di essere eliminati automaticamente
+    retention_rule (id) {
+        /// The `id` column of the `retention_rule` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// Tipo di video al quale si applica la regola: recording (registrazioni delle telecamere) o clip
+        #[max_length = 16]
+        video_kind -> Varchar,
+        /// Giorni per i quali i video vengono conservati dalla loro data, NULL per conservarli per sempre
+        retention_days -> Nullable<Integer>,
+        /// Società sportiva alla quale si applica la regola, NULL per le regole globali o di una squadra
+        #[max_length = 11]
+        club_id -> Nullable<Varchar>,
+        /// Squadra alla quale si applica la regola, NULL per le regole globali o di una società sportiva
+        team_id -> Nullable<Bigint>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `rfid_tag` table.
     ///
@@ -528,6 +654,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Quote di spazio per i video delle società sportive e delle squadre
+    storage_quota (id) {
+        /// The `id` column of the `storage_quota` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// Società sportiva alla quale si applica la quota
+        #[max_length = 11]
+        club_id -> Nullable<Varchar>,
+        /// Squadra alla quale si applica la quota
+        team_id -> Nullable<Bigint>,
+        /// Spazio massimo in byte occupato dai video
+        quota_bytes -> Bigint,
+    }
+}
+
 diesel::table! {
     /// Representation of the `team` table.
     ///
@@ -587,6 +732,20 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         notes -> Nullable<Text>,
+        /// Squadra alla quale si riferisce il segnaposto
+        team_id -> Nullable<Bigint>,
+        /// Formazione della partita del video alla quale si riferisce il segnaposto
+        formation_id -> Nullable<Bigint>,
+    }
+}
+
+diesel::table! {
+    /// Giocatori ai quali si riferiscono i segnaposti dei video
+    time_marker_player (time_marker_id, player_id) {
+        /// Segnaposto che indica il momento
+        time_marker_id -> Bigint,
+        /// Giocatore coinvolto nel momento
+        player_id -> Bigint,
     }
 }
 
@@ -708,7 +867,7 @@ diesel::table! {
         /// (Automatically generated by Diesel.)
         #[max_length = 64]
         phone -> Nullable<Varchar>,
-        /// Posizione dell'immagine del profilo di questo utente
+        /// Chiave dell'immagine del profilo di questo utente nell'archivio dei file multimediali
         #[max_length = 255]
         profile_image_location -> Varchar,
         /// Se questo utente è stato verificato tramite email
@@ -794,6 +953,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         date -> Datetime,
+        /// Momento, al millisecondo, nel quale è stata registrata la prima immagine del video
+        capture_start -> Nullable<Datetime>,
         /// The `notes` column of the `video` table.
         ///
         /// Its SQL type is `Nullable<Text>`.
@@ -804,6 +965,222 @@ diesel::table! {
         session_id -> Bigint,
         /// ID della camera che ha registrato questo video
         camera_id -> Bigint,
+        /// Chiave dell'immagine di copertina del video nell'archivio dei file multimediali
+        #[max_length = 255]
+        poster_location -> Nullable<Varchar>,
+        /// Chiave dell'immagine con le miniature del video a intervalli regolari
+        #[max_length = 255]
+        sprite_location -> Nullable<Varchar>,
+        /// Chiave della traccia WebVTT che associa a ogni intervallo del video la sua miniatura
+        #[max_length = 255]
+        thumbnails_location -> Nullable<Varchar>,
+        /// Chiave del manifest DASH del video on demand
+        #[max_length = 255]
+        vod_location -> Nullable<Varchar>,
+        /// I video fissati non vengono eliminati dalle regole di conservazione
+        pinned -> Bool,
+        /// Momento dal quale il video può essere eliminato secondo le regole di conservazione, NULL se viene conservato per sempre
+        expires_at -> Nullable<Datetime>,
+        /// Momento nel quale i proprietari sono stati avvisati della prossima eliminazione del video
+        expiration_warned_at -> Nullable<Datetime>,
+        /// Durata del video in millisecondi
+        duration_ms -> Nullable<Bigint>,
+        /// Larghezza in pixel della traccia video
+        width -> Nullable<Integer>,
+        /// Altezza in pixel della traccia video
+        height -> Nullable<Integer>,
+        /// Fotogrammi al secondo della traccia video
+        frame_rate -> Nullable<Double>,
+        /// Codec della traccia video
+        #[max_length = 32]
+        video_codec -> Nullable<Varchar>,
+        /// Codec della traccia audio, NULL se il video non ha audio
+        #[max_length = 32]
+        audio_codec -> Nullable<Varchar>,
+        /// Bitrate complessivo del video in bit al secondo
+        bit_rate -> Nullable<Bigint>,
+        /// Dimensione in byte del file del video
+        file_size -> Nullable<Bigint>,
+    }
+}
+
+diesel::table! {
+    /// Rappresenta la condivisione di un video con una società sportiva, valida per i suoi responsabili e per i giocatori e gli allenatori attuali delle sue squadre
+    video_club (video_id, club_id) {
+        /// The `video_id` column of the `video_club` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        video_id -> Bigint,
+        /// The `club_id` column of the `video_club` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        club_id -> Varchar,
+        /// The `read` column of the `video_club` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        read -> Bool,
+        /// The `edit` column of the `video_club` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        edit -> Bool,
+        /// The `delete` column of the `video_club` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        delete -> Bool,
+        /// The `share` column of the `video_club` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        share -> Bool,
+    }
+}
+
+diesel::table! {
+    /// Commenti dei video, organizzati in discussioni
+    video_comment (id) {
+        /// The `id` column of the `video_comment` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// The `video_id` column of the `video_comment` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        video_id -> Bigint,
+        /// Utente che ha scritto il commento
+        author_id -> Bigint,
+        /// Primo commento della discussione alla quale risponde il commento, NULL per il primo commento di una discussione
+        parent_id -> Nullable<Bigint>,
+        /// Istante del video al quale si riferisce la discussione, NULL per una discussione sul video intero
+        start_instant -> Nullable<Time>,
+        /// Fine dell'intervallo del video al quale si riferisce la discussione, NULL per un singolo istante
+        end_instant -> Nullable<Time>,
+        /// Testo del commento
+        body -> Text,
+        /// Se la discussione è stata risolta, solo per il primo commento di una discussione
+        resolved -> Bool,
+        /// Utente che ha risolto la discussione
+        resolved_by -> Nullable<Bigint>,
+        /// Momento nel quale la discussione è stata risolta
+        resolved_at -> Nullable<Datetime>,
+        /// The `created_at` column of the `video_comment` table.
+        ///
+        /// Its SQL type is `Datetime`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Datetime,
+        /// Momento dell'ultima modifica del testo, NULL se non è mai stato modificato
+        edited_at -> Nullable<Datetime>,
+    }
+}
+
+diesel::table! {
+    /// Utenti menzionati nei commenti dei video
+    video_comment_mention (comment_id, user_id) {
+        /// The `comment_id` column of the `video_comment_mention` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        comment_id -> Bigint,
+        /// Utente menzionato nel commento
+        user_id -> Bigint,
+    }
+}
+
+diesel::table! {
+    /// Cronologia delle modifiche dei commenti dei video
+    video_comment_revision (id) {
+        /// The `id` column of the `video_comment_revision` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bigint,
+        /// The `comment_id` column of the `video_comment_revision` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        comment_id -> Bigint,
+        /// Testo del commento prima della modifica
+        body -> Text,
+        /// Momento nel quale il testo è stato sostituito
+        replaced_at -> Datetime,
+    }
+}
+
+diesel::table! {
+    /// Avvisi agli utenti della prossima eliminazione dei video
+    video_expiration_notice (video_id, user_id) {
+        /// The `video_id` column of the `video_expiration_notice` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        video_id -> Bigint,
+        /// Utente avvisato, proprietario del video o con accesso al video tramite una condivisione
+        user_id -> Bigint,
+        /// Scadenza del video al momento dell'avviso
+        expires_at -> Datetime,
+        /// Momento nel quale è stato registrato l'avviso
+        notified_at -> Datetime,
+    }
+}
+
+diesel::table! {
+    /// Rappresenta la condivisione di un video con una squadra, valida per i suoi giocatori e allenatori attuali
+    video_team (video_id, team_id) {
+        /// The `video_id` column of the `video_team` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        video_id -> Bigint,
+        /// The `team_id` column of the `video_team` table.
+        ///
+        /// Its SQL type is `Bigint`.
+        ///
+        /// (Automatically generated by Diesel.)
+        team_id -> Bigint,
+        /// The `read` column of the `video_team` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        read -> Bool,
+        /// The `edit` column of the `video_team` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        edit -> Bool,
+        /// The `delete` column of the `video_team` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        delete -> Bool,
+        /// The `share` column of the `video_team` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        share -> Bool,
     }
 }
 
@@ -856,6 +1233,9 @@ diesel::table! {
 }
 
 diesel::joinable!(administrator -> person (person_id));
+diesel::joinable!(annotation -> screenshot (screenshot_id));
+diesel::joinable!(annotation -> time_marker (time_marker_id));
+diesel::joinable!(annotation -> user (author_id));
 diesel::joinable!(booking -> sport (sport));
 diesel::joinable!(booking -> user (author_id));
 diesel::joinable!(camera_session -> camera (camera_id));
@@ -872,16 +1252,28 @@ diesel::joinable!(formation_player_tag -> formation (formation_id));
 diesel::joinable!(formation_player_tag -> player (player_id));
 diesel::joinable!(formation_player_tag -> rfid_tag (rfid_tag_id));
 diesel::joinable!(game -> booking (booking_id));
+diesel::joinable!(highlight_job -> user (user_id));
+diesel::joinable!(highlight_job -> video (video_id));
+diesel::joinable!(highlight_segment -> highlight_job (job_id));
+diesel::joinable!(highlight_segment -> time_marker (time_marker_id));
 diesel::joinable!(influxdb_bucket -> team (team_id));
 diesel::joinable!(player -> person (person_id));
 diesel::joinable!(player_team -> player (player_id));
 diesel::joinable!(player_team -> team (team_id));
 diesel::joinable!(recording_session -> booking (booking_id));
 diesel::joinable!(recording_session -> user (author_id));
+diesel::joinable!(retention_rule -> sports_club (club_id));
+diesel::joinable!(retention_rule -> team (team_id));
 diesel::joinable!(screenshot -> video (video_id));
+diesel::joinable!(storage_quota -> sports_club (club_id));
+diesel::joinable!(storage_quota -> team (team_id));
 diesel::joinable!(team -> sport (sport));
 diesel::joinable!(team -> sports_club (club_id));
+diesel::joinable!(time_marker -> formation (formation_id));
+diesel::joinable!(time_marker -> team (team_id));
 diesel::joinable!(time_marker -> video (video_id));
+diesel::joinable!(time_marker_player -> player (player_id));
+diesel::joinable!(time_marker_player -> time_marker (time_marker_id));
 diesel::joinable!(training -> booking (booking_id));
 diesel::joinable!(training -> team (team_id));
 diesel::joinable!(training_player -> player (player_id));
@@ -895,11 +1287,22 @@ diesel::joinable!(user_club -> user (user_id));
 diesel::joinable!(user_invitation -> person (person_id));
 diesel::joinable!(video -> camera (camera_id));
 diesel::joinable!(video -> recording_session (session_id));
+diesel::joinable!(video_club -> sports_club (club_id));
+diesel::joinable!(video_club -> video (video_id));
+diesel::joinable!(video_comment -> video (video_id));
+diesel::joinable!(video_comment_mention -> user (user_id));
+diesel::joinable!(video_comment_mention -> video_comment (comment_id));
+diesel::joinable!(video_comment_revision -> video_comment (comment_id));
+diesel::joinable!(video_expiration_notice -> user (user_id));
+diesel::joinable!(video_expiration_notice -> video (video_id));
+diesel::joinable!(video_team -> team (team_id));
+diesel::joinable!(video_team -> video (video_id));
 diesel::joinable!(video_user -> user (user_id));
 diesel::joinable!(video_user -> video (video_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     administrator,
+    annotation,
     booking,
     camera,
     camera_session,
@@ -912,17 +1315,22 @@ diesel::allow_tables_to_appear_in_same_query!(
     formation_player,
     formation_player_tag,
     game,
+    highlight_job,
+    highlight_segment,
     influxdb_bucket,
     person,
     player,
     player_team,
     recording_session,
+    retention_rule,
     rfid_tag,
     screenshot,
     sport,
     sports_club,
+    storage_quota,
     team,
     time_marker,
+    time_marker_player,
     training,
     training_player,
     training_player_tag,
@@ -930,5 +1338,11 @@ diesel::allow_tables_to_appear_in_same_query!(
     user_club,
     user_invitation,
     video,
+    video_club,
+    video_comment,
+    video_comment_mention,
+    video_comment_revision,
+    video_expiration_notice,
+    video_team,
     video_user,
 );
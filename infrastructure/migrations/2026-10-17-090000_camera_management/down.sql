@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE `camera` DROP COLUMN `enabled`;
+ALTER TABLE `camera` DROP COLUMN `location_description`;
+ALTER TABLE `camera` DROP COLUMN `name`;
+
+SET FOREIGN_KEY_CHECKS = 0;
+ALTER TABLE `camera` MODIFY `id` bigint(20) NOT NULL;
+SET FOREIGN_KEY_CHECKS = 1;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- Gli ID delle telecamere vengono generati dal database, come per le altre tabelle
+SET FOREIGN_KEY_CHECKS = 0;
+ALTER TABLE `camera` MODIFY `id` bigint(20) NOT NULL AUTO_INCREMENT;
+SET FOREIGN_KEY_CHECKS = 1;
+
+ALTER TABLE `camera` ADD COLUMN `name` VARCHAR(64) NOT NULL COMMENT 'Nome della telecamera';
+ALTER TABLE `camera` ADD COLUMN `location_description` VARCHAR(255) DEFAULT NULL COMMENT 'Descrizione della posizione della telecamera nella struttura';
+ALTER TABLE `camera` ADD COLUMN `enabled` BOOLEAN NOT NULL DEFAULT TRUE COMMENT 'Le telecamere disabilitate non vengono utilizzate per le stream e le registrazioni';
+
+UPDATE `camera` SET `name` = CONCAT('Telecamera ', `id`);
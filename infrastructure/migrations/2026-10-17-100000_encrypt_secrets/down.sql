@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE `camera` MODIFY `password` VARCHAR(255) NOT NULL COMMENT 'Credenziali per connettersi alla camera';
+ALTER TABLE `influxdb_bucket` MODIFY `token` VARCHAR(255) NOT NULL;
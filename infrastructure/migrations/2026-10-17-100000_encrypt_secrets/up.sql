@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Le credenziali vengono salvate cifrate, il valore cifrato è più lungo di quello originale
+ALTER TABLE `camera` MODIFY `password` VARCHAR(1024) NOT NULL COMMENT 'Credenziali per connettersi alla camera, cifrate';
+ALTER TABLE `influxdb_bucket` MODIFY `token` VARCHAR(1024) NOT NULL COMMENT 'Token per connettersi al bucket, cifrato';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE `video` DROP COLUMN `thumbnails_location`;
+ALTER TABLE `video` DROP COLUMN `sprite_location`;
+ALTER TABLE `video` DROP COLUMN `poster_location`;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+-- Anteprime generate al termine della registrazione, mostrate dai player durante lo scorrimento del video
+ALTER TABLE `video` ADD COLUMN `poster_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso dell''immagine di copertina del video';
+ALTER TABLE `video` ADD COLUMN `sprite_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso dell''immagine con le miniature del video a intervalli regolari';
+ALTER TABLE `video` ADD COLUMN `thumbnails_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso della traccia WebVTT che associa a ogni intervallo del video la sua miniatura';
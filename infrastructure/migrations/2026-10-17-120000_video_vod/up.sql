@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Al termine della sessione di registrazione il video viene pubblicato anche in formato DASH on demand
+ALTER TABLE `video` ADD COLUMN `vod_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso del manifest DASH del video on demand';
@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE `video` MODIFY `poster_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso dell''immagine di copertina del video';
+ALTER TABLE `video` MODIFY `sprite_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso dell''immagine con le miniature del video a intervalli regolari';
+ALTER TABLE `video` MODIFY `thumbnails_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso della traccia WebVTT che associa a ogni intervallo del video la sua miniatura';
+ALTER TABLE `video` MODIFY `vod_location` VARCHAR(255) DEFAULT NULL COMMENT 'Percorso del manifest DASH del video on demand';
+
+-- Le chiavi tornano a essere percorsi nella cartella predefinita dei file multimediali
+UPDATE `video` SET `file_location` = CONCAT('./infrastructure/media/', `file_location`) WHERE `file_location` NOT LIKE './%' AND `file_location` NOT LIKE '/%';
+UPDATE `video` SET `poster_location` = CONCAT('./infrastructure/media/', `poster_location`) WHERE `poster_location` NOT LIKE './%' AND `poster_location` NOT LIKE '/%';
+UPDATE `video` SET `sprite_location` = CONCAT('./infrastructure/media/', `sprite_location`) WHERE `sprite_location` NOT LIKE './%' AND `sprite_location` NOT LIKE '/%';
+UPDATE `video` SET `thumbnails_location` = CONCAT('./infrastructure/media/', `thumbnails_location`) WHERE `thumbnails_location` NOT LIKE './%' AND `thumbnails_location` NOT LIKE '/%';
+UPDATE `video` SET `vod_location` = CONCAT('./infrastructure/media/', `vod_location`) WHERE `vod_location` NOT LIKE './%' AND `vod_location` NOT LIKE '/%';
+UPDATE `screenshot` SET `file_location` = CONCAT('./infrastructure/media/', `file_location`) WHERE `file_location` NOT LIKE './%' AND `file_location` NOT LIKE '/%';
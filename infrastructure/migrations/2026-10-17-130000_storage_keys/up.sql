@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- I file multimediali sono identificati dalla loro chiave nell'archivio, relativa alla cartella dei file multimediali,
+-- invece che dal loro percorso sul disco. I percorsi salvati in precedenza nella cartella predefinita
+-- (./infrastructure/media) vengono convertiti in chiavi.
+UPDATE `video` SET `file_location` = SUBSTRING(`file_location`, 24) WHERE `file_location` LIKE './infrastructure/media/%';
+UPDATE `video` SET `poster_location` = SUBSTRING(`poster_location`, 24) WHERE `poster_location` LIKE './infrastructure/media/%';
+UPDATE `video` SET `sprite_location` = SUBSTRING(`sprite_location`, 24) WHERE `sprite_location` LIKE './infrastructure/media/%';
+UPDATE `video` SET `thumbnails_location` = SUBSTRING(`thumbnails_location`, 24) WHERE `thumbnails_location` LIKE './infrastructure/media/%';
+UPDATE `video` SET `vod_location` = SUBSTRING(`vod_location`, 24) WHERE `vod_location` LIKE './infrastructure/media/%';
+UPDATE `screenshot` SET `file_location` = SUBSTRING(`file_location`, 24) WHERE `file_location` LIKE './infrastructure/media/%';
+
+ALTER TABLE `video` MODIFY `poster_location` VARCHAR(255) DEFAULT NULL COMMENT 'Chiave dell''immagine di copertina del video nell''archivio dei file multimediali';
+ALTER TABLE `video` MODIFY `sprite_location` VARCHAR(255) DEFAULT NULL COMMENT 'Chiave dell''immagine con le miniature del video a intervalli regolari';
+ALTER TABLE `video` MODIFY `thumbnails_location` VARCHAR(255) DEFAULT NULL COMMENT 'Chiave della traccia WebVTT che associa a ogni intervallo del video la sua miniatura';
+ALTER TABLE `video` MODIFY `vod_location` VARCHAR(255) DEFAULT NULL COMMENT 'Chiave del manifest DASH del video on demand';
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE `video` DROP COLUMN `expiration_warned_at`;
+ALTER TABLE `video` DROP COLUMN `expires_at`;
+ALTER TABLE `video` DROP COLUMN `pinned`;
+
+DROP TABLE `storage_quota`;
+DROP TABLE `retention_rule`;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+
+-- Regole di conservazione dei video, globali o specifiche di una società sportiva o di una squadra
+CREATE TABLE `retention_rule` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `video_kind` varchar(16) NOT NULL COMMENT 'Tipo di video al quale si applica la regola: recording (registrazioni delle telecamere) o clip',
+  `retention_days` int(11) DEFAULT NULL COMMENT 'Giorni per i quali i video vengono conservati dalla loro data, NULL per conservarli per sempre',
+  `club_id` varchar(11) DEFAULT NULL COMMENT 'Società sportiva alla quale si applica la regola, NULL per le regole globali o di una squadra',
+  `team_id` bigint(20) DEFAULT NULL COMMENT 'Squadra alla quale si applica la regola, NULL per le regole globali o di una società sportiva',
+  PRIMARY KEY (`id`),
+  KEY `retention_rule_club_id_fk` (`club_id`),
+  KEY `retention_rule_team_id_fk` (`team_id`),
+  CONSTRAINT `retention_rule_club_id_fk` FOREIGN KEY (`club_id`) REFERENCES `sports_club` (`vat_number`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `retention_rule_team_id_fk` FOREIGN KEY (`team_id`) REFERENCES `team` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `retention_rule_video_kind_check` CHECK (`video_kind` IN ('recording', 'clip')),
+  CONSTRAINT `retention_rule_days_check` CHECK (`retention_days` IS NULL OR `retention_days` > 0),
+  CONSTRAINT `retention_rule_scope_check` CHECK (`club_id` IS NULL OR `team_id` IS NULL)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Per quanto tempo vengono conservati i video, prima di essere eliminati automaticamente';
+
+-- Spazio massimo occupato dai video di una società sportiva o di una squadra
+CREATE TABLE `storage_quota` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `club_id` varchar(11) DEFAULT NULL COMMENT 'Società sportiva alla quale si applica la quota',
+  `team_id` bigint(20) DEFAULT NULL COMMENT 'Squadra alla quale si applica la quota',
+  `quota_bytes` bigint(20) NOT NULL COMMENT 'Spazio massimo in byte occupato dai video',
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `storage_quota_club_id_uk` (`club_id`),
+  UNIQUE KEY `storage_quota_team_id_uk` (`team_id`),
+  CONSTRAINT `storage_quota_club_id_fk` FOREIGN KEY (`club_id`) REFERENCES `sports_club` (`vat_number`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `storage_quota_team_id_fk` FOREIGN KEY (`team_id`) REFERENCES `team` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `storage_quota_bytes_check` CHECK (`quota_bytes` >= 0),
+  CONSTRAINT `storage_quota_scope_check` CHECK ((`club_id` IS NULL) <> (`team_id` IS NULL))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Quote di spazio per i video delle società sportive e delle squadre';
+
+ALTER TABLE `video` ADD COLUMN `pinned` BOOLEAN NOT NULL DEFAULT FALSE COMMENT 'I video fissati non vengono eliminati dalle regole di conservazione';
+ALTER TABLE `video` ADD COLUMN `expires_at` DATETIME DEFAULT NULL COMMENT 'Momento dal quale il video può essere eliminato secondo le regole di conservazione, NULL se viene conservato per sempre';
+ALTER TABLE `video` ADD COLUMN `expiration_warned_at` DATETIME DEFAULT NULL COMMENT 'Momento nel quale i proprietari sono stati avvisati della prossima eliminazione del video';
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE `video` DROP COLUMN `file_size`;
+ALTER TABLE `video` DROP COLUMN `bit_rate`;
+ALTER TABLE `video` DROP COLUMN `audio_codec`;
+ALTER TABLE `video` DROP COLUMN `video_codec`;
+ALTER TABLE `video` DROP COLUMN `frame_rate`;
+ALTER TABLE `video` DROP COLUMN `height`;
+ALTER TABLE `video` DROP COLUMN `width`;
+ALTER TABLE `video` DROP COLUMN `duration_ms`;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- Caratteristiche tecniche dei video, lette con FFprobe quando il video viene registrato
+ALTER TABLE `video` ADD COLUMN `duration_ms` BIGINT DEFAULT NULL COMMENT 'Durata del video in millisecondi';
+ALTER TABLE `video` ADD COLUMN `width` INT DEFAULT NULL COMMENT 'Larghezza in pixel della traccia video';
+ALTER TABLE `video` ADD COLUMN `height` INT DEFAULT NULL COMMENT 'Altezza in pixel della traccia video';
+ALTER TABLE `video` ADD COLUMN `frame_rate` DOUBLE DEFAULT NULL COMMENT 'Fotogrammi al secondo della traccia video';
+ALTER TABLE `video` ADD COLUMN `video_codec` VARCHAR(32) DEFAULT NULL COMMENT 'Codec della traccia video';
+ALTER TABLE `video` ADD COLUMN `audio_codec` VARCHAR(32) DEFAULT NULL COMMENT 'Codec della traccia audio, NULL se il video non ha audio';
+ALTER TABLE `video` ADD COLUMN `bit_rate` BIGINT DEFAULT NULL COMMENT 'Bitrate complessivo del video in bit al secondo';
+ALTER TABLE `video` ADD COLUMN `file_size` BIGINT DEFAULT NULL COMMENT 'Dimensione in byte del file del video';
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE `highlight_segment`;
+DROP TABLE `highlight_job`;
@@ -0,0 +1,43 @@
+-- Your SQL goes here
+
+-- Richieste di compilazione di un video di highlight a partire da segnaposti e clip
+CREATE TABLE `highlight_job` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `user_id` bigint(20) NOT NULL COMMENT 'Utente che ha richiesto il video di highlight',
+  `name` varchar(64) NOT NULL COMMENT 'Nome del video di highlight',
+  `notes` text DEFAULT NULL COMMENT 'Note del video di highlight',
+  `status` varchar(16) NOT NULL DEFAULT 'pending' COMMENT 'Stato della compilazione: pending, running, completed o failed',
+  `video_id` bigint(20) DEFAULT NULL COMMENT 'Video di highlight prodotto, NULL finché la compilazione non è completata',
+  `error` text DEFAULT NULL COMMENT 'Motivo per il quale la compilazione non è riuscita',
+  `created_at` datetime NOT NULL,
+  `completed_at` datetime DEFAULT NULL COMMENT 'Momento nel quale la compilazione è stata completata o non è riuscita',
+  PRIMARY KEY (`id`),
+  KEY `highlight_job_user_id_fk` (`user_id`),
+  KEY `highlight_job_video_id_fk` (`video_id`),
+  CONSTRAINT `highlight_job_user_id_fk` FOREIGN KEY (`user_id`) REFERENCES `user` (`person_id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `highlight_job_video_id_fk` FOREIGN KEY (`video_id`) REFERENCES `video` (`id`) ON DELETE SET NULL ON UPDATE CASCADE,
+  CONSTRAINT `highlight_job_status_check` CHECK (`status` IN ('pending', 'running', 'completed', 'failed'))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Richieste di compilazione di un video di highlight';
+
+-- Parti dei video che compongono un video di highlight, nell'ordine nel quale vengono riprodotte
+CREATE TABLE `highlight_segment` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `job_id` bigint(20) NOT NULL COMMENT 'Richiesta di compilazione alla quale appartiene questa parte',
+  `position` int(11) NOT NULL COMMENT 'Posizione della parte nel video di highlight, a partire da 0',
+  `video_id` bigint(20) NOT NULL COMMENT 'ID del video dal quale viene estratta questa parte',
+  `start_instant` time(3) NOT NULL COMMENT 'Istante del video nel quale inizia la parte, compreso il pre-roll',
+  `end_instant` time(3) NOT NULL COMMENT 'Istante del video nel quale termina la parte, compreso il post-roll',
+  `title` varchar(64) DEFAULT NULL COMMENT 'Titolo mostrato prima della parte, NULL per non mostrare un titolo',
+  `time_marker_id` bigint(20) DEFAULT NULL COMMENT 'Segnaposto dal quale è stata ottenuta la parte',
+  `clip_id` bigint(20) DEFAULT NULL COMMENT 'Clip dalla quale è stata ottenuta la parte',
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `highlight_segment_position_uk` (`job_id`, `position`),
+  KEY `highlight_segment_video_id_fk` (`video_id`),
+  KEY `highlight_segment_time_marker_id_fk` (`time_marker_id`),
+  KEY `highlight_segment_clip_id_fk` (`clip_id`),
+  CONSTRAINT `highlight_segment_job_id_fk` FOREIGN KEY (`job_id`) REFERENCES `highlight_job` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `highlight_segment_video_id_fk` FOREIGN KEY (`video_id`) REFERENCES `video` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `highlight_segment_time_marker_id_fk` FOREIGN KEY (`time_marker_id`) REFERENCES `time_marker` (`id`) ON DELETE SET NULL ON UPDATE CASCADE,
+  CONSTRAINT `highlight_segment_clip_id_fk` FOREIGN KEY (`clip_id`) REFERENCES `video` (`id`) ON DELETE SET NULL ON UPDATE CASCADE,
+  CONSTRAINT `highlight_segment_period_check` CHECK (`start_instant` < `end_instant`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Parti dei video che compongono un video di highlight';
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+
+-- Disegni (frecce, cerchi, zone e percorsi) tracciati sugli screenshot o sui fotogrammi dei segnaposti
+CREATE TABLE `annotation` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `screenshot_id` bigint(20) DEFAULT NULL COMMENT 'Screenshot sul quale è tracciata l''annotazione',
+  `time_marker_id` bigint(20) DEFAULT NULL COMMENT 'Segnaposto sul cui fotogramma è tracciata l''annotazione',
+  `author_id` bigint(20) NOT NULL COMMENT 'Utente che ha tracciato l''annotazione',
+  `label` varchar(64) DEFAULT NULL COMMENT 'Descrizione dell''annotazione',
+  `shapes` text NOT NULL COMMENT 'Forme dell''annotazione in formato JSON, con coordinate relative alle dimensioni del fotogramma',
+  `created_at` datetime NOT NULL,
+  `updated_at` datetime NOT NULL,
+  PRIMARY KEY (`id`),
+  KEY `annotation_screenshot_id_fk` (`screenshot_id`),
+  KEY `annotation_time_marker_id_fk` (`time_marker_id`),
+  KEY `annotation_author_id_fk` (`author_id`),
+  CONSTRAINT `annotation_screenshot_id_fk` FOREIGN KEY (`screenshot_id`) REFERENCES `screenshot` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `annotation_time_marker_id_fk` FOREIGN KEY (`time_marker_id`) REFERENCES `time_marker` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `annotation_author_id_fk` FOREIGN KEY (`author_id`) REFERENCES `user` (`person_id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `annotation_target_check` CHECK ((`screenshot_id` IS NULL) <> (`time_marker_id` IS NULL)),
+  CONSTRAINT `annotation_shapes_check` CHECK (JSON_VALID(`shapes`))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Annotazioni tracciate sugli screenshot e sui fotogrammi dei segnaposti';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE `video_comment_revision`;
+DROP TABLE `video_comment_mention`;
+DROP TABLE `video_comment`;
@@ -0,0 +1,49 @@
+-- Your SQL goes here
+
+-- Commenti dei video, organizzati in discussioni ancorate a un istante o a un intervallo del video
+CREATE TABLE `video_comment` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `video_id` bigint(20) NOT NULL,
+  `author_id` bigint(20) NOT NULL COMMENT 'Utente che ha scritto il commento',
+  `parent_id` bigint(20) DEFAULT NULL COMMENT 'Primo commento della discussione alla quale risponde il commento, NULL per il primo commento di una discussione',
+  `start_instant` time(3) DEFAULT NULL COMMENT 'Istante del video al quale si riferisce la discussione, NULL per una discussione sul video intero',
+  `end_instant` time(3) DEFAULT NULL COMMENT 'Fine dell''intervallo del video al quale si riferisce la discussione, NULL per un singolo istante',
+  `body` text NOT NULL COMMENT 'Testo del commento',
+  `resolved` tinyint(1) NOT NULL DEFAULT 0 COMMENT 'Se la discussione è stata risolta, solo per il primo commento di una discussione',
+  `resolved_by` bigint(20) DEFAULT NULL COMMENT 'Utente che ha risolto la discussione',
+  `resolved_at` datetime DEFAULT NULL COMMENT 'Momento nel quale la discussione è stata risolta',
+  `created_at` datetime NOT NULL,
+  `edited_at` datetime DEFAULT NULL COMMENT 'Momento dell''ultima modifica del testo, NULL se non è mai stato modificato',
+  PRIMARY KEY (`id`),
+  KEY `video_comment_video_id_fk` (`video_id`),
+  KEY `video_comment_author_id_fk` (`author_id`),
+  KEY `video_comment_parent_id_fk` (`parent_id`),
+  KEY `video_comment_resolved_by_fk` (`resolved_by`),
+  CONSTRAINT `video_comment_video_id_fk` FOREIGN KEY (`video_id`) REFERENCES `video` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_comment_author_id_fk` FOREIGN KEY (`author_id`) REFERENCES `user` (`person_id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_comment_parent_id_fk` FOREIGN KEY (`parent_id`) REFERENCES `video_comment` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_comment_resolved_by_fk` FOREIGN KEY (`resolved_by`) REFERENCES `user` (`person_id`) ON DELETE SET NULL ON UPDATE CASCADE,
+  CONSTRAINT `video_comment_instant_check` CHECK (`end_instant` IS NULL OR (`start_instant` IS NOT NULL AND `end_instant` > `start_instant`)),
+  CONSTRAINT `video_comment_reply_check` CHECK (`parent_id` IS NULL OR (`start_instant` IS NULL AND `resolved` = 0))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Commenti dei video, organizzati in discussioni';
+
+-- Utenti menzionati nei commenti
+CREATE TABLE `video_comment_mention` (
+  `comment_id` bigint(20) NOT NULL,
+  `user_id` bigint(20) NOT NULL COMMENT 'Utente menzionato nel commento',
+  PRIMARY KEY (`comment_id`, `user_id`),
+  KEY `video_comment_mention_user_id_fk` (`user_id`),
+  CONSTRAINT `video_comment_mention_comment_id_fk` FOREIGN KEY (`comment_id`) REFERENCES `video_comment` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_comment_mention_user_id_fk` FOREIGN KEY (`user_id`) REFERENCES `user` (`person_id`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Utenti menzionati nei commenti dei video';
+
+-- Testi precedenti dei commenti modificati
+CREATE TABLE `video_comment_revision` (
+  `id` bigint(20) NOT NULL AUTO_INCREMENT,
+  `comment_id` bigint(20) NOT NULL,
+  `body` text NOT NULL COMMENT 'Testo del commento prima della modifica',
+  `replaced_at` datetime NOT NULL COMMENT 'Momento nel quale il testo è stato sostituito',
+  PRIMARY KEY (`id`),
+  KEY `video_comment_revision_comment_id_fk` (`comment_id`),
+  CONSTRAINT `video_comment_revision_comment_id_fk` FOREIGN KEY (`comment_id`) REFERENCES `video_comment` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Cronologia delle modifiche dei commenti dei video';
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE `time_marker_player`;
+
+ALTER TABLE `time_marker` DROP FOREIGN KEY `time_marker_formation_id_fk`;
+ALTER TABLE `time_marker` DROP FOREIGN KEY `time_marker_team_id_fk`;
+ALTER TABLE `time_marker` DROP COLUMN `formation_id`;
+ALTER TABLE `time_marker` DROP COLUMN `team_id`;
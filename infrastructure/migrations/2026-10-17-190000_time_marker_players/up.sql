@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+-- Squadra e formazione della partita alle quali si riferisce un segnaposto
+ALTER TABLE `time_marker` ADD COLUMN `team_id` BIGINT(20) DEFAULT NULL COMMENT 'Squadra alla quale si riferisce il segnaposto';
+ALTER TABLE `time_marker` ADD COLUMN `formation_id` BIGINT(20) DEFAULT NULL COMMENT 'Formazione della partita del video alla quale si riferisce il segnaposto';
+ALTER TABLE `time_marker` ADD CONSTRAINT `time_marker_team_id_fk` FOREIGN KEY (`team_id`) REFERENCES `team` (`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+ALTER TABLE `time_marker` ADD CONSTRAINT `time_marker_formation_id_fk` FOREIGN KEY (`formation_id`) REFERENCES `formation` (`id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- Giocatori coinvolti nel momento indicato da un segnaposto
+CREATE TABLE `time_marker_player` (
+  `time_marker_id` bigint(20) NOT NULL COMMENT 'Segnaposto che indica il momento',
+  `player_id` bigint(20) NOT NULL COMMENT 'Giocatore coinvolto nel momento',
+  PRIMARY KEY (`time_marker_id`, `player_id`),
+  KEY `time_marker_player_player_id_fk` (`player_id`),
+  CONSTRAINT `time_marker_player_time_marker_id_fk` FOREIGN KEY (`time_marker_id`) REFERENCES `time_marker` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `time_marker_player_player_id_fk` FOREIGN KEY (`player_id`) REFERENCES `player` (`person_id`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Giocatori ai quali si riferiscono i segnaposti dei video';
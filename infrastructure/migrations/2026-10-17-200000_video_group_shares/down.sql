@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE `video_club`;
+DROP TABLE `video_team`;
@@ -0,0 +1,29 @@
+-- Your SQL goes here
+
+-- Condivisioni dei video con le squadre, valide per i loro giocatori e allenatori attuali
+CREATE TABLE `video_team` (
+  `video_id` bigint(20) NOT NULL,
+  `team_id` bigint(20) NOT NULL,
+  `read` tinyint(1) NOT NULL,
+  `edit` tinyint(1) NOT NULL,
+  `delete` tinyint(1) NOT NULL,
+  `share` tinyint(1) NOT NULL,
+  PRIMARY KEY (`video_id`, `team_id`),
+  KEY `video_team_team_id_fk` (`team_id`),
+  CONSTRAINT `video_team_video_id_fk` FOREIGN KEY (`video_id`) REFERENCES `video` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_team_team_id_fk` FOREIGN KEY (`team_id`) REFERENCES `team` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Rappresenta la condivisione di un video con una squadra, valida per i suoi giocatori e allenatori attuali';
+
+-- Condivisioni dei video con le società sportive, valide per i loro responsabili e per i giocatori e gli allenatori attuali delle loro squadre
+CREATE TABLE `video_club` (
+  `video_id` bigint(20) NOT NULL,
+  `club_id` varchar(11) NOT NULL,
+  `read` tinyint(1) NOT NULL,
+  `edit` tinyint(1) NOT NULL,
+  `delete` tinyint(1) NOT NULL,
+  `share` tinyint(1) NOT NULL,
+  PRIMARY KEY (`video_id`, `club_id`),
+  KEY `video_club_club_id_fk` (`club_id`),
+  CONSTRAINT `video_club_video_id_fk` FOREIGN KEY (`video_id`) REFERENCES `video` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_club_club_id_fk` FOREIGN KEY (`club_id`) REFERENCES `sports_club` (`vat_number`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Rappresenta la condivisione di un video con una società sportiva, valida per i suoi responsabili e per i giocatori e gli allenatori attuali delle sue squadre';
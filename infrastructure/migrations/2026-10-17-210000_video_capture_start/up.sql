@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Inizio della registrazione al millisecondo, per allineare i video delle diverse telecamere di una sessione
+ALTER TABLE `video` ADD COLUMN `capture_start` DATETIME(3) DEFAULT NULL COMMENT 'Momento, al millisecondo, nel quale è stata registrata la prima immagine del video' AFTER `date`;
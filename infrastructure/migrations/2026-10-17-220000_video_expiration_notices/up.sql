@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+-- Avvisi della prossima eliminazione dei video da parte delle regole di conservazione
+CREATE TABLE `video_expiration_notice` (
+  `video_id` bigint(20) NOT NULL,
+  `user_id` bigint(20) NOT NULL COMMENT 'Utente avvisato, proprietario del video o con accesso al video tramite una condivisione',
+  `expires_at` datetime NOT NULL COMMENT 'Scadenza del video al momento dell''avviso',
+  `notified_at` datetime NOT NULL COMMENT 'Momento nel quale è stato registrato l''avviso',
+  PRIMARY KEY (`video_id`, `user_id`),
+  KEY `video_expiration_notice_user_id_fk` (`user_id`),
+  CONSTRAINT `video_expiration_notice_video_id_fk` FOREIGN KEY (`video_id`) REFERENCES `video` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT `video_expiration_notice_user_id_fk` FOREIGN KEY (`user_id`) REFERENCES `user` (`person_id`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci COMMENT='Avvisi agli utenti della prossima eliminazione dei video';
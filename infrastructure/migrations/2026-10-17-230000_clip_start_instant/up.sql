@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+ALTER TABLE `clip_video`
+  ADD COLUMN `start_instant` time(3) DEFAULT NULL COMMENT 'Istante del video originale nel quale inizia la clip, NULL per i video di highlight' AFTER `clip_id`;
+
+-- Le clip già estratte iniziano nel video originale nel momento della registrazione della loro prima immagine
+UPDATE `clip_video`
+  JOIN `video` AS `clip` ON `clip`.`id` = `clip_video`.`clip_id`
+  JOIN `video` AS `original` ON `original`.`id` = `clip_video`.`original_video_id`
+SET `clip_video`.`start_instant` = COALESCE(
+  TIMEDIFF(`clip`.`capture_start`, `original`.`capture_start`),
+  TIMEDIFF(`clip`.`date`, `original`.`date`)
+)
+WHERE `clip_video`.`clip_id` NOT IN (
+  SELECT `video_id` FROM `highlight_job` WHERE `video_id` IS NOT NULL
+);
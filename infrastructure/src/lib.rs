@@ -1,8 +1,14 @@
 use std::env;
+use std::path::PathBuf;
 
 use diesel::{Connection, MysqlConnection};
 use dotenvy::dotenv;
 
+pub mod storage;
+
+/// Cartella predefinita nella quale vengono salvati i file multimediali
+const DEFAULT_MEDIA_ROOT: &str = "./infrastructure/media";
+
 /// Stabilisce la connessione al database
 pub fn establish_connection() -> MysqlConnection {
     dotenv().ok();
@@ -11,3 +17,15 @@ pub fn establish_connection() -> MysqlConnection {
     MysqlConnection::establish(&database_url)
         .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
 }
+
+/// Restituisce la cartella nella quale vengono salvati i file multimediali (registrazioni, screenshot, ...)
+///
+/// La cartella può essere configurata con la variabile d'ambiente `MEDIA_ROOT`.
+pub fn media_root() -> PathBuf {
+    dotenv().ok();
+
+    match env::var("MEDIA_ROOT") {
+        Ok(media_root) => PathBuf::from(media_root),
+        Err(_) => PathBuf::from(DEFAULT_MEDIA_ROOT),
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{Storage, StorageError};
+
+/// Archivio nella cartella dei file multimediali sul disco del server.
+///
+/// La chiave di ogni oggetto è il suo percorso relativo alla cartella.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> LocalStorage {
+        return LocalStorage { root };
+    }
+
+    // Aggiunge alla lista le chiavi dei file contenuti in `dir` e nelle sue sottocartelle
+    fn collect_keys(&self, dir: &Path, keys: &mut Vec<String>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                self.collect_keys(&path, keys)?;
+            } else if let Ok(relative) = path.strip_prefix(&self.root) {
+                let key = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                keys.push(key);
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put(&self, key: &str, source: &Path) -> Result<(), StorageError> {
+        let destination = self.root.join(key);
+
+        // Il file è stato preparato direttamente nella sua posizione finale
+        if destination == source {
+            return Ok(());
+        }
+
+        if let Some(dir) = destination.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                StorageError::Io(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+        }
+
+        // Tra due dischi diversi il file non può essere rinominato e viene copiato
+        if fs::rename(source, &destination).is_err() {
+            fs::copy(source, &destination).map_err(|e| {
+                StorageError::Io(format!(
+                    "Failed to copy {} to {}: {}",
+                    source.display(),
+                    destination.display(),
+                    e
+                ))
+            })?;
+            let _ = fs::remove_file(source);
+        }
+
+        return Ok(());
+    }
+
+    fn get(&self, key: &str, destination: &mut dyn Write) -> Result<(), StorageError> {
+        let mut file = match File::open(self.root.join(key)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(StorageError::NotFound(key.to_string()))
+            }
+            Err(e) => return Err(StorageError::Io(format!("Failed to open {}: {}", key, e))),
+        };
+
+        io::copy(&mut file, destination)
+            .map_err(|e| StorageError::Io(format!("Failed to read {}: {}", key, e)))?;
+
+        return Ok(());
+    }
+
+    fn size(&self, key: &str) -> Result<u64, StorageError> {
+        match fs::metadata(self.root.join(key)) {
+            Ok(metadata) => return Ok(metadata.len()),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(StorageError::NotFound(key.to_string()))
+            }
+            Err(e) => return Err(StorageError::Io(format!("Failed to read {}: {}", key, e))),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.root.join(key);
+
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(StorageError::Io(format!("Failed to remove {}: {}", key, e))),
+        }
+
+        // Le cartelle rimaste vuote vengono rimosse, fino alla cartella dei file multimediali
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if current == self.root || fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+
+        return Ok(());
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        // La ricerca parte dalla cartella più interna indicata dal prefisso
+        let dir = match prefix.rfind('/') {
+            Some(end) => self.root.join(&prefix[..end]),
+            None => self.root.clone(),
+        };
+
+        let mut keys = Vec::new();
+        match self.collect_keys(&dir, &mut keys) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(keys),
+            Err(e) => {
+                return Err(StorageError::Io(format!(
+                    "Failed to list {}: {}",
+                    prefix, e
+                )))
+            }
+        }
+
+        keys.retain(|key| key.starts_with(prefix));
+        keys.sort();
+
+        return Ok(keys);
+    }
+
+    fn presign(
+        &self,
+        _key: &str,
+        _expires_in: Duration,
+        _content_disposition: Option<&str>,
+    ) -> Result<Option<String>, StorageError> {
+        // I file sul disco vengono inviati dal server stesso
+        return Ok(None);
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        return Some(self.root.join(key));
+    }
+
+    fn check(&self) -> Result<(), StorageError> {
+        let probe = self.root.join(".storage-check");
+
+        // Scrivere un file verifica che la cartella esista o possa essere creata e che sia scrivibile
+        fs::create_dir_all(&self.root)
+            .and_then(|()| fs::write(&probe, b""))
+            .and_then(|()| fs::remove_file(&probe))
+            .map_err(|e| {
+                StorageError::Io(format!("Failed to write in {}: {}", self.root.display(), e))
+            })?;
+
+        return Ok(());
+    }
+}
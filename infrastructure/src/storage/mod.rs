@@ -0,0 +1,243 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use dotenvy::dotenv;
+
+use crate::media_root;
+
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Durata predefinita in secondi degli URL firmati generati dall'archivio
+const DEFAULT_PRESIGN_SECONDS: u64 = 3600;
+
+/// Errore di un'operazione sull'archivio dei file multimediali
+#[derive(Debug)]
+pub enum StorageError {
+    /// L'oggetto richiesto non esiste
+    NotFound(String),
+    /// Errore di lettura o scrittura su disco
+    Io(String),
+    /// Errore dell'archivio remoto
+    Remote(String),
+    /// Configurazione dell'archivio non valida
+    Config(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NotFound(key) => write!(f, "Object {} not found", key),
+            StorageError::Io(message) => write!(f, "I/O error - {}", message),
+            StorageError::Remote(message) => write!(f, "Remote storage error - {}", message),
+            StorageError::Config(message) => {
+                write!(f, "Invalid storage configuration - {}", message)
+            }
+        }
+    }
+}
+
+/// Archivio nel quale vengono salvati i file multimediali (registrazioni, screenshot, clip, anteprime, ...).
+///
+/// Gli oggetti sono identificati da una chiave relativa all'archivio, con le cartelle separate da `/`,
+/// ad esempio `screenshots/video_1/20261017-120000000.jpg`. Nel database vengono salvate le chiavi
+/// e non i percorsi sul disco, così che l'archivio possa essere spostato o sostituito.
+pub trait Storage: Send + Sync {
+    /// Salva nell'archivio il file locale `source` con la chiave `key`, sostituendo l'oggetto esistente.
+    ///
+    /// Il file locale viene spostato nell'archivio e non è più disponibile al termine dell'operazione.
+    fn put(&self, key: &str, source: &Path) -> Result<(), StorageError>;
+
+    /// Scrive in `destination` il contenuto dell'oggetto con la chiave `key`.
+    fn get(&self, key: &str, destination: &mut dyn Write) -> Result<(), StorageError>;
+
+    /// Restituisce la dimensione in byte dell'oggetto con la chiave `key`.
+    fn size(&self, key: &str) -> Result<u64, StorageError>;
+
+    /// Elimina l'oggetto con la chiave `key`. Eliminare un oggetto che non esiste non è un errore.
+    fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Restituisce le chiavi degli oggetti che iniziano con `prefix`.
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError>;
+
+    /// Restituisce le chiavi degli oggetti che iniziano con `prefix`, insieme alla loro dimensione in byte.
+    fn list_sizes(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError> {
+        return self
+            .list(prefix)?
+            .into_iter()
+            .map(|key| self.size(&key).map(|size| (key, size)))
+            .collect();
+    }
+
+    /// Restituisce un URL firmato per leggere l'oggetto senza credenziali fino alla sua scadenza,
+    /// o None se l'archivio non è raggiungibile tramite HTTP e l'oggetto va letto con `local_path` o `get`.
+    ///
+    /// Con `content_disposition` l'archivio invia l'header `Content-Disposition` indicato.
+    fn presign(
+        &self,
+        key: &str,
+        expires_in: Duration,
+        content_disposition: Option<&str>,
+    ) -> Result<Option<String>, StorageError>;
+
+    /// Restituisce il percorso sul disco dell'oggetto, se l'archivio è locale.
+    fn local_path(&self, key: &str) -> Option<PathBuf>;
+
+    /// Verifica che l'archivio sia raggiungibile e che sia possibile salvarvi dei file.
+    fn check(&self) -> Result<(), StorageError>;
+}
+
+static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+/// Crea e verifica l'archivio dei file multimediali configurato (vedi `storage`).
+///
+/// Viene chiamata all'avvio del server, così che una configurazione non valida o un archivio
+/// non raggiungibile impediscano l'avvio invece di far fallire le singole richieste.
+pub fn init_storage() -> Result<(), StorageError> {
+    presign_seconds()?;
+
+    if STORAGE.get().is_none() {
+        let _ = STORAGE.set(create_storage()?);
+    }
+
+    return storage().check();
+}
+
+fn create_storage() -> Result<Box<dyn Storage>, StorageError> {
+    dotenv().ok();
+
+    match env::var("STORAGE_DRIVER").as_deref() {
+        Ok("s3") => return Ok(Box::new(S3Storage::from_env()?)),
+        Ok("local") | Err(_) => return Ok(Box::new(LocalStorage::new(media_root()))),
+        Ok(driver) => {
+            return Err(StorageError::Config(format!(
+                "unknown STORAGE_DRIVER {}, use local or s3",
+                driver
+            )))
+        }
+    }
+}
+
+/// Restituisce l'archivio dei file multimediali configurato.
+///
+/// L'archivio viene scelto con la variabile d'ambiente `STORAGE_DRIVER`:
+/// - `local` (predefinito): i file vengono salvati nella cartella dei file multimediali (`MEDIA_ROOT`);
+/// - `s3`: i file vengono salvati in un bucket di un servizio compatibile con S3 (AWS S3, MinIO, ...),
+///   configurato con le variabili `S3_ENDPOINT`, `S3_BUCKET`, `S3_REGION`, `S3_ACCESS_KEY` e `S3_SECRET_KEY`.
+///
+/// La configurazione viene verificata all'avvio del server da `init_storage`.
+pub fn storage() -> &'static dyn Storage {
+    return STORAGE
+        .get_or_init(|| create_storage().unwrap_or_else(|e| panic!("{}", e)))
+        .as_ref();
+}
+
+/// Restituisce il percorso sul disco nel quale viene preparato il file con la chiave `key`
+/// prima di essere salvato nell'archivio.
+///
+/// Con l'archivio locale coincide con il percorso dell'oggetto, e salvarlo non richiede di copiarlo.
+pub fn working_path(key: &str) -> PathBuf {
+    return media_root().join(key);
+}
+
+/// Restituisce la posizione dalla quale FFmpeg e FFprobe possono leggere l'oggetto:
+/// il suo percorso sul disco o un suo URL firmato.
+pub fn read_location(key: &str) -> Result<String, StorageError> {
+    let storage = storage();
+
+    if let Some(path) = storage.local_path(key) {
+        return Ok(path.to_string_lossy().to_string());
+    }
+
+    match storage.presign(key, presign_duration(), None)? {
+        Some(url) => return Ok(url),
+        None => return Err(StorageError::NotFound(key.to_string())),
+    }
+}
+
+/// Restituisce il contenuto dell'oggetto con la chiave `key`, per i file di piccole dimensioni.
+pub fn read(key: &str) -> Result<Vec<u8>, StorageError> {
+    let mut content = Vec::new();
+    storage().get(key, &mut content)?;
+
+    return Ok(content);
+}
+
+/// Salva nell'archivio tutti i file della cartella locale `dir` nella cartella `prefix` dell'archivio,
+/// e restituisce le chiavi salvate.
+///
+/// La cartella viene rimossa se al termine è vuota.
+pub fn put_dir(prefix: &str, dir: &Path) -> Result<Vec<String>, StorageError> {
+    let storage = storage();
+
+    let entries = fs::read_dir(dir)
+        .map_err(|e| StorageError::Io(format!("Failed to read {}: {}", dir.display(), e)))?;
+
+    let mut keys = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| StorageError::Io(format!("Failed to read {}: {}", dir.display(), e)))?
+            .path();
+
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if path.is_file() => name.to_string(),
+            _ => continue,
+        };
+
+        let key = format!("{}/{}", prefix, name);
+        storage.put(&key, &path)?;
+        keys.push(key);
+    }
+
+    // Con l'archivio locale la cartella contiene gli oggetti salvati e non viene rimossa
+    let _ = fs::remove_dir(dir);
+
+    return Ok(keys);
+}
+
+/// Elimina tutti gli oggetti della cartella `prefix` dell'archivio.
+pub fn delete_dir(prefix: &str) -> Result<(), StorageError> {
+    let storage = storage();
+
+    // Senza la barra finale verrebbero eliminate anche le cartelle con lo stesso inizio (`video_1` e `video_10`)
+    for key in storage.list(&format!("{}/", prefix))? {
+        storage.delete(&key)?;
+    }
+
+    return Ok(());
+}
+
+/// Restituisce la durata degli URL firmati generati dall'archivio.
+///
+/// La durata può essere configurata con la variabile d'ambiente `STORAGE_PRESIGN_SECONDS`.
+/// Se non è valida, come verificato all'avvio da `init_storage`, viene usata la durata predefinita.
+pub fn presign_duration() -> Duration {
+    return Duration::from_secs(presign_seconds().unwrap_or(DEFAULT_PRESIGN_SECONDS));
+}
+
+fn presign_seconds() -> Result<u64, StorageError> {
+    dotenv().ok();
+
+    match env::var("STORAGE_PRESIGN_SECONDS") {
+        Ok(seconds) => {
+            return seconds
+                .parse::<u64>()
+                .ok()
+                .filter(|seconds| *seconds > 0)
+                .ok_or_else(|| {
+                    StorageError::Config(
+                        "STORAGE_PRESIGN_SECONDS must be a positive number".to_string(),
+                    )
+                })
+        }
+        Err(_) => return Ok(DEFAULT_PRESIGN_SECONDS),
+    }
+}
@@ -45,3 +45,16 @@ pub fn is_future_datetime(datetime: &NaiveDateTime) -> Result<(), ValidationErro
             .with_message(Cow::Borrowed("The datetime must be in the future")))
     }
 }
+
+pub fn is_valid_color(color: &str) -> Result<(), ValidationError> {
+    let re = Regex::new(r"^#([0-9a-fA-F]{6}|[0-9a-fA-F]{8})$").unwrap();
+    if re.is_match(color) {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("invalid_color").with_message(Cow::Borrowed(
+                "The color must be in the format #RRGGBB or #RRGGBBAA",
+            )),
+        )
+    }
+}